@@ -0,0 +1,53 @@
+const COLOR_ENTRY: u16 = 0x0001;
+const GLOBAL_COLOR: u16 = 0;
+
+pub(crate) fn encode(swatches: &[(String, [f32; 3])]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(b"ASEF");
+    out.extend_from_slice(&1u16.to_be_bytes());
+    out.extend_from_slice(&0u16.to_be_bytes());
+    out.extend_from_slice(&(swatches.len() as u32).to_be_bytes());
+
+    for (name, rgb) in swatches {
+        let name: Vec<u16> = name.encode_utf16().chain([0]).collect();
+        let mut block = Vec::new();
+        block.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        for unit in &name {
+            block.extend_from_slice(&unit.to_be_bytes());
+        }
+        block.extend_from_slice(b"RGB ");
+        for c in rgb {
+            block.extend_from_slice(&c.clamp(0.0, 1.0).to_be_bytes());
+        }
+        block.extend_from_slice(&GLOBAL_COLOR.to_be_bytes());
+
+        out.extend_from_slice(&COLOR_ENTRY.to_be_bytes());
+        out.extend_from_slice(&(block.len() as u32).to_be_bytes());
+        out.extend_from_slice(&block);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_header_and_color_entries() {
+        let bytes = encode(&[("#FF0000".to_string(), [1.0, 0.0, 0.0])]);
+
+        assert_eq!(&bytes[..4], b"ASEF");
+        assert_eq!(&bytes[4..8], &[0, 1, 0, 0]);
+        assert_eq!(&bytes[8..12], &1u32.to_be_bytes());
+        assert_eq!(&bytes[12..14], &COLOR_ENTRY.to_be_bytes());
+
+        // name length + 8 UTF-16 units + model + 3 floats + color type
+        let block_len = 2 + 8 * 2 + 4 + 3 * 4 + 2;
+        assert_eq!(&bytes[14..18], &(block_len as u32).to_be_bytes());
+        assert_eq!(bytes.len(), 18 + block_len);
+        assert_eq!(&bytes[18..20], &8u16.to_be_bytes());
+        assert_eq!(&bytes[20..22], &[0, b'#']);
+        assert_eq!(&bytes[36..40], b"RGB ");
+        assert_eq!(&bytes[40..44], &1.0f32.to_be_bytes());
+    }
+}
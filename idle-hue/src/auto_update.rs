@@ -6,14 +6,24 @@ use app_update_client::VERSION_SERVER_PROD;
 use app_update_client::{VersionServerAppClient, VersionServerClient};
 use semver::Version;
 use std::env;
+use std::fs;
 use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
 
 const APP_NAME: &str = "idle-hue";
 
+pub const INSTALL_UPDATE_ARG: &str = "--install-update";
+// The helper's exit code when there was nothing newer to install.
+const HELPER_UP_TO_DATE: i32 = 2;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum UpdateStatus {
     Idle,
     Checking,
+    // `elevate` when installing it will ask for administrator rights.
+    Available { version: Version, elevate: bool },
     UpToDate { version: Version },
     Downloading { version: Version },
     Installing { version: Version },
@@ -24,62 +34,141 @@ pub enum UpdateStatus {
 #[derive(Clone)]
 pub struct AutoUpdater {
     updater: AppUpdater<VersionServerAppClient>,
+    elevated: bool,
+    background: bool,
+}
+
+enum PreflightError {
+    ReadOnly(PathBuf),
+    Failed(String),
+}
+
+impl std::fmt::Display for PreflightError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreflightError::ReadOnly(dir) => write!(f, "can't write to {}", dir.display()),
+            PreflightError::Failed(message) => f.write_str(message),
+        }
+    }
 }
 
 impl AutoUpdater {
     pub fn new() -> Self {
-        let current_version =
-            Version::parse(env!("CARGO_PKG_VERSION")).unwrap_or_else(|_| Version::new(0, 1, 0));
-
         #[cfg(feature = "prod")]
         let base_url = VERSION_SERVER_PROD;
 
         #[cfg(not(feature = "prod"))]
         let base_url = VERSION_SERVER_DEV;
 
-        let config = UpdateConfig::new(current_version)
+        let config = UpdateConfig::new(current_version())
             .expect("idle-hue updates are only supported on macOS and Windows");
         let api = VersionServerClient::new(base_url).for_app(APP_NAME);
 
         Self {
             updater: AppUpdater::new(config, api),
+            elevated: false,
+            background: false,
+        }
+    }
+
+    pub async fn install_as_helper(result: Option<&str>) -> i32 {
+        let last = Arc::new(Mutex::new(UpdateStatus::Idle));
+        let updater = Self {
+            elevated: true,
+            ..Self::new()
+        };
+        updater
+            .check_and_install_updates_with_callback(Some({
+                let last = last.clone();
+                move |status: UpdateStatus| {
+                    let last = last.clone();
+                    async move {
+                        if status != UpdateStatus::Idle {
+                            *last.lock().unwrap_or_else(|e| e.into_inner()) = status;
+                        }
+                    }
+                }
+            }))
+            .await;
+        let last = last.lock().unwrap_or_else(|e| e.into_inner());
+        match &*last {
+            UpdateStatus::Updated { version } => match result {
+                Some(result) if fs::write(result, version.to_string()).is_ok() => 0,
+                _ => 1,
+            },
+            UpdateStatus::UpToDate { .. } => HELPER_UP_TO_DATE,
+            _ => 1,
         }
     }
 
+    pub fn in_background(mut self) -> Self {
+        self.background = true;
+        self
+    }
+
     pub async fn check_and_install_updates_with_callback<F, Fut>(&self, status_callback: Option<F>)
     where
         F: Fn(UpdateStatus) -> Fut + Send + Sync + Clone,
         Fut: Future<Output = ()> + Send,
     {
-        let result = self
-            .updater
-            .update_with_status({
+        // Why the check stopped before downloading, if it did.
+        let stop = Arc::new((Mutex::new(None), Notify::new()));
+        let (background, elevated) = (self.background, self.elevated);
+        let update = self.updater.update_with_status({
+            let status_callback = status_callback.clone();
+            let stop = stop.clone();
+            move |status| {
                 let status_callback = status_callback.clone();
-                move |status| {
-                    let status_callback = status_callback.clone();
-                    async move {
+                let stop = stop.clone();
+                async move {
+                    if let AppUpdateStatus::Downloading { version } = &status
+                        && let Some(status) = before_download(background, elevated, version)
+                    {
+                        *stop.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(status);
+                        stop.1.notify_one();
+                        // Dropped by the select below, so nothing gets downloaded.
+                        std::future::pending::<()>().await;
+                    }
+                    if let Some(callback) = status_callback {
+                        let status = match status {
+                            AppUpdateStatus::Checking => UpdateStatus::Checking,
+                            AppUpdateStatus::UpToDate { version } => {
+                                UpdateStatus::UpToDate { version }
+                            }
+                            AppUpdateStatus::Downloading { version } => {
+                                UpdateStatus::Downloading { version }
+                            }
+                            AppUpdateStatus::Installing { version } => {
+                                UpdateStatus::Installing { version }
+                            }
+                            AppUpdateStatus::Updated { version } => {
+                                UpdateStatus::Updated { version }
+                            }
+                        };
+                        callback(status).await;
+                    }
+                }
+            }
+        });
+        let result = tokio::select! {
+            result = update => result,
+            () = stop.1.notified() => {
+                let stopped = stop.0.lock().unwrap_or_else(|e| e.into_inner()).take();
+                match stopped {
+                    Some(Stop::Report(UpdateStatus::Error(message))) => {
+                        report_error(status_callback, message).await
+                    }
+                    Some(Stop::Report(status)) => {
                         if let Some(callback) = status_callback {
-                            let status = match status {
-                                AppUpdateStatus::Checking => UpdateStatus::Checking,
-                                AppUpdateStatus::UpToDate { version } => {
-                                    UpdateStatus::UpToDate { version }
-                                }
-                                AppUpdateStatus::Downloading { version } => {
-                                    UpdateStatus::Downloading { version }
-                                }
-                                AppUpdateStatus::Installing { version } => {
-                                    UpdateStatus::Installing { version }
-                                }
-                                AppUpdateStatus::Updated { version } => {
-                                    UpdateStatus::Updated { version }
-                                }
-                            };
                             callback(status).await;
                         }
                     }
+                    Some(Stop::Elevate(version)) => elevate(status_callback, version).await,
+                    None => {}
                 }
-            })
-            .await;
+                return;
+            }
+        };
 
         match result {
             Ok(UpdateOutcome::UpToDate { .. }) => {
@@ -89,13 +178,223 @@ impl AutoUpdater {
                 }
             }
             Ok(UpdateOutcome::Updated { .. }) => {}
-            Err(error) => {
-                if let Some(callback) = status_callback {
-                    callback(UpdateStatus::Error(error.to_string())).await;
-                    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                    callback(UpdateStatus::Idle).await;
-                }
+            Err(error) => report_error(status_callback, error.to_string()).await,
+        }
+    }
+}
+
+fn current_version() -> Version {
+    Version::parse(env!("CARGO_PKG_VERSION")).unwrap_or_else(|_| Version::new(0, 1, 0))
+}
+
+enum Stop {
+    Report(UpdateStatus),
+    Elevate(Version),
+}
+
+// Runs once a newer release is found, so nothing is checked, and nobody is asked for
+// administrator rights, when there's nothing to install. Background checks only offer it.
+fn before_download(background: bool, elevated: bool, version: &Version) -> Option<Stop> {
+    let preflight = preflight();
+    let needs_elevation = matches!(preflight, Err(PreflightError::ReadOnly(_)))
+        && cfg!(target_os = "windows")
+        && !elevated;
+    if background {
+        return Some(Stop::Report(UpdateStatus::Available {
+            version: version.clone(),
+            elevate: needs_elevation,
+        }));
+    }
+    match preflight {
+        Ok(()) => None,
+        Err(_) if needs_elevation => Some(Stop::Elevate(version.clone())),
+        Err(e) => Some(Stop::Report(UpdateStatus::Error(e.to_string()))),
+    }
+}
+
+async fn elevate<F, Fut>(status_callback: Option<F>, version: Version)
+where
+    F: Fn(UpdateStatus) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    if let Some(callback) = &status_callback {
+        callback(UpdateStatus::Installing { version }).await;
+    }
+    match tokio::task::spawn_blocking(install_elevated).await {
+        Ok(Ok(Some(version))) => {
+            if let Some(callback) = status_callback {
+                callback(UpdateStatus::Updated { version }).await;
+            }
+        }
+        Ok(Ok(None)) => {
+            if let Some(callback) = status_callback {
+                callback(UpdateStatus::UpToDate {
+                    version: current_version(),
+                })
+                .await;
+                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+                callback(UpdateStatus::Idle).await;
+            }
+        }
+        Ok(Err(e)) => report_error(status_callback, e).await,
+        Err(e) => report_error(status_callback, e.to_string()).await,
+    }
+}
+
+async fn report_error<F, Fut>(status_callback: Option<F>, message: String)
+where
+    F: Fn(UpdateStatus) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    if let Some(callback) = status_callback {
+        callback(UpdateStatus::Error(message)).await;
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+        callback(UpdateStatus::Idle).await;
+    }
+}
+
+// The release's size isn't known until it's downloaded, so the installed copy stands in.
+fn preflight() -> Result<(), PreflightError> {
+    let exe = env::current_exe()
+        .map_err(|e| PreflightError::Failed(format!("can't find idle-hue: {e}")))?;
+    let target = install_target(&exe);
+    let install_dir = target.parent().ok_or_else(|| {
+        PreflightError::Failed(format!("can't update idle-hue at {}", target.display()))
+    })?;
+    let probe = install_dir.join(".idle-hue-update-check");
+    fs::write(&probe, b"")
+        .and_then(|()| fs::remove_file(&probe))
+        .map_err(|_| PreflightError::ReadOnly(install_dir.to_path_buf()))?;
+
+    let size = disk_usage(&target);
+    let temp = env::temp_dir();
+    check_space(
+        &temp,
+        size * 2,
+        fs2::available_space(&temp).unwrap_or(u64::MAX),
+    )?;
+    check_space(
+        install_dir,
+        size,
+        fs2::available_space(install_dir).unwrap_or(u64::MAX),
+    )
+}
+
+fn install_target(exe: &Path) -> PathBuf {
+    exe.ancestors()
+        .find(|path| path.extension().is_some_and(|extension| extension == "app"))
+        .unwrap_or(exe)
+        .to_path_buf()
+}
+
+fn disk_usage(path: &Path) -> u64 {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| disk_usage(&entry.path()))
+                    .sum()
+            })
+            .unwrap_or(0),
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    }
+}
+
+fn check_space(location: &Path, needed: u64, available: u64) -> Result<(), PreflightError> {
+    if available >= needed {
+        return Ok(());
+    }
+    let megabytes = needed.div_ceil(1024 * 1024);
+    Err(PreflightError::Failed(format!(
+        "need {megabytes} MB free in {} to update",
+        location.display()
+    )))
+}
+
+fn install_elevated() -> Result<Option<Version>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::ffi::OsStrExt;
+        use windows_sys::Win32::Foundation::CloseHandle;
+        use windows_sys::Win32::System::Threading::{
+            GetExitCodeProcess, INFINITE, WaitForSingleObject,
+        };
+        use windows_sys::Win32::UI::Shell::{
+            SEE_MASK_NOCLOSEPROCESS, SHELLEXECUTEINFOW, ShellExecuteExW,
+        };
+        use windows_sys::Win32::UI::WindowsAndMessaging::SW_HIDE;
+
+        let exe = env::current_exe().map_err(|e| format!("can't find idle-hue: {e}"))?;
+        let file: Vec<u16> = exe.as_os_str().encode_wide().chain([0]).collect();
+        let verb: Vec<u16> = "runas".encode_utf16().chain([0]).collect();
+        let result = env::temp_dir().join(format!("idle-hue-update-{}", std::process::id()));
+        let parameters = format!("{INSTALL_UPDATE_ARG} \"{}\"", result.display());
+        let parameters: Vec<u16> = parameters.encode_utf16().chain([0]).collect();
+        let mut info: SHELLEXECUTEINFOW = unsafe { std::mem::zeroed() };
+        info.cbSize = std::mem::size_of::<SHELLEXECUTEINFOW>() as u32;
+        info.fMask = SEE_MASK_NOCLOSEPROCESS;
+        info.lpVerb = verb.as_ptr();
+        info.lpFile = file.as_ptr();
+        info.lpParameters = parameters.as_ptr();
+        info.nShow = SW_HIDE;
+        let mut exit_code = 1;
+        unsafe {
+            // Fails when the UAC prompt is declined.
+            if ShellExecuteExW(&mut info) == 0 || info.hProcess.is_null() {
+                return Err("updating needs administrator rights".to_string());
             }
+            WaitForSingleObject(info.hProcess, INFINITE);
+            GetExitCodeProcess(info.hProcess, &mut exit_code);
+            CloseHandle(info.hProcess);
         }
+        let installed = fs::read_to_string(&result);
+        let _ = fs::remove_file(&result);
+        match exit_code as i32 {
+            0 => installed
+                .ok()
+                .and_then(|version| Version::parse(version.trim()).ok())
+                .map(Some)
+                .ok_or_else(|| "the update couldn't be installed".to_string()),
+            HELPER_UP_TO_DATE => Ok(None),
+            _ => Err("the update couldn't be installed".to_string()),
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    Err("updating needs administrator rights".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preflight_finds_the_bundle_and_reports_missing_space() {
+        let exe = Path::new("/Applications/idle-hue.app/Contents/MacOS/idle-hue");
+        assert_eq!(install_target(exe), Path::new("/Applications/idle-hue.app"));
+        let exe = Path::new("/opt/idle-hue/idle-hue.exe");
+        assert_eq!(install_target(exe), exe);
+
+        let temp = Path::new("/tmp");
+        assert!(check_space(temp, 10, 10).is_ok());
+        assert_eq!(
+            check_space(temp, 3 * 1024 * 1024 + 1, 1024)
+                .unwrap_err()
+                .to_string(),
+            "need 4 MB free in /tmp to update"
+        );
+    }
+
+    #[test]
+    fn background_checks_stop_to_offer_updates() {
+        let version = Version::new(2, 0, 0);
+        assert!(matches!(
+            before_download(true, false, &version),
+            Some(Stop::Report(UpdateStatus::Available { version: offered, elevate: false }))
+                if offered == version
+        ));
+        assert!(before_download(false, false, &version).is_none());
     }
 }
@@ -0,0 +1,52 @@
+/// Below this chroma a color's hue is meaningless, so blends take the other end's hue.
+const ACHROMATIC: f32 = 0.02;
+
+/// Returns `steps` colors evenly spaced in OKLCH strictly between `from` and `to`,
+/// taking the shorter way around the hue circle.
+pub(crate) fn interpolate(from: [f32; 3], to: [f32; 3], steps: usize) -> Vec<[f32; 3]> {
+    (1..=steps)
+        .map(|i| mix(from, to, i as f32 / (steps + 1) as f32))
+        .collect()
+}
+
+pub(crate) fn mix(from: [f32; 3], to: [f32; 3], t: f32) -> [f32; 3] {
+    let (mut from_hue, mut to_hue) = (from[2], to[2]);
+    if from[1] < ACHROMATIC {
+        from_hue = to_hue;
+    } else if to[1] < ACHROMATIC {
+        to_hue = from_hue;
+    }
+    let mut hue_delta = (to_hue - from_hue).rem_euclid(360.0);
+    if hue_delta > 180.0 {
+        hue_delta -= 360.0;
+    }
+    [
+        from[0] + (to[0] - from[0]) * t,
+        from[1] + (to[1] - from[1]) * t,
+        (from_hue + hue_delta * t).rem_euclid(360.0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_are_even_and_take_the_short_hue_arc() {
+        let steps = interpolate([0.2, 0.1, 350.0], [0.8, 0.2, 30.0], 3);
+
+        assert_eq!(steps.len(), 3);
+        let expected = [[0.35, 0.125, 0.0], [0.5, 0.15, 10.0], [0.65, 0.175, 20.0]];
+        for (step, want) in steps.iter().zip(expected) {
+            for (a, b) in step.iter().zip(want) {
+                assert!((a - b).abs() < 1e-4, "{step:?} != {want:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn grays_borrow_the_other_hue() {
+        let steps = interpolate([0.5, 0.0, 0.0], [0.7, 0.2, 120.0], 1);
+        assert!((steps[0][2] - 120.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,85 @@
+/// Separable blend modes from the CSS compositing spec, applied to gamma-encoded sRGB
+/// the way browsers apply `mix-blend-mode`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+}
+
+impl BlendMode {
+    pub(crate) const ALL: [BlendMode; 4] = [
+        BlendMode::Normal,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Overlay,
+    ];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            BlendMode::Normal => "normal",
+            BlendMode::Multiply => "multiply",
+            BlendMode::Screen => "screen",
+            BlendMode::Overlay => "overlay",
+        }
+    }
+
+    fn channel(self, backdrop: f32, source: f32) -> f32 {
+        match self {
+            BlendMode::Normal => source,
+            BlendMode::Multiply => backdrop * source,
+            BlendMode::Screen => backdrop + source - backdrop * source,
+            BlendMode::Overlay if backdrop <= 0.5 => 2.0 * backdrop * source,
+            BlendMode::Overlay => {
+                let screen = 2.0 * backdrop - 1.0;
+                screen + source - screen * source
+            }
+        }
+    }
+
+    pub(crate) fn composite(self, source: [f32; 3], alpha: f32, backdrop: [f32; 3]) -> [f32; 3] {
+        let alpha = alpha.clamp(0.0, 1.0);
+        std::array::from_fn(|i| {
+            let (backdrop, source) = (backdrop[i].clamp(0.0, 1.0), source[i].clamp(0.0, 1.0));
+            alpha * self.channel(backdrop, source) + (1.0 - alpha) * backdrop
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composites_over_an_opaque_backdrop() {
+        let source = [1.0, 0.5, 0.0];
+        let backdrop = [0.5, 0.5, 1.0];
+        let close = |a: [f32; 3], b: [f32; 3]| {
+            assert!(
+                a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-6),
+                "{a:?} != {b:?}"
+            );
+        };
+
+        close(BlendMode::Normal.composite(source, 1.0, backdrop), source);
+        close(
+            BlendMode::Multiply.composite(source, 1.0, backdrop),
+            [0.5, 0.25, 0.0],
+        );
+        close(
+            BlendMode::Screen.composite(source, 1.0, backdrop),
+            [1.0, 0.75, 1.0],
+        );
+        close(
+            BlendMode::Overlay.composite(source, 1.0, backdrop),
+            [1.0, 0.5, 1.0],
+        );
+        close(
+            BlendMode::Multiply.composite(source, 0.5, backdrop),
+            [0.5, 0.375, 0.5],
+        );
+        close(BlendMode::Screen.composite(source, 0.0, backdrop), backdrop);
+    }
+}
@@ -0,0 +1,465 @@
+use color::{AlphaColor, ColorSpace, ColorSpaceTag, Hwb, Lab, Lch, LinearSrgb, Oklab, Oklch, Srgb};
+
+/// The most components any mode has (CMYK); modes with fewer leave the rest at zero.
+pub(crate) const MAX_CHANNELS: usize = 4;
+
+pub(crate) type Components = [f32; MAX_CHANNELS];
+
+pub(crate) struct Channel {
+    pub(crate) label: &'static str,
+    pub(crate) min: f32,
+    pub(crate) max: f32,
+}
+
+pub(crate) const OKLCH_CHANNELS: [Channel; 3] = [
+    Channel {
+        label: "L",
+        min: 0.0,
+        max: 1.0,
+    },
+    Channel {
+        label: "C",
+        min: 0.0,
+        max: 0.4,
+    },
+    Channel {
+        label: "H",
+        min: 0.0,
+        max: 360.0,
+    },
+];
+
+const HSV_CHANNELS: [Channel; 3] = [
+    Channel {
+        label: "H",
+        min: 0.0,
+        max: 360.0,
+    },
+    Channel {
+        label: "S",
+        min: 0.0,
+        max: 100.0,
+    },
+    Channel {
+        label: "V",
+        min: 0.0,
+        max: 100.0,
+    },
+];
+
+const OKLAB_CHANNELS: [Channel; 3] = [
+    Channel {
+        label: "L",
+        min: 0.0,
+        max: 1.0,
+    },
+    Channel {
+        label: "a",
+        min: -0.4,
+        max: 0.4,
+    },
+    Channel {
+        label: "b",
+        min: -0.4,
+        max: 0.4,
+    },
+];
+
+const LAB_CHANNELS: [Channel; 3] = [
+    Channel {
+        label: "L",
+        min: 0.0,
+        max: 100.0,
+    },
+    Channel {
+        label: "a",
+        min: -125.0,
+        max: 125.0,
+    },
+    Channel {
+        label: "b",
+        min: -125.0,
+        max: 125.0,
+    },
+];
+
+const LCH_CHANNELS: [Channel; 3] = [
+    Channel {
+        label: "L",
+        min: 0.0,
+        max: 100.0,
+    },
+    Channel {
+        label: "C",
+        min: 0.0,
+        max: 150.0,
+    },
+    Channel {
+        label: "H",
+        min: 0.0,
+        max: 360.0,
+    },
+];
+
+/// Linear-light sRGB, before the transfer curve: what shaders and game engines expect.
+const LINEAR_CHANNELS: [Channel; 3] = [
+    Channel {
+        label: "R",
+        min: 0.0,
+        max: 1.0,
+    },
+    Channel {
+        label: "G",
+        min: 0.0,
+        max: 1.0,
+    },
+    Channel {
+        label: "B",
+        min: 0.0,
+        max: 1.0,
+    },
+];
+
+const CMYK_CHANNELS: [Channel; 4] = [
+    Channel {
+        label: "C",
+        min: 0.0,
+        max: 100.0,
+    },
+    Channel {
+        label: "M",
+        min: 0.0,
+        max: 100.0,
+    },
+    Channel {
+        label: "Y",
+        min: 0.0,
+        max: 100.0,
+    },
+    Channel {
+        label: "K",
+        min: 0.0,
+        max: 100.0,
+    },
+];
+
+/// The color itself is always stored as OKLCH; modes only convert on the way in and out.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum ColorMode {
+    #[default]
+    Oklch,
+    Oklab,
+    Lab,
+    Lch,
+    Hsv,
+    Cmyk,
+    Linear,
+}
+
+impl ColorMode {
+    const ALL: [ColorMode; 7] = [
+        ColorMode::Oklch,
+        ColorMode::Oklab,
+        ColorMode::Lab,
+        ColorMode::Lch,
+        ColorMode::Hsv,
+        ColorMode::Cmyk,
+        ColorMode::Linear,
+    ];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            ColorMode::Oklch => "OKLCH",
+            ColorMode::Oklab => "OKLAB",
+            ColorMode::Lab => "LAB",
+            ColorMode::Lch => "LCH",
+            ColorMode::Hsv => "HSV",
+            ColorMode::Cmyk => "CMYK",
+            ColorMode::Linear => "LINEAR",
+        }
+    }
+
+    pub(crate) fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|m| *m == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub(crate) fn for_tag(tag: ColorSpaceTag) -> Option<Self> {
+        match tag {
+            ColorSpaceTag::Oklch => Some(ColorMode::Oklch),
+            ColorSpaceTag::Oklab => Some(ColorMode::Oklab),
+            ColorSpaceTag::Lab => Some(ColorMode::Lab),
+            ColorSpaceTag::Lch => Some(ColorMode::Lch),
+            ColorSpaceTag::Hsl | ColorSpaceTag::Hwb => Some(ColorMode::Hsv),
+            ColorSpaceTag::LinearSrgb => Some(ColorMode::Linear),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn channels(self) -> &'static [Channel] {
+        match self {
+            ColorMode::Oklch => &OKLCH_CHANNELS,
+            ColorMode::Oklab => &OKLAB_CHANNELS,
+            ColorMode::Lab => &LAB_CHANNELS,
+            ColorMode::Lch => &LCH_CHANNELS,
+            ColorMode::Hsv => &HSV_CHANNELS,
+            ColorMode::Cmyk => &CMYK_CHANNELS,
+            ColorMode::Linear => &LINEAR_CHANNELS,
+        }
+    }
+
+    /// Index of the component that is a hue angle, which wraps around instead of clamping.
+    fn hue_channel(self) -> Option<usize> {
+        match self {
+            ColorMode::Oklch | ColorMode::Lch => Some(2),
+            ColorMode::Hsv => Some(0),
+            ColorMode::Oklab | ColorMode::Lab | ColorMode::Cmyk | ColorMode::Linear => None,
+        }
+    }
+
+    pub(crate) fn components(self, oklch: [f32; 3]) -> Components {
+        let components = match self {
+            ColorMode::Oklch => pad(oklch),
+            ColorMode::Oklab => pad(convert::<Oklch, Oklab>(oklch)),
+            ColorMode::Lab => pad(convert::<Oklch, Lab>(oklch)),
+            ColorMode::Lch => pad(convert::<Oklch, Lch>(oklch)),
+            ColorMode::Hsv => {
+                let hwb = convert::<Srgb, Hwb>(srgb(oklch));
+                let value = 1.0 - hwb[2] / 100.0;
+                let saturation = if value <= 0.0 {
+                    0.0
+                } else {
+                    1.0 - hwb[1] / 100.0 / value
+                };
+                pad([hwb[0], saturation * 100.0, value * 100.0])
+            }
+            ColorMode::Cmyk => {
+                let [r, g, b] = srgb(oklch);
+                let k = 1.0 - r.max(g).max(b);
+                let ink = |channel: f32| {
+                    if k >= 1.0 {
+                        0.0
+                    } else {
+                        (1.0 - channel - k) / (1.0 - k) * 100.0
+                    }
+                };
+                [ink(r), ink(g), ink(b), k * 100.0]
+            }
+            ColorMode::Linear => pad(convert::<Oklch, LinearSrgb>(oklch)),
+        };
+        let channels = self.channels();
+        std::array::from_fn(|i| {
+            let Some(channel) = channels.get(i) else {
+                return 0.0;
+            };
+            if !components[i].is_finite() {
+                channel.min
+            } else if self.hue_channel() == Some(i) {
+                components[i].rem_euclid(channel.max)
+            } else {
+                components[i].clamp(channel.min, channel.max)
+            }
+        })
+    }
+
+    pub(crate) fn to_oklch(self, components: Components) -> [f32; 3] {
+        let [c0, c1, c2, c3] = components;
+        match self {
+            ColorMode::Oklch => [c0, c1, c2],
+            ColorMode::Oklab => convert::<Oklab, Oklch>([c0, c1, c2]),
+            ColorMode::Lab => convert::<Lab, Oklch>([c0, c1, c2]),
+            ColorMode::Lch => convert::<Lch, Oklch>([c0, c1, c2]),
+            ColorMode::Hsv => {
+                let [h, s, v] = [c0, c1 / 100.0, c2 / 100.0];
+                convert::<Hwb, Oklch>([h, (1.0 - s) * v * 100.0, (1.0 - v) * 100.0])
+            }
+            ColorMode::Cmyk => {
+                let k = 1.0 - c3 / 100.0;
+                let channel = |ink: f32| (1.0 - ink / 100.0) * k;
+                convert::<Srgb, Oklch>([channel(c0), channel(c1), channel(c2)])
+            }
+            ColorMode::Linear => convert::<LinearSrgb, Oklch>([c0, c1, c2]),
+        }
+    }
+
+    pub(crate) fn format(self, oklch: [f32; 3], alpha: f32) -> String {
+        let c = self.components(oklch);
+        let code = match self {
+            ColorMode::Oklch => format!("oklch({:.2} {:.3} {:.1})", c[0], c[1], c[2]),
+            ColorMode::Oklab => format!("oklab({:.2} {:.3} {:.3})", c[0], c[1], c[2]),
+            ColorMode::Lab => format!("lab({:.1} {:.1} {:.1})", c[0], c[1], c[2]),
+            ColorMode::Lch => format!("lch({:.1} {:.1} {:.1})", c[0], c[1], c[2]),
+            ColorMode::Hsv => format!("hsv({:.0}, {:.0}%, {:.0}%)", c[0], c[1], c[2]),
+            ColorMode::Cmyk => format!(
+                "cmyk({:.0}%, {:.0}%, {:.0}%, {:.0}%)",
+                c[0], c[1], c[2], c[3]
+            ),
+            ColorMode::Linear => {
+                format!("color(srgb-linear {:.4} {:.4} {:.4})", c[0], c[1], c[2])
+            }
+        };
+        if alpha < 1.0 {
+            format!("{} / {alpha:.2})", code.trim_end_matches(')'))
+        } else {
+            code
+        }
+    }
+}
+
+/// Parses the notations CSS has no syntax for: `hsv(h, s%, v%)` (or `hsb(...)`) and
+/// `cmyk(c%, m%, y%, k%)`, each with an optional trailing alpha.
+pub(crate) fn parse_mode_code(input: &str) -> Option<(ColorMode, [f32; 3], f32)> {
+    let input = input.trim().to_ascii_lowercase();
+    let (mode, args) = if let Some(args) = input
+        .strip_prefix("hsv(")
+        .or_else(|| input.strip_prefix("hsb("))
+    {
+        (ColorMode::Hsv, args)
+    } else {
+        (ColorMode::Cmyk, input.strip_prefix("cmyk(")?)
+    };
+    let mut parts = args
+        .strip_suffix(')')?
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let value = p.trim_end_matches(['%', '°']).parse::<f32>().ok()?;
+            Some((value, p.ends_with('%')))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let alpha = match parts.len().checked_sub(mode.channels().len())? {
+        0 => 1.0,
+        1 => match parts.pop()? {
+            (alpha, true) => alpha / 100.0,
+            (alpha, false) => alpha,
+        },
+        _ => return None,
+    };
+    let mut components = Components::default();
+    for (i, ((part, _), channel)) in parts.iter().zip(mode.channels()).enumerate() {
+        components[i] = if mode.hue_channel() == Some(i) {
+            *part
+        } else {
+            part.clamp(channel.min, channel.max)
+        };
+    }
+    Some((mode, mode.to_oklch(components), alpha.clamp(0.0, 1.0)))
+}
+
+fn pad([a, b, c]: [f32; 3]) -> Components {
+    [a, b, c, 0.0]
+}
+
+fn convert<Src: ColorSpace, Dst: ColorSpace>(components: [f32; 3]) -> [f32; 3] {
+    let c = AlphaColor::<Src>::new([components[0], components[1], components[2], 1.0])
+        .convert::<Dst>()
+        .components;
+    [c[0], c[1], c[2]]
+}
+
+fn srgb(oklch: [f32; 3]) -> [f32; 3] {
+    let c = convert::<Oklch, Srgb>(oklch);
+    [
+        c[0].clamp(0.0, 1.0),
+        c[1].clamp(0.0, 1.0),
+        c[2].clamp(0.0, 1.0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Components, b: Components) {
+        for i in 0..MAX_CHANNELS {
+            assert!((a[i] - b[i]).abs() < 0.5, "{a:?} != {b:?}");
+        }
+    }
+
+    #[test]
+    fn hsv_components_round_trip_through_oklch() {
+        let hsv = [210.0, 60.0, 80.0, 0.0];
+        let oklch = ColorMode::Hsv.to_oklch(hsv);
+        assert_close(ColorMode::Hsv.components(oklch), hsv);
+    }
+
+    #[test]
+    fn oklab_components_round_trip_through_oklch() {
+        let oklab = [0.6, -0.1, 0.08, 0.0];
+        let oklch = ColorMode::Oklab.to_oklch(oklab);
+        let back = ColorMode::Oklab.components(oklch);
+        for i in 0..3 {
+            assert!((back[i] - oklab[i]).abs() < 1e-4, "{back:?} != {oklab:?}");
+        }
+    }
+
+    #[test]
+    fn lab_codes_round_trip() {
+        let parsed = color::parse_color("lab(52.2 40.1 -59.9)").unwrap();
+        let oklch = parsed.to_alpha_color::<Oklch>().components;
+        let code = ColorMode::Lab.format([oklch[0], oklch[1], oklch[2]], 1.0);
+        assert_eq!(code, "lab(52.2 40.1 -59.9)");
+    }
+
+    #[test]
+    fn linear_codes_round_trip() {
+        let parsed = color::parse_color("color(srgb-linear 0.2 0.5 0.05)").unwrap();
+        assert_eq!(ColorMode::for_tag(parsed.cs), Some(ColorMode::Linear));
+        let oklch = parsed.to_alpha_color::<Oklch>().components;
+        let oklch = [oklch[0], oklch[1], oklch[2]];
+        assert_eq!(
+            ColorMode::Linear.format(oklch, 1.0),
+            "color(srgb-linear 0.2000 0.5000 0.0500)"
+        );
+        // Mid gray is about 0.214 before the sRGB transfer curve.
+        let gray = ColorMode::Linear.components(convert::<Srgb, Oklch>([0.5; 3]));
+        assert!((gray[0] - 0.214).abs() < 1e-3, "{gray:?}");
+    }
+
+    #[test]
+    fn lch_hue_wraps_instead_of_clamping() {
+        let oklch = ColorMode::Lch.to_oklch([60.0, 40.0, 400.0, 0.0]);
+        assert_close(ColorMode::Lch.components(oklch), [60.0, 40.0, 40.0, 0.0]);
+    }
+
+    #[test]
+    fn cmyk_components_round_trip_through_oklch() {
+        let cmyk = [0.0, 50.0, 100.0, 20.0];
+        let oklch = ColorMode::Cmyk.to_oklch(cmyk);
+        assert_close(ColorMode::Cmyk.components(oklch), cmyk);
+        assert_eq!(
+            ColorMode::Cmyk.format(oklch, 1.0),
+            "cmyk(0%, 50%, 100%, 20%)"
+        );
+    }
+
+    #[test]
+    fn translucent_colors_format_with_alpha() {
+        let oklch = [0.7, 0.15, 180.0];
+        assert_eq!(
+            ColorMode::Oklch.format(oklch, 0.5),
+            "oklch(0.70 0.150 180.0 / 0.50)"
+        );
+        assert_eq!(
+            ColorMode::Oklch.format(oklch, 1.0),
+            "oklch(0.70 0.150 180.0)"
+        );
+    }
+
+    #[test]
+    fn parses_hsv_hsb_and_cmyk_codes() {
+        let (mode, red, alpha) = parse_mode_code("hsv(0, 100%, 100%)").unwrap();
+        assert_eq!(mode, ColorMode::Hsv);
+        assert_close(mode.components(red), [0.0, 100.0, 100.0, 0.0]);
+        assert_eq!(alpha, 1.0);
+        assert_eq!(
+            parse_mode_code("hsb(0 100 100)"),
+            parse_mode_code("hsv(0, 100%, 100%)")
+        );
+        let (mode, _, _) = parse_mode_code("cmyk(0%, 100%, 100%, 0%)").unwrap();
+        assert_eq!(mode, ColorMode::Cmyk);
+        assert_eq!(parse_mode_code("cmyk(0, 100, 100)"), None);
+        assert_eq!(parse_mode_code("hsv(0 100 100 / 50%)").unwrap().2, 0.5);
+        assert_eq!(parse_mode_code("rgb(0, 0, 0)"), None);
+    }
+}
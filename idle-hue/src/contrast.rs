@@ -0,0 +1,172 @@
+use crate::gamut::GamutMapping;
+
+/// Colors at or above this OKLCH lightness are treated as meant for dark backgrounds.
+const DARK_THEME_LIGHTNESS: f32 = 0.6;
+/// Chroma is eased off when moving to a dark theme, where saturated colors glare.
+const DARK_THEME_CHROMA: f32 = 0.9;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum ContrastAlgorithm {
+    #[default]
+    Wcag,
+    /// APCA lightness contrast (Lc), roughly -108 to 106; negative for light text on dark.
+    Apca,
+}
+
+impl ContrastAlgorithm {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            ContrastAlgorithm::Wcag => "WCAG",
+            ContrastAlgorithm::Apca => "APCA",
+        }
+    }
+
+    pub(crate) fn toggled(self) -> Self {
+        match self {
+            ContrastAlgorithm::Wcag => ContrastAlgorithm::Apca,
+            ContrastAlgorithm::Apca => ContrastAlgorithm::Wcag,
+        }
+    }
+
+    pub(crate) fn format(self, text: [f32; 3], background: [f32; 3]) -> String {
+        match self {
+            ContrastAlgorithm::Wcag => format!("{:.2}:1", wcag_ratio(text, background)),
+            ContrastAlgorithm::Apca => format!("Lc {:.0}", apca_lc(text, background)),
+        }
+    }
+}
+
+pub(crate) fn wcag_ratio(a: [f32; 3], b: [f32; 3]) -> f32 {
+    let luminance = |[r, g, b]: [f32; 3]| {
+        let linear = |c: f32| {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+    };
+    let (la, lb) = (luminance(a), luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+/// Returns `(light, dark)` theme variants of an OKLCH color: the color itself, plus a
+/// partner for the opposite background with the same hue whose WCAG contrast against
+/// that background matches the original's against its own.
+pub(crate) fn theme_pair(oklch: [f32; 3]) -> ([f32; 3], [f32; 3]) {
+    const WHITE: [f32; 3] = [1.0; 3];
+    const BLACK: [f32; 3] = [0.0; 3];
+    let srgb = |values| GamutMapping::Perceptual.to_srgb(values);
+    let [l, c, h] = oklch;
+
+    let for_dark = l >= DARK_THEME_LIGHTNESS;
+    let (own_bg, other_bg) = if for_dark {
+        (BLACK, WHITE)
+    } else {
+        (WHITE, BLACK)
+    };
+    let chroma = if for_dark {
+        c / DARK_THEME_CHROMA
+    } else {
+        c * DARK_THEME_CHROMA
+    };
+    let target = wcag_ratio(srgb(oklch), own_bg);
+
+    // Contrast against white falls as lightness rises and against black it grows, so
+    // bisect on whichever side of the target the current guess lands.
+    let (mut lo, mut hi) = (0.0_f32, 1.0_f32);
+    for _ in 0..24 {
+        let mid = (lo + hi) / 2.0;
+        let ratio = wcag_ratio(srgb([mid, chroma, h]), other_bg);
+        if (ratio < target) == for_dark {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    let partner = [(lo + hi) / 2.0, chroma, h];
+    if for_dark {
+        (partner, oklch)
+    } else {
+        (oklch, partner)
+    }
+}
+
+/// APCA 0.0.98G-4g lightness contrast of `text` on `background`.
+pub(crate) fn apca_lc(text: [f32; 3], background: [f32; 3]) -> f32 {
+    const BLACK_THRESHOLD: f32 = 0.022;
+    const BLACK_CLAMP: f32 = 1.414;
+    const DELTA_Y_MIN: f32 = 0.0005;
+    const LOW_CLIP: f32 = 0.1;
+    const OFFSET: f32 = 0.027;
+    const SCALE: f32 = 1.14;
+
+    let screen_luminance = |[r, g, b]: [f32; 3]| {
+        let y = 0.2126729 * r.clamp(0.0, 1.0).powf(2.4)
+            + 0.7151522 * g.clamp(0.0, 1.0).powf(2.4)
+            + 0.0721750 * b.clamp(0.0, 1.0).powf(2.4);
+        if y < BLACK_THRESHOLD {
+            y + (BLACK_THRESHOLD - y).powf(BLACK_CLAMP)
+        } else {
+            y
+        }
+    };
+    let (y_text, y_background) = (screen_luminance(text), screen_luminance(background));
+    if (y_background - y_text).abs() < DELTA_Y_MIN {
+        return 0.0;
+    }
+    let lc = if y_background > y_text {
+        let sapc = (y_background.powf(0.56) - y_text.powf(0.57)) * SCALE;
+        if sapc < LOW_CLIP { 0.0 } else { sapc - OFFSET }
+    } else {
+        let sapc = (y_background.powf(0.65) - y_text.powf(0.62)) * SCALE;
+        if sapc > -LOW_CLIP { 0.0 } else { sapc + OFFSET }
+    };
+    lc * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: [f32; 3] = [1.0, 1.0, 1.0];
+    const BLACK: [f32; 3] = [0.0, 0.0, 0.0];
+
+    fn gray(v: u8) -> [f32; 3] {
+        [v as f32 / 255.0; 3]
+    }
+
+    #[test]
+    fn wcag_ratio_matches_reference_values() {
+        assert!((wcag_ratio(WHITE, BLACK) - 21.0).abs() < 1e-3);
+        assert!((wcag_ratio(gray(0x77), WHITE) - 4.48).abs() < 0.01);
+    }
+
+    #[test]
+    fn theme_pair_matches_contrast_on_the_opposite_background() {
+        let srgb = |values| GamutMapping::Perceptual.to_srgb(values);
+        let original = [0.45, 0.15, 260.0];
+        let (light, dark) = theme_pair(original);
+
+        assert_eq!(light, original);
+        assert!(dark[0] > light[0]);
+        assert!((dark[2] - 260.0).abs() < 1e-4);
+        let light_ratio = wcag_ratio(srgb(light), WHITE);
+        let dark_ratio = wcag_ratio(srgb(dark), BLACK);
+        assert!((light_ratio - dark_ratio).abs() < 0.05);
+
+        let (light, dark) = theme_pair([0.8, 0.1, 140.0]);
+        assert_eq!(dark, [0.8, 0.1, 140.0]);
+        assert!(light[0] < 0.8);
+    }
+
+    #[test]
+    fn apca_matches_reference_values() {
+        assert!((apca_lc(gray(0x88), WHITE) - 63.06).abs() < 0.05);
+        assert!((apca_lc(WHITE, gray(0x88)) + 68.54).abs() < 0.05);
+        assert!((apca_lc(BLACK, gray(0xaa)) - 58.15).abs() < 0.05);
+        assert!((apca_lc(gray(0xaa), BLACK) + 56.24).abs() < 0.05);
+        assert_eq!(apca_lc(gray(0x80), gray(0x80)), 0.0);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::contrast::ContrastAlgorithm;
+use crate::palette_png::{TEXT_HEIGHT, draw_text, fill, text_width};
+use image::{ImageFormat, Rgba, RgbaImage};
+use std::io::Cursor;
+
+const CELL: u32 = 90;
+const GAP: u32 = 2;
+const PADDING: u32 = 12;
+const BACKGROUND: Rgba<u8> = Rgba([0xff, 0xff, 0xff, 0xff]);
+
+/// The first row and column hold the plain swatches; the cell at row `i`, column `j`
+/// shows color `i` as text on color `j`, labeled with their contrast.
+pub(crate) fn encode(colors: &[[f32; 3]], algorithm: ContrastAlgorithm) -> Result<Vec<u8>, String> {
+    let span = PADDING * 2 + (colors.len() as u32 + 1) * (CELL + GAP) - GAP;
+    let mut image = RgbaImage::from_pixel(span, span, BACKGROUND);
+    let origin = |index: u32| PADDING + index * (CELL + GAP);
+
+    for (i, rgb) in colors.iter().enumerate() {
+        let swatch = rgba(*rgb);
+        let i = i as u32 + 1;
+        fill(&mut image, origin(i), origin(0), CELL, CELL, swatch);
+        fill(&mut image, origin(0), origin(i), CELL, CELL, swatch);
+    }
+    for (row, text) in colors.iter().enumerate() {
+        for (col, bg) in colors.iter().enumerate() {
+            let (x, y) = (origin(col as u32 + 1), origin(row as u32 + 1));
+            fill(&mut image, x, y, CELL, CELL, rgba(*bg));
+            let label = algorithm.format(*text, *bg);
+            draw_text(
+                &mut image,
+                x + CELL.saturating_sub(text_width(&label)) / 2,
+                y + (CELL - TEXT_HEIGHT) / 2,
+                &label,
+                rgba(*text),
+            );
+        }
+    }
+
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode PNG: {e}"))?;
+    Ok(bytes)
+}
+
+fn rgba(rgb: [f32; 3]) -> Rgba<u8> {
+    let [r, g, b] = rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    Rgba([r, g, b, 0xff])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lays_out_headers_and_pairs() {
+        let red = Rgba([0xff, 0x00, 0x00, 0xff]);
+        let colors = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]];
+        let bytes = encode(&colors, ContrastAlgorithm::Wcag).unwrap();
+        let image = image::load_from_memory(&bytes).unwrap().to_rgba8();
+
+        assert_eq!(image.width(), PADDING * 2 + 3 * CELL + 2 * GAP);
+        assert_eq!(*image.get_pixel(PADDING + 1, PADDING + 1), BACKGROUND);
+        let second_column = PADDING + 2 * (CELL + GAP) + 1;
+        let first_row = PADDING + CELL + GAP + 1;
+        assert_eq!(*image.get_pixel(second_column, PADDING + 1), red);
+        assert_eq!(
+            *image.get_pixel(PADDING + 1, first_row),
+            Rgba([0x00, 0x00, 0x00, 0xff])
+        );
+        assert_eq!(*image.get_pixel(second_column, first_row), red);
+    }
+}
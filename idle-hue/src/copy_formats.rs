@@ -0,0 +1,163 @@
+use crate::color_mode::ColorMode;
+use crate::gamut;
+use color::{AlphaColor, Hsl, Srgb};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CopyFormat {
+    Hex,
+    Rgb,
+    Oklch,
+    Oklab,
+    Lab,
+    Hsl,
+    DisplayP3,
+    SwiftUi,
+    UiKit,
+    Android,
+    Flutter,
+    Linear,
+}
+
+impl CopyFormat {
+    pub(crate) const ALL: [CopyFormat; 12] = [
+        CopyFormat::Hex,
+        CopyFormat::Rgb,
+        CopyFormat::Oklch,
+        CopyFormat::Oklab,
+        CopyFormat::Lab,
+        CopyFormat::Hsl,
+        CopyFormat::DisplayP3,
+        CopyFormat::SwiftUi,
+        CopyFormat::UiKit,
+        CopyFormat::Android,
+        CopyFormat::Flutter,
+        CopyFormat::Linear,
+    ];
+
+    pub(crate) const CSS: [CopyFormat; 7] = [
+        CopyFormat::Hex,
+        CopyFormat::Rgb,
+        CopyFormat::Hsl,
+        CopyFormat::Oklch,
+        CopyFormat::Oklab,
+        CopyFormat::Lab,
+        CopyFormat::DisplayP3,
+    ];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            CopyFormat::Hex => "Hex",
+            CopyFormat::Rgb => "rgb()",
+            CopyFormat::Oklch => "oklch()",
+            CopyFormat::Oklab => "oklab()",
+            CopyFormat::Lab => "lab()",
+            CopyFormat::Hsl => "hsl()",
+            CopyFormat::DisplayP3 => "display-p3",
+            CopyFormat::SwiftUi => "SwiftUI",
+            CopyFormat::UiKit => "UIColor",
+            CopyFormat::Android => "Android",
+            CopyFormat::Flutter => "Flutter",
+            CopyFormat::Linear => "linear floats",
+        }
+    }
+
+    pub(crate) fn format(self, rgb: [f32; 3], oklch: [f32; 3], alpha: f32) -> String {
+        let rgb = rgb.map(|c| c.clamp(0.0, 1.0));
+        let [r, g, b] = rgb.map(|c| (c * 255.0).round() as u8);
+        let a = (alpha.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let opaque = alpha >= 1.0;
+        match self {
+            CopyFormat::Hex if opaque => format!("#{r:02x}{g:02x}{b:02x}"),
+            CopyFormat::Hex => format!("#{r:02x}{g:02x}{b:02x}{a:02x}"),
+            CopyFormat::Rgb if opaque => format!("rgb({r}, {g}, {b})"),
+            CopyFormat::Rgb => format!("rgba({r}, {g}, {b}, {alpha:.2})"),
+            CopyFormat::Oklch => ColorMode::Oklch.format(oklch, alpha),
+            CopyFormat::Oklab => ColorMode::Oklab.format(oklch, alpha),
+            CopyFormat::Lab => ColorMode::Lab.format(oklch, alpha),
+            CopyFormat::Hsl => {
+                let [h, s, l, _] = AlphaColor::<Srgb>::new([rgb[0], rgb[1], rgb[2], 1.0])
+                    .convert::<Hsl>()
+                    .components;
+                let h = if h.is_finite() { h } else { 0.0 };
+                if opaque {
+                    format!("hsl({h:.0}, {s:.0}%, {l:.0}%)")
+                } else {
+                    format!("hsla({h:.0}, {s:.0}%, {l:.0}%, {alpha:.2})")
+                }
+            }
+            CopyFormat::DisplayP3 => {
+                let [r, g, b] = gamut::to_display_p3(oklch);
+                if opaque {
+                    format!("color(display-p3 {r:.3} {g:.3} {b:.3})")
+                } else {
+                    format!("color(display-p3 {r:.3} {g:.3} {b:.3} / {alpha:.2})")
+                }
+            }
+            CopyFormat::SwiftUi if opaque => format!(
+                "Color(red: {:.3}, green: {:.3}, blue: {:.3})",
+                rgb[0], rgb[1], rgb[2]
+            ),
+            CopyFormat::SwiftUi => format!(
+                "Color(red: {:.3}, green: {:.3}, blue: {:.3}, opacity: {alpha:.2})",
+                rgb[0], rgb[1], rgb[2]
+            ),
+            CopyFormat::UiKit => format!(
+                "UIColor(red: {:.3}, green: {:.3}, blue: {:.3}, alpha: {alpha:.2})",
+                rgb[0], rgb[1], rgb[2]
+            ),
+            CopyFormat::Android if opaque => {
+                format!("Color.parseColor(\"#{r:02X}{g:02X}{b:02X}\")")
+            }
+            CopyFormat::Android => format!("Color.parseColor(\"#{a:02X}{r:02X}{g:02X}{b:02X}\")"),
+            CopyFormat::Flutter => format!("Color(0x{a:02X}{r:02X}{g:02X}{b:02X})"),
+            CopyFormat::Linear => {
+                let [r, g, b, _] = ColorMode::Linear.components(oklch);
+                if opaque {
+                    format!("{r:.4}, {g:.4}, {b:.4}")
+                } else {
+                    format!("{r:.4}, {g:.4}, {b:.4}, {alpha:.2}")
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_platform_snippets() {
+        let rgb = [70.0 / 255.0, 130.0 / 255.0, 180.0 / 255.0];
+        let oklch = [0.59, 0.09, 246.0];
+        let format = |f: CopyFormat, alpha| f.format(rgb, oklch, alpha);
+
+        assert_eq!(format(CopyFormat::Hex, 1.0), "#4682b4");
+        assert_eq!(format(CopyFormat::Rgb, 1.0), "rgb(70, 130, 180)");
+        assert_eq!(format(CopyFormat::Hsl, 1.0), "hsl(207, 44%, 49%)");
+        assert!(format(CopyFormat::Oklab, 1.0).starts_with("oklab(0.59 "));
+        assert!(format(CopyFormat::Lab, 0.5).ends_with(" / 0.50)"));
+        assert_eq!(
+            format(CopyFormat::SwiftUi, 1.0),
+            "Color(red: 0.275, green: 0.510, blue: 0.706)"
+        );
+        assert_eq!(
+            format(CopyFormat::Android, 1.0),
+            "Color.parseColor(\"#4682B4\")"
+        );
+        assert_eq!(format(CopyFormat::Flutter, 1.0), "Color(0xFF4682B4)");
+        assert_eq!(
+            CopyFormat::Linear.format(rgb, [1.0, 0.0, 0.0], 0.5),
+            "1.0000, 1.0000, 1.0000, 0.50"
+        );
+        assert_eq!(
+            format(CopyFormat::Android, 0.5),
+            "Color.parseColor(\"#804682B4\")"
+        );
+        assert_eq!(format(CopyFormat::Hex, 0.5), "#4682b480");
+        assert_eq!(
+            CopyFormat::DisplayP3.format([1.0; 3], [1.0, 0.0, 0.0], 1.0),
+            "color(display-p3 1.000 1.000 1.000)"
+        );
+    }
+}
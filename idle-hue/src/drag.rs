@@ -0,0 +1,101 @@
+pub(crate) const SENSITIVITIES: [f32; 4] = [0.25, 0.5, 1.0, 2.0];
+/// Holding Shift makes a drag this much finer, for things like tuning OKLCH chroma.
+pub(crate) const FINE_ADJUST: f32 = 0.1;
+
+pub(crate) fn next(sensitivity: f32) -> f32 {
+    SENSITIVITIES
+        .into_iter()
+        .find(|step| *step > sensitivity + 1e-3)
+        .unwrap_or(SENSITIVITIES[0])
+}
+
+pub(crate) fn clamp(sensitivity: f32) -> f32 {
+    if sensitivity.is_finite() {
+        sensitivity.clamp(SENSITIVITIES[0], SENSITIVITIES[SENSITIVITIES.len() - 1])
+    } else {
+        1.0
+    }
+}
+
+/// Must be called on the main thread.
+pub(crate) fn fine_adjust_held() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        use objc2_app_kit::{NSEvent, NSEventModifierFlags};
+
+        NSEvent::modifierFlags_class().contains(NSEventModifierFlags::Shift)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetKeyState, VK_SHIFT};
+
+        unsafe { GetKeyState(VK_SHIFT as i32) < 0 }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    false
+}
+
+/// A slider drag in progress. The sliders report where the pointer is along their range;
+/// this turns that into a value that moves `scale` times as far from where the drag (or
+/// the last change of scale) began.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct SliderDrag {
+    pub(crate) slider: usize,
+    pointer: f32,
+    value: f32,
+    scale: f32,
+    last: f32,
+}
+
+impl SliderDrag {
+    /// Starts a drag where the press landed, so a click still jumps straight there.
+    pub(crate) fn start(slider: usize, pointer: f32, scale: f32) -> Self {
+        SliderDrag {
+            slider,
+            pointer,
+            value: pointer,
+            scale,
+            last: pointer,
+        }
+    }
+
+    /// The value for the pointer at `pointer`. A new `scale` (Shift pressed or released)
+    /// re-anchors at the last value, so switching mid-drag never makes the knob jump.
+    pub(crate) fn follow(&mut self, pointer: f32, scale: f32, (min, max): (f32, f32)) -> f32 {
+        if scale != self.scale {
+            *self = SliderDrag {
+                pointer,
+                value: self.last,
+                scale,
+                ..*self
+            };
+        }
+        self.last = (self.value + (pointer - self.pointer) * scale).clamp(min, max);
+        self.last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fine_drags_move_a_tenth_as_far_without_jumping() {
+        let range = (0.0, 0.4);
+        let mut drag = SliderDrag::start(1, 0.2, 1.0);
+        assert_eq!(drag.follow(0.3, 1.0, range), 0.3);
+
+        let fine = FINE_ADJUST;
+        assert_eq!(drag.follow(0.35, fine, range), 0.3);
+        assert!((drag.follow(0.05, fine, range) - 0.27).abs() < 1e-6);
+        assert!((drag.follow(0.15, 1.0, range) - 0.27).abs() < 1e-6);
+        assert!((drag.follow(0.25, 1.0, range) - 0.37).abs() < 1e-6);
+        assert_eq!(drag.follow(0.4, 1.0, range), 0.4);
+
+        assert_eq!(next(1.0), 2.0);
+        assert_eq!(next(2.0), 0.25);
+        assert_eq!(clamp(f32::NAN), 1.0);
+    }
+}
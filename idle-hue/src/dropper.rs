@@ -1,14 +1,42 @@
 use std::sync::Arc;
 use tokio::sync::oneshot;
 
-pub(crate) fn sample_color() -> oneshot::Receiver<Option<[f32; 3]>> {
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum CaptureSpace {
+    #[default]
+    Srgb,
+    DisplayP3,
+}
+
+impl CaptureSpace {
+    pub(crate) const ALL: [CaptureSpace; 2] = [CaptureSpace::Srgb, CaptureSpace::DisplayP3];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            CaptureSpace::Srgb => "sRGB",
+            CaptureSpace::DisplayP3 => "Display P3",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Sample {
+    // Wide-gamut picks fall outside `0.0..=1.0`.
+    pub(crate) rgb: [f32; 3],
+    pub(crate) profile: Option<String>,
+    pub(crate) wide_gamut: bool,
+}
+
+pub(crate) fn sample_color() -> oneshot::Receiver<Option<Sample>> {
     let (tx, rx) = oneshot::channel();
     let tx = Arc::new(std::sync::Mutex::new(Some(tx)));
 
     #[cfg(target_os = "macos")]
     {
         use block2::RcBlock;
-        use objc2_app_kit::{NSColor, NSColorSampler, NSColorSpace};
+        use objc2_app_kit::{NSColor, NSColorSampler, NSColorSpace, NSDisplayGamut, NSScreen};
+        use objc2_foundation::MainThreadMarker;
 
         let sampler = NSColorSampler::new();
 
@@ -17,15 +45,26 @@ pub(crate) fn sample_color() -> oneshot::Receiver<Option<[f32; 3]>> {
                 None
             } else {
                 let color = unsafe { &*color };
-                let srgb_space = NSColorSpace::sRGBColorSpace();
+                let profile = color
+                    .colorSpace()
+                    .localizedName()
+                    .map(|name| name.to_string());
+                // Extended sRGB keeps Display P3 pixels that sRGB can't hold instead of
+                // clamping them to its edge.
+                let srgb_space = NSColorSpace::extendedSRGBColorSpace();
                 let srgb_color = color.colorUsingColorSpace(&srgb_space);
-                srgb_color.map(|c|
-                    [
+                let wide_gamut = MainThreadMarker::new()
+                    .and_then(NSScreen::mainScreen)
+                    .is_some_and(|screen| screen.canRepresentDisplayGamut(NSDisplayGamut::P3));
+                srgb_color.map(|c| Sample {
+                    rgb: [
                         c.redComponent() as f32,
                         c.greenComponent() as f32,
                         c.blueComponent() as f32,
-                    ]
-                )
+                    ],
+                    profile,
+                    wide_gamut,
+                })
             };
             if let Some(tx) = tx.lock().unwrap().take() {
                 let _ = tx.send(result);
@@ -35,7 +74,17 @@ pub(crate) fn sample_color() -> oneshot::Receiver<Option<[f32; 3]>> {
         unsafe { sampler.showSamplerWithSelectionHandler(&handler) };
     }
 
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(target_os = "windows")]
+    {
+        std::thread::spawn(move || {
+            let result = windows::sample_next_click();
+            if let Some(tx) = tx.lock().unwrap().take() {
+                let _ = tx.send(result);
+            }
+        });
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
     {
         if let Some(tx) = tx.lock().unwrap().take() {
             let _ = tx.send(None);
@@ -44,3 +93,299 @@ pub(crate) fn sample_color() -> oneshot::Receiver<Option<[f32; 3]>> {
 
     rx
 }
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use super::Sample;
+    use crate::icc;
+    use std::cell::Cell;
+    use std::ptr::{null, null_mut};
+    use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, MAX_PATH, POINT, RECT, WPARAM};
+    use windows_sys::Win32::Graphics::Gdi::{
+        BLACK_BRUSH, BeginPaint, COLORONCOLOR, CreateDCW, DeleteDC, EndPaint, FrameRect, GetDC,
+        GetMonitorInfoW, GetPixel, GetStockObject, InvalidateRect, MONITOR_DEFAULTTONEAREST,
+        MONITORINFO, MONITORINFOEXW, MonitorFromPoint, PAINTSTRUCT, ReleaseDC, SRCCOPY,
+        SetStretchBltMode, StretchBlt, WHITE_BRUSH,
+    };
+    use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows_sys::Win32::UI::ColorSystem::GetICMProfileW;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::VK_ESCAPE;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW,
+        GetCursorPos, GetMessageW, HWND_TOPMOST, KBDLLHOOKSTRUCT, MSG, MSLLHOOKSTRUCT,
+        PostQuitMessage, RegisterClassW, SWP_NOACTIVATE, SWP_SHOWWINDOW, SetWindowPos,
+        SetWindowsHookExW, UnhookWindowsHookEx, WH_KEYBOARD_LL, WH_MOUSE_LL, WM_KEYDOWN,
+        WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MOUSEMOVE, WM_PAINT, WM_RBUTTONDOWN, WNDCLASSW,
+        WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TOPMOST, WS_EX_TRANSPARENT, WS_POPUP,
+    };
+
+    const CLR_INVALID: u32 = 0xFFFF_FFFF;
+    // Odd, so the pixel a click would pick sits in the middle.
+    const MAGNIFIED_PIXELS: i32 = 11;
+    const ZOOM: i32 = 11;
+    const MAGNIFIER_SIZE: i32 = MAGNIFIED_PIXELS * ZOOM;
+    const MAGNIFIER_OFFSET: i32 = 24;
+
+    thread_local! {
+        static SAMPLED: Cell<Option<([f32; 3], POINT)>> = const { Cell::new(None) };
+        static MAGNIFIER: Cell<HWND> = const { Cell::new(null_mut()) };
+        static POINTER: Cell<POINT> = const { Cell::new(POINT { x: 0, y: 0 }) };
+    }
+
+    pub(super) fn sample_next_click() -> Option<Sample> {
+        SAMPLED.with(|s| s.set(None));
+        unsafe {
+            let mouse_hook = SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_proc), null_mut(), 0);
+            if mouse_hook.is_null() {
+                log::error!("Failed to install eyedropper mouse hook");
+                return None;
+            }
+            let keyboard_hook =
+                SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_proc), null_mut(), 0);
+            let magnifier = open_magnifier();
+            MAGNIFIER.with(|m| m.set(magnifier));
+            let mut point = POINT::default();
+            if GetCursorPos(&mut point) != 0 {
+                follow(point);
+            }
+
+            let mut msg: MSG = std::mem::zeroed();
+            while GetMessageW(&mut msg, null_mut(), 0, 0) > 0 {
+                DispatchMessageW(&msg);
+            }
+
+            MAGNIFIER.with(|m| m.set(null_mut()));
+            if !magnifier.is_null() {
+                DestroyWindow(magnifier);
+            }
+            UnhookWindowsHookEx(mouse_hook);
+            if !keyboard_hook.is_null() {
+                UnhookWindowsHookEx(keyboard_hook);
+            }
+        }
+        let (rgb, point) = SAMPLED.with(|s| s.take())?;
+        // The desktop isn't color managed, so the pixel is in whatever space the monitor
+        // under it was profiled as.
+        Some(match monitor_profile(point) {
+            Some(profile) => Sample {
+                rgb: profile.to_srgb(rgb),
+                wide_gamut: profile.is_wide_gamut(),
+                profile: Some(profile.description),
+            },
+            None => Sample {
+                rgb,
+                profile: None,
+                wide_gamut: false,
+            },
+        })
+    }
+
+    fn monitor_profile(point: POINT) -> Option<icc::Profile> {
+        let path = unsafe {
+            let monitor = MonitorFromPoint(point, MONITOR_DEFAULTTONEAREST);
+            let mut info = MONITORINFOEXW::default();
+            info.monitorInfo.cbSize = size_of::<MONITORINFOEXW>() as u32;
+            let info_ptr = &mut info as *mut MONITORINFOEXW as *mut MONITORINFO;
+            if GetMonitorInfoW(monitor, info_ptr) == 0 {
+                return None;
+            }
+            let dc = CreateDCW(
+                info.szDevice.as_ptr(),
+                info.szDevice.as_ptr(),
+                null(),
+                null(),
+            );
+            if dc.is_null() {
+                return None;
+            }
+            let mut path = [0u16; MAX_PATH as usize];
+            let mut len = MAX_PATH;
+            let found = GetICMProfileW(dc, &mut len, path.as_mut_ptr());
+            DeleteDC(dc);
+            if found == 0 {
+                return None;
+            }
+            let end = path.iter().position(|c| *c == 0).unwrap_or(path.len());
+            String::from_utf16_lossy(&path[..end])
+        };
+        let bytes = std::fs::read(&path).ok()?;
+        icc::parse(&bytes)
+            .inspect_err(|e| log::warn!("Ignoring display profile {path}: {e}"))
+            .ok()
+    }
+
+    fn open_magnifier() -> HWND {
+        let class: Vec<u16> = "idle-hue-magnifier".encode_utf16().chain([0]).collect();
+        unsafe {
+            let instance = GetModuleHandleW(null());
+            let mut window_class: WNDCLASSW = std::mem::zeroed();
+            window_class.lpfnWndProc = Some(magnifier_proc);
+            window_class.hInstance = instance;
+            window_class.lpszClassName = class.as_ptr();
+            // Fails once the class exists from an earlier pick, which is fine.
+            RegisterClassW(&window_class);
+            CreateWindowExW(
+                WS_EX_TOPMOST | WS_EX_TOOLWINDOW | WS_EX_TRANSPARENT | WS_EX_NOACTIVATE,
+                class.as_ptr(),
+                null(),
+                WS_POPUP,
+                0,
+                0,
+                MAGNIFIER_SIZE,
+                MAGNIFIER_SIZE,
+                null_mut(),
+                null_mut(),
+                instance,
+                null(),
+            )
+        }
+    }
+
+    fn follow(point: POINT) {
+        let magnifier = MAGNIFIER.with(Cell::get);
+        if magnifier.is_null() {
+            return;
+        }
+        POINTER.with(|p| p.set(point));
+        let mut x = point.x + MAGNIFIER_OFFSET;
+        let mut y = point.y + MAGNIFIER_OFFSET;
+        unsafe {
+            let monitor = MonitorFromPoint(point, MONITOR_DEFAULTTONEAREST);
+            let mut info = MONITORINFO {
+                cbSize: size_of::<MONITORINFO>() as u32,
+                ..Default::default()
+            };
+            if GetMonitorInfoW(monitor, &mut info) != 0 {
+                if x + MAGNIFIER_SIZE > info.rcMonitor.right {
+                    x = point.x - MAGNIFIER_OFFSET - MAGNIFIER_SIZE;
+                }
+                if y + MAGNIFIER_SIZE > info.rcMonitor.bottom {
+                    y = point.y - MAGNIFIER_OFFSET - MAGNIFIER_SIZE;
+                }
+            }
+            SetWindowPos(
+                magnifier,
+                HWND_TOPMOST,
+                x,
+                y,
+                MAGNIFIER_SIZE,
+                MAGNIFIER_SIZE,
+                SWP_NOACTIVATE | SWP_SHOWWINDOW,
+            );
+            InvalidateRect(magnifier, null(), 0);
+        }
+    }
+
+    unsafe extern "system" fn magnifier_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        if msg != WM_PAINT {
+            return unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) };
+        }
+        let point = POINTER.with(Cell::get);
+        let half = MAGNIFIED_PIXELS / 2;
+        unsafe {
+            let mut paint: PAINTSTRUCT = std::mem::zeroed();
+            let dc = BeginPaint(hwnd, &mut paint);
+            let screen = GetDC(null_mut());
+            if !screen.is_null() {
+                SetStretchBltMode(dc, COLORONCOLOR);
+                StretchBlt(
+                    dc,
+                    0,
+                    0,
+                    MAGNIFIER_SIZE,
+                    MAGNIFIER_SIZE,
+                    screen,
+                    point.x - half,
+                    point.y - half,
+                    MAGNIFIED_PIXELS,
+                    MAGNIFIED_PIXELS,
+                    SRCCOPY,
+                );
+                ReleaseDC(null_mut(), screen);
+            }
+            let black = GetStockObject(BLACK_BRUSH);
+            let white = GetStockObject(WHITE_BRUSH);
+            let border = RECT {
+                left: 0,
+                top: 0,
+                right: MAGNIFIER_SIZE,
+                bottom: MAGNIFIER_SIZE,
+            };
+            FrameRect(dc, &border, black);
+            // Black outside white, so the frame shows against any color.
+            let picked = RECT {
+                left: half * ZOOM - 1,
+                top: half * ZOOM - 1,
+                right: (half + 1) * ZOOM + 1,
+                bottom: (half + 1) * ZOOM + 1,
+            };
+            FrameRect(dc, &picked, black);
+            let inner = RECT {
+                left: half * ZOOM,
+                top: half * ZOOM,
+                right: (half + 1) * ZOOM,
+                bottom: (half + 1) * ZOOM,
+            };
+            FrameRect(dc, &inner, white);
+            EndPaint(hwnd, &paint);
+        }
+        0
+    }
+
+    unsafe extern "system" fn mouse_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code >= 0 {
+            let info = unsafe { &*(lparam as *const MSLLHOOKSTRUCT) };
+            match wparam as u32 {
+                WM_LBUTTONDOWN => {
+                    let pixel = pixel_at(info.pt.x, info.pt.y).map(|rgb| (rgb, info.pt));
+                    SAMPLED.with(|s| s.set(pixel));
+                    return 1;
+                }
+                WM_LBUTTONUP | WM_RBUTTONDOWN => {
+                    unsafe { PostQuitMessage(0) };
+                    return 1;
+                }
+                WM_MOUSEMOVE => follow(info.pt),
+                _ => {}
+            }
+        }
+        unsafe { CallNextHookEx(null_mut(), code, wparam, lparam) }
+    }
+
+    unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code >= 0 && wparam as u32 == WM_KEYDOWN {
+            let info = unsafe { &*(lparam as *const KBDLLHOOKSTRUCT) };
+            if info.vkCode == VK_ESCAPE as u32 {
+                SAMPLED.with(|s| s.set(None));
+                unsafe { PostQuitMessage(0) };
+                return 1;
+            }
+        }
+        unsafe { CallNextHookEx(null_mut(), code, wparam, lparam) }
+    }
+
+    fn pixel_at(x: i32, y: i32) -> Option<[f32; 3]> {
+        let pixel = unsafe {
+            let screen = GetDC(null_mut());
+            if screen.is_null() {
+                return None;
+            }
+            let pixel = GetPixel(screen, x, y);
+            ReleaseDC(null_mut(), screen);
+            pixel
+        };
+        if pixel == CLR_INVALID {
+            return None;
+        }
+        Some([
+            (pixel & 0xff) as f32 / 255.0,
+            ((pixel >> 8) & 0xff) as f32 / 255.0,
+            ((pixel >> 16) & 0xff) as f32 / 255.0,
+        ])
+    }
+}
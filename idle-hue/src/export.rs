@@ -0,0 +1,114 @@
+use crate::{PALETTE_WIDTH, ase, palette_png, tailwind};
+use color::{AlphaColor, Oklch, Srgb};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PaletteExport {
+    Ase,
+    Png,
+    /// Copied to the clipboard rather than saved.
+    Tailwind,
+}
+
+impl PaletteExport {
+    pub(crate) const ALL: [PaletteExport; 3] = [
+        PaletteExport::Ase,
+        PaletteExport::Png,
+        PaletteExport::Tailwind,
+    ];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            PaletteExport::Ase => ".ase",
+            PaletteExport::Png => ".png",
+            PaletteExport::Tailwind => "tailwind",
+        }
+    }
+
+    fn filter_name(self) -> &'static str {
+        match self {
+            PaletteExport::Ase => "Adobe Swatch Exchange",
+            PaletteExport::Png => "PNG image",
+            PaletteExport::Tailwind => "JavaScript",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            PaletteExport::Ase => "ase",
+            PaletteExport::Png => "png",
+            PaletteExport::Tailwind => "js",
+        }
+    }
+
+    pub(crate) fn is_snippet(self) -> bool {
+        matches!(self, PaletteExport::Tailwind)
+    }
+
+    pub(crate) fn encode(self, cells: &[Option<[f32; 3]>]) -> Result<Vec<u8>, String> {
+        let palette = cells.iter().flatten();
+        match self {
+            PaletteExport::Ase => Ok(ase::encode(
+                &palette
+                    .map(|oklch| {
+                        let rgb = srgb(*oklch);
+                        (hex_name(rgb), rgb)
+                    })
+                    .collect::<Vec<_>>(),
+            )),
+            PaletteExport::Png => palette_png::encode(
+                &cells.iter().map(|cell| cell.map(srgb)).collect::<Vec<_>>(),
+                PALETTE_WIDTH,
+            ),
+            PaletteExport::Tailwind => Ok(tailwind::encode(
+                &palette
+                    .map(|oklch| (*oklch, hex_name(srgb(*oklch))))
+                    .collect::<Vec<_>>(),
+            )
+            .into_bytes()),
+        }
+    }
+
+    pub(crate) async fn save(self, cells: Vec<Option<[f32; 3]>>) -> Result<Option<String>, String> {
+        let bytes = self.encode(&cells)?;
+        save_file(
+            bytes,
+            &format!("palette.{}", self.extension()),
+            self.filter_name(),
+            self.extension(),
+        )
+        .await
+    }
+}
+
+/// Cancelling the dialog is not an error and returns `None`.
+pub(crate) async fn save_file(
+    bytes: Vec<u8>,
+    file_name: &str,
+    filter_name: &str,
+    extension: &str,
+) -> Result<Option<String>, String> {
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_file_name(file_name)
+        .add_filter(filter_name, &[extension])
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
+    tokio::fs::write(file.path(), bytes)
+        .await
+        .map_err(|e| format!("Failed to write {}: {e}", file.path().display()))?;
+    Ok(Some(file.file_name()))
+}
+
+fn srgb([l, c, h]: [f32; 3]) -> [f32; 3] {
+    let [r, g, b, _] = AlphaColor::<Oklch>::new([l, c, h, 1.0])
+        .convert::<Srgb>()
+        .components;
+    [r, g, b].map(|c| c.clamp(0.0, 1.0))
+}
+
+fn hex_name(rgb: [f32; 3]) -> String {
+    let [r, g, b] = rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!("#{r:02X}{g:02X}{b:02X}")
+}
@@ -0,0 +1,173 @@
+/// Rewrites arithmetic in the arguments of a color function typed into a format field,
+/// e.g. `rgb(128+16, 64, 32)`, into plain numbers. An argument starting with `+`, `*` or
+/// `/` applies to the same argument of `current`, the code the field held before editing,
+/// so `oklch(0.7 *0.5 180)` halves the chroma. Returns `None` when there is nothing to
+/// evaluate or an expression doesn't make sense.
+pub(crate) fn expand(input: &str, current: &str) -> Option<String> {
+    let (name, args) = function(input)?;
+    let comma_separated = args.contains(',');
+    let tokens = arguments(args);
+    if !tokens.iter().any(|token| is_expression(token)) {
+        return None;
+    }
+    let current_tokens = function(current)
+        .filter(|(current_name, _)| current_name.eq_ignore_ascii_case(name))
+        .map(|(_, args)| arguments(args))
+        .unwrap_or_default();
+
+    let mut values = Vec::with_capacity(tokens.len());
+    for (i, token) in tokens.iter().enumerate() {
+        if !is_expression(token) {
+            values.push(token.to_string());
+            continue;
+        }
+        let (expression, unit) = match token.strip_suffix('%') {
+            Some(expression) => (expression, "%"),
+            None => (*token, ""),
+        };
+        let value = if expression.starts_with(['+', '*', '/']) {
+            let base = current_tokens.get(i)?;
+            let base: f32 = base.trim_end_matches('%').parse().ok()?;
+            eval(&format!("({base}){expression}"))?
+        } else {
+            eval(expression)?
+        };
+        values.push(format!("{}{unit}", trim_number(value)));
+    }
+
+    let args = if comma_separated {
+        values.join(", ")
+    } else {
+        values.join(" ")
+    };
+    Some(format!("{name}({args})"))
+}
+
+/// Allows a trailing `;`, as pasted CSS often has.
+fn function(code: &str) -> Option<(&str, &str)> {
+    let code = code.trim().trim_end_matches(';').trim_end();
+    let (name, rest) = code.split_once('(')?;
+    let args = rest.strip_suffix(')')?;
+    let name = name.trim();
+    name.chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        .then_some((name, args))
+}
+
+/// Arguments separated by commas, or by spaces when there are none. A lone `/` (the CSS
+/// alpha separator) is kept as its own argument.
+fn arguments(args: &str) -> Vec<&str> {
+    if args.contains(',') {
+        args.split(',').map(str::trim).collect()
+    } else {
+        args.split_whitespace().collect()
+    }
+}
+
+fn is_expression(token: &str) -> bool {
+    let token = token.trim_end_matches('%');
+    token != "/"
+        && (token.starts_with('+') || token.parse::<f32>().is_err())
+        && !token.is_empty()
+        && token
+            .chars()
+            .all(|c| c.is_ascii_digit() || "+-*/.() ".contains(c))
+}
+
+fn trim_number(value: f32) -> String {
+    let text = format!("{value:.3}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn eval(expression: &str) -> Option<f32> {
+    let tokens: Vec<char> = expression.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut parser = Parser { tokens, at: 0 };
+    let value = parser.sum()?;
+    (parser.at == parser.tokens.len() && value.is_finite()).then_some(value)
+}
+
+struct Parser {
+    tokens: Vec<char>,
+    at: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.tokens.get(self.at).copied()
+    }
+
+    fn sum(&mut self) -> Option<f32> {
+        let mut value = self.product()?;
+        while let Some(op @ ('+' | '-')) = self.peek() {
+            self.at += 1;
+            let rhs = self.product()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Some(value)
+    }
+
+    fn product(&mut self) -> Option<f32> {
+        let mut value = self.factor()?;
+        while let Some(op @ ('*' | '/')) = self.peek() {
+            self.at += 1;
+            let rhs = self.factor()?;
+            value = if op == '*' { value * rhs } else { value / rhs };
+        }
+        Some(value)
+    }
+
+    fn factor(&mut self) -> Option<f32> {
+        match self.peek()? {
+            '-' => {
+                self.at += 1;
+                Some(-self.factor()?)
+            }
+            '(' => {
+                self.at += 1;
+                let value = self.sum()?;
+                (self.peek()? == ')').then(|| self.at += 1)?;
+                Some(value)
+            }
+            _ => {
+                let start = self.at;
+                while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+                    self.at += 1;
+                }
+                self.tokens[start..self.at]
+                    .iter()
+                    .collect::<String>()
+                    .parse()
+                    .ok()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_absolute_and_relative_arguments() {
+        assert_eq!(
+            expand("rgb(128+16, 64, 32)", "rgb(128, 64, 32)").as_deref(),
+            Some("rgb(144, 64, 32)")
+        );
+        assert_eq!(
+            expand("oklch(0.70 *0.5 180.0)", "oklch(0.70 0.150 180.0)").as_deref(),
+            Some("oklch(0.70 0.075 180.0)")
+        );
+        assert_eq!(
+            expand("hsv(200, +10%, (50-10)*2%)", "hsv(210, 40%, 80%)").as_deref(),
+            Some("hsv(200, 50%, 80%)")
+        );
+        assert_eq!(
+            expand("oklch(0.5 0.1 90 / /2)", "oklch(0.5 0.1 90 / 0.80)").as_deref(),
+            Some("oklch(0.5 0.1 90 / 0.4)")
+        );
+
+        assert_eq!(expand("rgb(128, 64, 32)", "rgb(1, 2, 3)"), None);
+        assert_eq!(expand("rgb(*2, 64, 32)", "oklch(0.5 0.1 90)"), None);
+        assert_eq!(expand("rgb(1+, 64, 32)", "rgb(1, 2, 3)"), None);
+    }
+}
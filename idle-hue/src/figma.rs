@@ -0,0 +1,323 @@
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+
+const API_FILES: &str = "https://api.figma.com/v1/files";
+const COLLECTION_NAME: &str = "idle-hue";
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) enum FigmaStatus {
+    #[default]
+    Idle,
+    Publishing,
+    Published {
+        count: usize,
+    },
+    Error(String),
+}
+
+pub(crate) fn file_key(input: &str) -> Option<&str> {
+    let input = input.trim();
+    let is_key = |key: &&str| !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric());
+    match input.split_once("figma.com/") {
+        Some((_, path)) => {
+            let mut segments = path.split(['/', '?', '#']);
+            let kind = segments.next()?;
+            if !matches!(kind, "file" | "design" | "board" | "proto") {
+                return None;
+            }
+            segments.next().filter(is_key)
+        }
+        None => Some(input).filter(is_key),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Collection {
+    id: String,
+    mode_id: String,
+    variables: HashMap<String, String>,
+}
+
+/// Needs a token with the `file_variables:read` and `file_variables:write` scopes and a
+/// plan that allows variables.
+pub(crate) async fn publish(
+    token: String,
+    file_key: String,
+    colors: Vec<(String, [f32; 3])>,
+) -> Result<usize, String> {
+    if colors.is_empty() {
+        return Err("The palette is empty".to_string());
+    }
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{API_FILES}/{file_key}/variables/local"))
+        .header("X-Figma-Token", token.trim())
+        .send()
+        .await
+        .map_err(|e| format!("Could not reach Figma: {e}"))?;
+    if !response.status().is_success() {
+        return Err(error(response).await);
+    }
+    let local: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Unexpected reply from Figma: {e}"))?;
+    let existing = existing_collection(&local);
+
+    let response = client
+        .post(format!("{API_FILES}/{file_key}/variables"))
+        .header("X-Figma-Token", token.trim())
+        .json(&variables_body(&colors, existing.as_ref()))
+        .send()
+        .await
+        .map_err(|e| format!("Could not reach Figma: {e}"))?;
+    if response.status().is_success() {
+        return Ok(colors.len());
+    }
+    Err(error(response).await)
+}
+
+async fn error(response: reqwest::Response) -> String {
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+    let message = body
+        .get("message")
+        .and_then(Value::as_str)
+        .or(status.canonical_reason())
+        .unwrap_or("request failed");
+    format!("Figma {}: {message}", status.as_u16())
+}
+
+/// Collections and variables from libraries the file uses are skipped.
+fn existing_collection(local: &Value) -> Option<Collection> {
+    let meta = local.get("meta")?;
+    let collection = meta
+        .get("variableCollections")?
+        .as_object()?
+        .values()
+        .find(|c| c["name"] == COLLECTION_NAME && c["remote"] != true)?;
+    let id = collection["id"].as_str()?.to_string();
+    let mode_id = collection["defaultModeId"].as_str()?.to_string();
+    let variables = meta
+        .get("variables")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|variables| variables.values())
+        .filter(|v| {
+            v["variableCollectionId"] == id.as_str()
+                && v["resolvedType"] == "COLOR"
+                && v["deletedButReferenced"] != true
+        })
+        .filter_map(|v| {
+            Some((
+                v["name"].as_str()?.to_string(),
+                v["id"].as_str()?.to_string(),
+            ))
+        })
+        .collect();
+    Some(Collection {
+        id,
+        mode_id,
+        variables,
+    })
+}
+
+/// Ids that aren't real are temporary ones that only have to be unique within
+/// the request; Figma assigns the real ones.
+fn variables_body(colors: &[(String, [f32; 3])], existing: Option<&Collection>) -> Value {
+    let (collection_id, mode_id) = match existing {
+        Some(collection) => (collection.id.as_str(), collection.mode_id.as_str()),
+        None => ("collection", "mode"),
+    };
+    let mut variables = Vec::new();
+    let mut values = Vec::new();
+    for (i, (name, [r, g, b])) in colors.iter().enumerate() {
+        let variable_id = match existing.and_then(|c| c.variables.get(name)) {
+            Some(id) => id.clone(),
+            None => {
+                let id = format!("color_{i}");
+                variables.push(json!({
+                    "action": "CREATE",
+                    "id": id,
+                    "name": name,
+                    "variableCollectionId": collection_id,
+                    "resolvedType": "COLOR",
+                }));
+                id
+            }
+        };
+        values.push(json!({
+            "variableId": variable_id,
+            "modeId": mode_id,
+            "value": { "r": r, "g": g, "b": b, "a": 1.0 },
+        }));
+    }
+    let mut body = json!({
+        "variables": variables,
+        "variableModeValues": values,
+    });
+    if existing.is_none() {
+        body["variableCollections"] = json!([{
+            "action": "CREATE",
+            "id": "collection",
+            "name": COLLECTION_NAME,
+            "initialModeId": "mode",
+        }]);
+    }
+    body
+}
+
+/// Kept in its own file rather than in state.json.
+pub(crate) async fn load_token(path: PathBuf) -> Option<String> {
+    let token = tokio::fs::read_to_string(path).await.ok()?;
+    Some(token.trim().to_string()).filter(|token| !token.is_empty())
+}
+
+pub(crate) async fn save_token(path: PathBuf, token: String) -> Result<(), String> {
+    if token.is_empty() {
+        return match tokio::fs::remove_file(&path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to remove {}: {e}", path.display()))
+            }
+            _ => Ok(()),
+        };
+    }
+    if let Some(parent) = path.parent() {
+        let _ = tokio::fs::create_dir_all(parent).await;
+    }
+    let mut options = tokio::fs::OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options
+        .open(&path)
+        .await
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    // `mode` only applies when the file is created.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))
+            .await
+            .map_err(|e| format!("Failed to protect {}: {e}", path.display()))?;
+    }
+    file.write_all(token.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_keys_come_from_urls_or_bare_keys() {
+        assert_eq!(
+            file_key("https://www.figma.com/design/AbC123xyz/My-Palette?node-id=0-1"),
+            Some("AbC123xyz")
+        );
+        assert_eq!(file_key("figma.com/file/AbC123xyz/Old"), Some("AbC123xyz"));
+        assert_eq!(file_key("  AbC123xyz "), Some("AbC123xyz"));
+        assert_eq!(file_key("https://www.figma.com/community/plugin/1"), None);
+        assert_eq!(file_key("not a key"), None);
+    }
+
+    #[test]
+    fn body_links_values_to_created_variables() {
+        let body = variables_body(&[("palette/01".to_string(), [1.0, 0.5, 0.0])], None);
+
+        assert_eq!(body["variableCollections"][0]["name"], COLLECTION_NAME);
+        assert_eq!(body["variables"][0]["name"], "palette/01");
+        assert_eq!(body["variables"][0]["variableCollectionId"], "collection");
+        assert_eq!(body["variableModeValues"][0]["variableId"], "color_0");
+        assert_eq!(body["variableModeValues"][0]["modeId"], "mode");
+        assert_eq!(body["variableModeValues"][0]["value"]["g"], 0.5);
+    }
+
+    #[test]
+    fn later_publishes_update_the_existing_collection() {
+        let local = json!({
+            "status": 200,
+            "error": false,
+            "meta": {
+                "variableCollections": {
+                    "VariableCollectionId:1:1": {
+                        "id": "VariableCollectionId:1:1",
+                        "name": COLLECTION_NAME,
+                        "defaultModeId": "1:0",
+                        "remote": false,
+                    },
+                    "VariableCollectionId:9:9": {
+                        "id": "VariableCollectionId:9:9",
+                        "name": COLLECTION_NAME,
+                        "defaultModeId": "9:0",
+                        "remote": true,
+                    },
+                },
+                "variables": {
+                    "VariableID:1:2": {
+                        "id": "VariableID:1:2",
+                        "name": "palette/01",
+                        "variableCollectionId": "VariableCollectionId:1:1",
+                        "resolvedType": "COLOR",
+                    },
+                    "VariableID:9:2": {
+                        "id": "VariableID:9:2",
+                        "name": "palette/02",
+                        "variableCollectionId": "VariableCollectionId:9:9",
+                        "resolvedType": "COLOR",
+                    },
+                },
+            },
+        });
+        let existing = existing_collection(&local).unwrap();
+        assert_eq!(existing.id, "VariableCollectionId:1:1");
+        assert_eq!(existing.variables.len(), 1);
+
+        let colors = [
+            ("palette/01".to_string(), [1.0, 0.5, 0.0]),
+            ("palette/02".to_string(), [0.0, 0.0, 1.0]),
+        ];
+        let body = variables_body(&colors, Some(&existing));
+
+        assert!(body.get("variableCollections").is_none());
+        assert_eq!(body["variables"].as_array().unwrap().len(), 1);
+        assert_eq!(body["variables"][0]["name"], "palette/02");
+        assert_eq!(
+            body["variables"][0]["variableCollectionId"],
+            "VariableCollectionId:1:1"
+        );
+        assert_eq!(
+            body["variableModeValues"][0]["variableId"],
+            "VariableID:1:2"
+        );
+        assert_eq!(body["variableModeValues"][0]["modeId"], "1:0");
+        assert_eq!(body["variableModeValues"][1]["variableId"], "color_1");
+        assert!(existing_collection(&json!({ "meta": {} })).is_none());
+    }
+
+    #[tokio::test]
+    async fn tokens_live_in_an_owner_only_file() {
+        let path =
+            std::env::temp_dir().join(format!("idle-hue-figma-token-{}", std::process::id()));
+        save_token(path.clone(), "figd_secret".to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            load_token(path.clone()).await.as_deref(),
+            Some("figd_secret")
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        save_token(path.clone(), String::new()).await.unwrap();
+        assert!(!path.exists());
+        assert_eq!(load_token(path).await, None);
+    }
+}
@@ -0,0 +1,151 @@
+use color::{AlphaColor, DisplayP3, Oklab, Oklch, Srgb};
+
+const EPSILON: f32 = 1e-4;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum GamutMapping {
+    /// Clamp each sRGB channel independently. Fast, but can shift hue and lightness.
+    #[default]
+    Clamp,
+    /// Reduce chroma at constant lightness and hue until the color fits (CSS Color 4).
+    Perceptual,
+}
+
+impl GamutMapping {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            GamutMapping::Clamp => "clip",
+            GamutMapping::Perceptual => "map",
+        }
+    }
+
+    pub(crate) fn toggled(self) -> Self {
+        match self {
+            GamutMapping::Clamp => GamutMapping::Perceptual,
+            GamutMapping::Perceptual => GamutMapping::Clamp,
+        }
+    }
+
+    pub(crate) fn to_srgb(self, oklch: [f32; 3]) -> [f32; 3] {
+        match self {
+            GamutMapping::Clamp => clip(unclamped_srgb(oklch)),
+            GamutMapping::Perceptual => css_gamut_map(oklch),
+        }
+    }
+}
+
+pub(crate) fn in_srgb_gamut(oklch: [f32; 3]) -> bool {
+    unclamped_srgb(oklch)
+        .iter()
+        .all(|c| (-EPSILON..=1.0 + EPSILON).contains(c))
+}
+
+pub(crate) fn to_display_p3([l, c, h]: [f32; 3]) -> [f32; 3] {
+    let [r, g, b, _] = AlphaColor::<Oklch>::new([l, c, h, 1.0])
+        .convert::<DisplayP3>()
+        .components;
+    clip([r, g, b])
+}
+
+fn unclamped_srgb([l, c, h]: [f32; 3]) -> [f32; 3] {
+    let [r, g, b, _] = AlphaColor::<Oklch>::new([l, c, h, 1.0])
+        .convert::<Srgb>()
+        .components;
+    [r, g, b]
+}
+
+fn clip(rgb: [f32; 3]) -> [f32; 3] {
+    rgb.map(|c| {
+        if c.is_finite() {
+            c.clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    })
+}
+
+fn delta_e_ok(oklch: [f32; 3], [r, g, b]: [f32; 3]) -> f32 {
+    let [l1, a1, b1, _] = AlphaColor::<Oklch>::new([oklch[0], oklch[1], oklch[2], 1.0])
+        .convert::<Oklab>()
+        .components;
+    let [l2, a2, b2, _] = AlphaColor::<Srgb>::new([r, g, b, 1.0])
+        .convert::<Oklab>()
+        .components;
+    ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+}
+
+/// Binary search on chroma, accepting a clipped result once it is within a
+/// just-noticeable difference of the reduced color.
+fn css_gamut_map([l, c, h]: [f32; 3]) -> [f32; 3] {
+    const JND: f32 = 0.02;
+
+    if l >= 1.0 {
+        return [1.0; 3];
+    }
+    if l <= 0.0 {
+        return [0.0; 3];
+    }
+    if in_srgb_gamut([l, c, h]) {
+        return clip(unclamped_srgb([l, c, h]));
+    }
+
+    let mut clipped = clip(unclamped_srgb([l, c, h]));
+    if delta_e_ok([l, c, h], clipped) < JND {
+        return clipped;
+    }
+
+    let (mut min, mut max) = (0.0_f32, c);
+    let mut min_in_gamut = true;
+    while max - min > EPSILON {
+        let chroma = (min + max) / 2.0;
+        let current = [l, chroma, h];
+        if min_in_gamut && in_srgb_gamut(current) {
+            min = chroma;
+            continue;
+        }
+        clipped = clip(unclamped_srgb(current));
+        let error = delta_e_ok(current, clipped);
+        if error < JND {
+            if JND - error < EPSILON {
+                break;
+            }
+            min_in_gamut = false;
+            min = chroma;
+        } else {
+            max = chroma;
+        }
+    }
+    clipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_gamut_colors_are_unchanged() {
+        let oklch = [0.7, 0.1, 180.0];
+        assert!(in_srgb_gamut(oklch));
+        let clamped = GamutMapping::Clamp.to_srgb(oklch);
+        let mapped = GamutMapping::Perceptual.to_srgb(oklch);
+        for (a, b) in clamped.iter().zip(mapped) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn perceptual_mapping_preserves_hue_better_than_clamping() {
+        let oklch = [0.8, 0.37, 145.0];
+        assert!(!in_srgb_gamut(oklch));
+
+        let hue_of = |[r, g, b]: [f32; 3]| {
+            AlphaColor::<Srgb>::new([r, g, b, 1.0])
+                .convert::<Oklch>()
+                .components[2]
+        };
+        let mapped = GamutMapping::Perceptual.to_srgb(oklch);
+        let clamped = GamutMapping::Clamp.to_srgb(oklch);
+        assert!(mapped.iter().all(|c| (0.0..=1.0).contains(c)));
+        assert!((hue_of(mapped) - 145.0).abs() < (hue_of(clamped) - 145.0).abs());
+    }
+}
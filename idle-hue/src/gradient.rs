@@ -0,0 +1,70 @@
+use crate::blend;
+use crate::color_mode::ColorMode;
+
+pub(crate) const MAX_STOPS: usize = 8;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct GradientPreset {
+    pub(crate) stops: Vec<[f32; 3]>,
+}
+
+impl GradientPreset {
+    pub(crate) fn samples(&self, count: usize) -> Vec<[f32; 3]> {
+        let (Some(&first), Some(&last)) = (self.stops.first(), self.stops.last()) else {
+            return Vec::new();
+        };
+        if count < 2 || self.stops.len() < 2 {
+            return vec![first; count];
+        }
+        let spans = (self.stops.len() - 1) as f32;
+        (0..count)
+            .map(|i| {
+                if i == count - 1 {
+                    return last;
+                }
+                let position = i as f32 / (count - 1) as f32 * spans;
+                let span = (position.floor() as usize).min(self.stops.len() - 2);
+                blend::mix(
+                    self.stops[span],
+                    self.stops[span + 1],
+                    position - span as f32,
+                )
+            })
+            .collect()
+    }
+
+    /// Interpolated in OKLCH, like the preview.
+    pub(crate) fn css(&self) -> String {
+        let stops = self
+            .stops
+            .iter()
+            .map(|&stop| ColorMode::Oklch.format(stop, 1.0))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("linear-gradient(to right in oklch, {stops})")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_span_every_stop_and_export_as_css() {
+        let gradient = GradientPreset {
+            stops: vec![[0.2, 0.1, 0.0], [0.6, 0.1, 90.0], [1.0, 0.0, 90.0]],
+        };
+        let samples = gradient.samples(5);
+        assert_eq!(samples.len(), 5);
+        assert_eq!(samples[0], gradient.stops[0]);
+        assert_eq!(samples[2], gradient.stops[1]);
+        assert_eq!(samples[4], gradient.stops[2]);
+        assert!((samples[1][0] - 0.4).abs() < 1e-6 && (samples[1][2] - 45.0).abs() < 1e-4);
+
+        assert_eq!(
+            gradient.css(),
+            "linear-gradient(to right in oklch, oklch(0.20 0.100 0.0), \
+             oklch(0.60 0.100 90.0), oklch(1.00 0.000 90.0))"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum HistorySource {
+    Picked,
+    Pasted,
+    Edited,
+    Copied,
+    Random,
+    Linked,
+}
+
+impl HistorySource {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            HistorySource::Picked => "picked",
+            HistorySource::Pasted => "pasted",
+            HistorySource::Edited => "edited",
+            HistorySource::Copied => "copied",
+            HistorySource::Random => "random",
+            HistorySource::Linked => "linked",
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct HistoryEntry {
+    /// Seconds since the Unix epoch.
+    pub(crate) time: u64,
+    pub(crate) oklch: [f32; 3],
+    pub(crate) alpha: f32,
+    pub(crate) hex: String,
+    pub(crate) source: HistorySource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) profile: Option<String>,
+}
+
+impl HistoryEntry {
+    pub(crate) fn new(oklch: [f32; 3], alpha: f32, hex: String, source: HistorySource) -> Self {
+        Self {
+            time: now(),
+            oklch,
+            alpha,
+            hex,
+            source,
+            profile: None,
+        }
+    }
+
+    pub(crate) fn matches(&self, query: &str) -> bool {
+        let query = query.trim().trim_start_matches('#').to_lowercase();
+        query.is_empty()
+            || self.hex.to_lowercase().contains(&query)
+            || self.source.label().contains(&query)
+            || self
+                .profile
+                .as_ref()
+                .is_some_and(|profile| profile.to_lowercase().contains(&query))
+    }
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+pub(crate) fn age(time: u64, now: u64) -> String {
+    let seconds = now.saturating_sub(time);
+    match seconds {
+        0..60 => "just now".to_string(),
+        60..3_600 => format!("{}m ago", seconds / 60),
+        3_600..86_400 => format!("{}h ago", seconds / 3_600),
+        86_400..1_209_600 => format!("{}d ago", seconds / 86_400),
+        _ => format!("{}w ago", seconds / 604_800),
+    }
+}
+
+pub(crate) async fn load(path: PathBuf) -> Vec<HistoryEntry> {
+    let Ok(content) = tokio::fs::read_to_string(path).await else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+pub(crate) async fn append(path: PathBuf, entry: HistoryEntry) -> Result<(), String> {
+    let mut line = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+    line.push('\n');
+    if let Some(parent) = path.parent() {
+        let _ = tokio::fs::create_dir_all(parent).await;
+    }
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .await
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    file.write_all(line.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ages_and_search() {
+        assert_eq!(age(1_000, 1_030), "just now");
+        assert_eq!(age(1_000, 1_000 + 5 * 60), "5m ago");
+        assert_eq!(age(1_000, 1_000 + 3 * 86_400), "3d ago");
+        assert_eq!(age(2_000, 1_000), "just now");
+
+        let entry = HistoryEntry::new(
+            [0.6, 0.1, 240.0],
+            1.0,
+            "#4682B4".to_string(),
+            HistorySource::Picked,
+        );
+        assert!(entry.matches("#4682b"));
+        assert!(entry.matches("pick"));
+        assert!(entry.matches(""));
+        assert!(!entry.matches("pasted"));
+    }
+}
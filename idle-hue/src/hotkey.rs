@@ -0,0 +1,71 @@
+use global_hotkey::hotkey::HotKey;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+
+pub(crate) const DEFAULT_HOTKEY: &str = "CmdOrCtrl+Shift+C";
+
+/// A system-wide shortcut that stays registered for as long as this is alive.
+pub(crate) struct GlobalHotkey {
+    manager: GlobalHotKeyManager,
+    hotkey: HotKey,
+}
+
+impl GlobalHotkey {
+    /// `on_press` runs on whichever thread the platform delivers hotkey events on.
+    pub(crate) fn register(
+        shortcut: &str,
+        on_press: impl Fn() + Send + Sync + 'static,
+    ) -> Result<Self, String> {
+        let hotkey: HotKey = shortcut
+            .parse()
+            .map_err(|e| format!("Invalid hotkey {shortcut:?}: {e}"))?;
+        let manager = GlobalHotKeyManager::new().map_err(|e| e.to_string())?;
+        manager
+            .register(hotkey)
+            .map_err(|e| format!("Failed to register hotkey {shortcut:?}: {e}"))?;
+        let id = hotkey.id();
+        GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+            if event.id == id && event.state == HotKeyState::Pressed {
+                on_press();
+            }
+        }));
+        Ok(Self { manager, hotkey })
+    }
+}
+
+impl Drop for GlobalHotkey {
+    fn drop(&mut self) {
+        GlobalHotKeyEvent::set_event_handler(None::<fn(GlobalHotKeyEvent)>);
+        let _ = self.manager.unregister(self.hotkey);
+    }
+}
+
+/// Must be called on the main thread.
+pub(crate) fn bring_to_front() {
+    #[cfg(target_os = "macos")]
+    {
+        use objc2::MainThreadMarker;
+        use objc2_app_kit::NSApplication;
+
+        if let Some(mtm) = MainThreadMarker::new() {
+            #[allow(deprecated)]
+            NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows_sys::Win32::UI::WindowsAndMessaging::{
+            IsIconic, SW_RESTORE, SetForegroundWindow, ShowWindow,
+        };
+
+        let Some(hwnd) = crate::window_geometry::windows::window() else {
+            return;
+        };
+        unsafe {
+            if IsIconic(hwnd) != 0 {
+                ShowWindow(hwnd, SW_RESTORE);
+            }
+            SetForegroundWindow(hwnd);
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use color::{AlphaColor, Srgb, XyzD50};
+
+const HEADER_LEN: usize = 128;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Profile {
+    pub(crate) description: String,
+    /// The red, green and blue colorants in the D50 connection space.
+    colorants: [[f32; 3]; 3],
+    curves: [Curve; 3],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Curve {
+    Gamma(f32),
+    Table(Vec<f32>),
+    /// An ICC `para` curve: the function type and its parameters `g, a, b, c, d, e, f`.
+    Parametric(u16, [f32; 7]),
+}
+
+impl Curve {
+    fn eval(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        match self {
+            Curve::Gamma(g) => x.powf(*g),
+            Curve::Table(table) => {
+                let position = x * (table.len() - 1) as f32;
+                let i = (position.floor() as usize).min(table.len() - 2);
+                let t = position - i as f32;
+                table[i] + (table[i + 1] - table[i]) * t
+            }
+            Curve::Parametric(kind, [g, a, b, c, d, e, f]) => match kind {
+                0 => x.powf(*g),
+                1 if x >= -b / a => (a * x + b).powf(*g),
+                1 => 0.0,
+                2 if x >= -b / a => (a * x + b).powf(*g) + c,
+                2 => *c,
+                3 if x >= *d => (a * x + b).powf(*g),
+                3 => c * x,
+                _ if x >= *d => (a * x + b).powf(*g) + e,
+                _ => c * x + f,
+            },
+        }
+    }
+}
+
+impl Profile {
+    pub(crate) fn is_wide_gamut(&self) -> bool {
+        const SRGB_D50: [[f32; 3]; 3] = [
+            [0.4361, 0.2225, 0.0139],
+            [0.3851, 0.7169, 0.0971],
+            [0.1431, 0.0606, 0.7141],
+        ];
+        gamut_area(&self.colorants) > gamut_area(&SRGB_D50) * 1.15
+    }
+
+    /// Colors outside sRGB come back outside `0.0..=1.0` rather than clamped.
+    pub(crate) fn to_srgb(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let mut xyz = [0.0; 3];
+        for (channel, value) in rgb.into_iter().enumerate() {
+            let linear = self.curves[channel].eval(value);
+            for (axis, component) in xyz.iter_mut().enumerate() {
+                *component += linear * self.colorants[channel][axis];
+            }
+        }
+        let [r, g, b, _] = AlphaColor::<XyzD50>::new([xyz[0], xyz[1], xyz[2], 1.0])
+            .convert::<Srgb>()
+            .components;
+        [r, g, b]
+    }
+}
+
+fn gamut_area(colorants: &[[f32; 3]; 3]) -> f32 {
+    let [r, g, b] = colorants.map(|[x, y, z]| {
+        let sum = x + y + z;
+        [x / sum, y / sum]
+    });
+    ((g[0] - r[0]) * (b[1] - r[1]) - (b[0] - r[0]) * (g[1] - r[1])).abs() / 2.0
+}
+
+/// Only matrix/TRC RGB profiles are supported.
+pub(crate) fn parse(bytes: &[u8]) -> Result<Profile, String> {
+    if bytes.len() < HEADER_LEN + 4 || &bytes[36..40] != b"acsp" {
+        return Err("Not an ICC profile".to_string());
+    }
+    if &bytes[16..20] != b"RGB " {
+        return Err("Not an RGB profile".to_string());
+    }
+    let tag_count = u32_at(bytes, HEADER_LEN)? as usize;
+    // The count comes from the file, so only reserve what the table could actually hold.
+    let mut tags = Vec::with_capacity(tag_count.min((bytes.len() - HEADER_LEN - 4) / 12));
+    for i in 0..tag_count {
+        let entry = HEADER_LEN + 4 + i * 12;
+        let signature = bytes.get(entry..entry + 4).ok_or("Truncated tag table")?;
+        let offset = u32_at(bytes, entry + 4)? as usize;
+        let size = u32_at(bytes, entry + 8)? as usize;
+        let data = bytes
+            .get(offset..offset + size)
+            .ok_or("Tag points outside the profile")?;
+        tags.push((signature, data));
+    }
+    let tag = |signature: &[u8; 4]| {
+        tags.iter()
+            .find(|(s, _)| *s == signature)
+            .map(|(_, data)| *data)
+            .ok_or_else(|| {
+                format!(
+                    "Profile has no {} tag; only matrix/TRC profiles are supported",
+                    String::from_utf8_lossy(signature)
+                )
+            })
+    };
+
+    Ok(Profile {
+        description: tags
+            .iter()
+            .find(|(s, _)| *s == b"desc")
+            .and_then(|(_, data)| description(data))
+            .unwrap_or_default(),
+        colorants: [
+            xyz(tag(b"rXYZ")?)?,
+            xyz(tag(b"gXYZ")?)?,
+            xyz(tag(b"bXYZ")?)?,
+        ],
+        curves: [
+            curve(tag(b"rTRC")?)?,
+            curve(tag(b"gTRC")?)?,
+            curve(tag(b"bTRC")?)?,
+        ],
+    })
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Result<u16, String> {
+    bytes
+        .get(at..at + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| "Truncated profile".to_string())
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Result<u32, String> {
+    bytes
+        .get(at..at + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| "Truncated profile".to_string())
+}
+
+/// An `s15Fixed16Number`.
+fn fixed_at(bytes: &[u8], at: usize) -> Result<f32, String> {
+    Ok(u32_at(bytes, at)? as i32 as f32 / 65536.0)
+}
+
+fn xyz(data: &[u8]) -> Result<[f32; 3], String> {
+    if !data.starts_with(b"XYZ ") {
+        return Err("Colorant is not an XYZ tag".to_string());
+    }
+    Ok([fixed_at(data, 8)?, fixed_at(data, 12)?, fixed_at(data, 16)?])
+}
+
+fn curve(data: &[u8]) -> Result<Curve, String> {
+    match data.get(..4) {
+        Some(b"curv") => {
+            let count = u32_at(data, 8)? as usize;
+            match count {
+                0 => Ok(Curve::Gamma(1.0)),
+                1 => Ok(Curve::Gamma(u16_at(data, 12)? as f32 / 256.0)),
+                _ => (0..count)
+                    .map(|i| Ok(u16_at(data, 12 + i * 2)? as f32 / 65535.0))
+                    .collect::<Result<_, String>>()
+                    .map(Curve::Table),
+            }
+        }
+        Some(b"para") => {
+            let kind = u16_at(data, 8)?;
+            let count = match kind {
+                0 => 1,
+                1 => 3,
+                2 => 4,
+                3 => 5,
+                4 => 7,
+                _ => return Err(format!("Unknown parametric curve type {kind}")),
+            };
+            let mut params = [0.0; 7];
+            for (i, param) in params.iter_mut().take(count).enumerate() {
+                *param = fixed_at(data, 12 + i * 4)?;
+            }
+            Ok(Curve::Parametric(kind, params))
+        }
+        _ => Err("Unsupported tone curve".to_string()),
+    }
+}
+
+/// Reads a v2 `desc` or v4 `mluc` description, taking the first localization.
+fn description(data: &[u8]) -> Option<String> {
+    match data.get(..4)? {
+        b"desc" => {
+            let len = u32_at(data, 8).ok()? as usize;
+            let text = data.get(12..12 + len)?;
+            let text = text.split(|b| *b == 0).next()?;
+            Some(String::from_utf8_lossy(text).into_owned())
+        }
+        b"mluc" => {
+            let len = u32_at(data, 20).ok()? as usize;
+            let offset = u32_at(data, 24).ok()? as usize;
+            let units: Vec<u16> = data
+                .get(offset..offset + len)?
+                .chunks_exact(2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                .collect();
+            Some(String::from_utf16_lossy(&units))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal v4 Display P3 profile: D50-adapted P3 colorants and the sRGB curve.
+    fn display_p3() -> Vec<u8> {
+        let fixed = |v: f32| ((v * 65536.0).round() as i32).to_be_bytes();
+        let xyz = |[x, y, z]: [f32; 3]| {
+            let mut tag = b"XYZ \0\0\0\0".to_vec();
+            for v in [x, y, z] {
+                tag.extend(fixed(v));
+            }
+            tag
+        };
+        let mut trc = b"para\0\0\0\0\0\x03\0\0".to_vec();
+        for v in [2.4, 1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92, 0.04045] {
+            trc.extend(fixed(v));
+        }
+        let mut desc = b"mluc\0\0\0\0\0\0\0\x01\0\0\0\x0cenUS".to_vec();
+        let name: Vec<u8> = "Display P3"
+            .encode_utf16()
+            .flat_map(u16::to_be_bytes)
+            .collect();
+        desc.extend((name.len() as u32).to_be_bytes());
+        desc.extend(28u32.to_be_bytes());
+        desc.extend(name);
+        let tags: [(&[u8; 4], Vec<u8>); 7] = [
+            (b"desc", desc),
+            (b"rXYZ", xyz([0.5151, 0.2412, -0.0011])),
+            (b"gXYZ", xyz([0.2920, 0.6922, 0.0419])),
+            (b"bXYZ", xyz([0.1571, 0.0666, 0.7841])),
+            (b"rTRC", trc.clone()),
+            (b"gTRC", trc.clone()),
+            (b"bTRC", trc),
+        ];
+
+        let mut bytes = vec![0; HEADER_LEN];
+        bytes[16..20].copy_from_slice(b"RGB ");
+        bytes[36..40].copy_from_slice(b"acsp");
+        bytes.extend((tags.len() as u32).to_be_bytes());
+        let mut offset = HEADER_LEN + 4 + tags.len() * 12;
+        let mut data: Vec<u8> = Vec::new();
+        for (signature, tag) in &tags {
+            bytes.extend(*signature);
+            bytes.extend((offset as u32).to_be_bytes());
+            bytes.extend((tag.len() as u32).to_be_bytes());
+            offset += tag.len();
+            data.extend(tag);
+        }
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn converts_wide_gamut_pixels_to_srgb() {
+        let profile = parse(&display_p3()).unwrap();
+        assert_eq!(profile.description, "Display P3");
+
+        let white = profile.to_srgb([1.0, 1.0, 1.0]);
+        assert!(white.iter().all(|c| (c - 1.0).abs() < 0.01), "{white:?}");
+        // Pure P3 red lies outside sRGB.
+        let red = profile.to_srgb([1.0, 0.0, 0.0]);
+        assert!(red[0] > 1.05 && red[1] < 0.0, "{red:?}");
+        assert!(profile.is_wide_gamut());
+
+        assert!(parse(&[0; 64]).is_err());
+
+        let mut huge = display_p3();
+        huge[HEADER_LEN..HEADER_LEN + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(parse(&huge).is_err());
+    }
+}
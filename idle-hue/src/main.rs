@@ -2,9 +2,40 @@
 #![allow(clippy::type_complexity)]
 #![allow(clippy::too_many_arguments)]
 
+mod ase;
 mod auto_update;
-#[cfg(not(target_os = "windows"))]
+mod blend;
+mod blend_modes;
+mod color_mode;
+mod contrast;
+mod contrast_grid;
+mod copy_formats;
+mod drag;
 mod dropper;
+mod export;
+mod expr;
+mod figma;
+mod gamut;
+mod gradient;
+mod history;
+mod hotkey;
+// Only Windows reads display profiles itself; macOS converts samples for us.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+mod icc;
+mod material;
+mod named_colors;
+mod palette_link;
+mod palette_png;
+mod relative;
+mod single_instance;
+mod tailwind;
+mod toast;
+mod tokens;
+mod ui_scale;
+mod ui_tint;
+mod undo;
+mod url_scheme;
+mod window_geometry;
 
 #[cfg(target_os = "windows")]
 use ::winit::platform::windows::IconExtWindows;
@@ -12,23 +43,118 @@ use ::winit::window::Icon;
 use app_update::restart_application;
 use arboard::Clipboard;
 use auto_update::{AutoUpdater, UpdateStatus};
-use color::{AlphaColor, ColorSpaceTag, Oklch, Srgb, parse_color};
+use blend_modes::BlendMode;
+use color::{AlphaColor, ColorSpaceTag, DisplayP3, Oklab, Oklch, Srgb, parse_color};
+use color_mode::{ColorMode, Components, MAX_CHANNELS, OKLCH_CHANNELS, parse_mode_code};
+use contrast::ContrastAlgorithm;
+use copy_formats::CopyFormat;
+use drag::SliderDrag;
+use dropper::CaptureSpace;
+use export::PaletteExport;
+use figma::FigmaStatus;
+use gamut::{GamutMapping, in_srgb_gamut};
+use gradient::GradientPreset;
 use haven::winit::WinitApp;
 use haven::*;
+use history::{HistoryEntry, HistorySource};
+use hotkey::GlobalHotkey;
+use rand::Rng;
+use relative::RelativeBase;
+use semver::Version;
 use std::array::from_fn;
-use std::sync::Arc;
 use std::sync::mpsc::{Receiver, Sender, channel};
-use tokio::sync::Mutex;
+use toast::{ToastKind, Toasts};
+use ui_scale::{font, px};
+use ui_tint::UiTint;
+use undo::UndoStack;
+use window_geometry::WindowGeometry;
 
 type UiCallback = Box<dyn FnOnce(&mut State, &mut PaneState) + Send>;
 
 #[derive(Clone, Debug)]
 struct PaletteState {
-    colors: [Option<[f32; 3]>; PALETTE_SIZE],
+    // Always a whole number of pages.
+    colors: Vec<Option<[f32; 3]>>,
+    page: usize,
     hover: [bool; PALETTE_SIZE],
+    insert_on_drop: bool,
     dragging: Option<usize>,
     drag_target: PaletteDragTarget,
     drag_offset: Point,
+    blending: bool,
+    blend_ends: Vec<usize>,
+    blend_steps: usize,
+    labels: Vec<String>,
+    labeling: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BlendAction {
+    Toggle,
+    Fewer,
+    More,
+    Fill,
+    Copy,
+}
+
+impl BlendAction {
+    const ALL: [BlendAction; 5] = [
+        BlendAction::Toggle,
+        BlendAction::Fewer,
+        BlendAction::More,
+        BlendAction::Fill,
+        BlendAction::Copy,
+    ];
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct UndoSnapshot {
+    values: [f32; 3],
+    alpha: f32,
+    palette: Vec<Option<[f32; 3]>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PaletteSort {
+    Hue,
+    Lightness,
+    Chroma,
+}
+
+impl PaletteSort {
+    const ALL: [PaletteSort; 3] = [
+        PaletteSort::Hue,
+        PaletteSort::Lightness,
+        PaletteSort::Chroma,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            PaletteSort::Hue => "sort by hue",
+            PaletteSort::Lightness => "sort by lightness",
+            PaletteSort::Chroma => "sort by chroma",
+        }
+    }
+
+    // Grays are collected after the hues, light to dark.
+    fn compare(self, a: [f32; 3], b: [f32; 3]) -> std::cmp::Ordering {
+        // Below this chroma a color's hue is noise.
+        const GRAY_CHROMA: f32 = 0.02;
+        match self {
+            PaletteSort::Hue => {
+                let (a_gray, b_gray) = (a[1] < GRAY_CHROMA, b[1] < GRAY_CHROMA);
+                a_gray.cmp(&b_gray).then_with(|| {
+                    if a_gray {
+                        b[0].total_cmp(&a[0])
+                    } else {
+                        a[2].rem_euclid(360.0).total_cmp(&b[2].rem_euclid(360.0))
+                    }
+                })
+            }
+            PaletteSort::Lightness => b[0].total_cmp(&a[0]),
+            PaletteSort::Chroma => b[1].total_cmp(&a[1]),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -39,6 +165,81 @@ enum PaletteDragTarget {
     Delete,
 }
 
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+enum ThemePreference {
+    System,
+    Light,
+    Dark,
+}
+
+impl ThemePreference {
+    fn next(self) -> Self {
+        match self {
+            ThemePreference::Dark => ThemePreference::Light,
+            ThemePreference::Light => ThemePreference::System,
+            ThemePreference::System => ThemePreference::Dark,
+        }
+    }
+
+    fn icon(self) -> &'static str {
+        match self {
+            ThemePreference::System => MONITOR_ICON,
+            ThemePreference::Light => SUN_ICON,
+            ThemePreference::Dark => MOON_ICON,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct CopyMenu {
+    position: Point,
+    buttons: [ButtonState; CopyFormat::ALL.len()],
+    base_buttons: [ButtonState; 6],
+}
+
+struct FigmaPanel {
+    open: bool,
+    token: TextState,
+    file: TextState,
+    publish_button: ButtonState,
+    status: FigmaStatus,
+}
+
+struct BlendPreviewPanel {
+    open: bool,
+    background: TextState,
+    mode: BlendMode,
+    alpha: f32,
+    mode_buttons: [ButtonState; BlendMode::ALL.len()],
+    alpha_buttons: [ButtonState; 2],
+    copy_button: ButtonState,
+}
+
+struct GradientPanel {
+    open: bool,
+    draft: Vec<[f32; 3]>,
+    add_button: ButtonState,
+    clear_button: ButtonState,
+    save_button: ButtonState,
+    copy_buttons: [ButtonState; MAX_GRADIENTS],
+    delete_buttons: [ButtonState; MAX_GRADIENTS],
+}
+
+struct SwatchPanel {
+    open: bool,
+    size_buttons: [ButtonState; SWATCH_SIZES.len()],
+    label_button: ButtonState,
+    save_button: ButtonState,
+}
+
+// `hotkey` is only copied to `State::hotkey` once the new shortcut registers.
+struct PickerSettingsPanel {
+    open: bool,
+    hotkey: TextState,
+    hotkey_dropper_button: ButtonState,
+    capture_space_buttons: [ButtonState; CaptureSpace::ALL.len()],
+}
+
 #[derive(Clone, Copy, Debug)]
 struct TextPopover {
     field: usize,
@@ -51,11 +252,206 @@ struct TextPopover {
 impl Default for PaletteState {
     fn default() -> Self {
         Self {
-            colors: from_fn(|_| None),
+            colors: vec![None; PALETTE_SIZE],
+            page: 0,
+            insert_on_drop: false,
             hover: [false; PALETTE_SIZE],
             dragging: None,
             drag_target: PaletteDragTarget::None,
             drag_offset: Point::ZERO,
+            blending: false,
+            blend_ends: Vec::new(),
+            blend_steps: 5,
+            labels: Vec::new(),
+            labeling: false,
+        }
+    }
+}
+
+impl PaletteState {
+    fn page_count(&self) -> usize {
+        self.colors.len() / PALETTE_SIZE
+    }
+
+    fn row_label(&self, row: usize) -> &str {
+        self.labels
+            .get(self.page * PALETTE_HEIGHT + row)
+            .map_or("", String::as_str)
+    }
+
+    fn set_row_label(&mut self, row: usize, label: &str) {
+        let index = self.page * PALETTE_HEIGHT + row;
+        if self.labels.len() <= index {
+            self.labels.resize(index + 1, String::new());
+        }
+        self.labels[index] = label.trim().to_string();
+        while self.labels.last().is_some_and(String::is_empty) {
+            self.labels.pop();
+        }
+    }
+
+    fn move_swatch(&mut self, from: usize, to: usize) {
+        if self.insert_on_drop {
+            let color = self.colors.remove(from);
+            self.colors.insert(to, color);
+        } else {
+            self.colors.swap(from, to);
+        }
+    }
+
+    fn set_colors(&mut self, mut colors: Vec<Option<[f32; 3]>>) {
+        colors.resize(
+            colors.len().div_ceil(PALETTE_SIZE).max(1) * PALETTE_SIZE,
+            None,
+        );
+        self.colors = colors;
+        self.page = 0;
+        self.trim_empty_pages();
+    }
+
+    // Paging past a last page that has colors starts a new one; empty pages past the
+    // current one are dropped.
+    fn turn_page(&mut self, forward: bool) {
+        if forward {
+            let page_is_empty = self.page_cells(self.page).iter().all(Option::is_none);
+            if self.page + 1 == self.page_count() {
+                if page_is_empty {
+                    return;
+                }
+                self.colors.extend([None; PALETTE_SIZE]);
+            }
+            self.page += 1;
+        } else if self.page > 0 {
+            self.page -= 1;
+            self.trim_empty_pages();
+        }
+        self.hover = [false; PALETTE_SIZE];
+    }
+
+    fn import(&mut self, cells: &[Option<[f32; 3]>]) {
+        let used_pages = self
+            .colors
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |last| last / PALETTE_SIZE + 1);
+        self.colors.truncate(used_pages * PALETTE_SIZE);
+        self.colors.extend_from_slice(cells);
+        self.colors.resize(
+            self.colors.len().div_ceil(PALETTE_SIZE).max(1) * PALETTE_SIZE,
+            None,
+        );
+        self.page = used_pages.min(self.page_count() - 1);
+        self.hover = [false; PALETTE_SIZE];
+    }
+
+    fn fill_empty(&mut self, colors: &[[f32; 3]]) {
+        let mut index = self.page * PALETTE_SIZE;
+        for color in colors {
+            loop {
+                if index == self.colors.len() {
+                    self.colors.extend([None; PALETTE_SIZE]);
+                }
+                if self.colors[index].is_none() {
+                    break;
+                }
+                index += 1;
+            }
+            self.colors[index] = Some(*color);
+        }
+    }
+
+    fn sort(&mut self, by: PaletteSort) {
+        let mut colors: Vec<[f32; 3]> = self.colors.iter().flatten().copied().collect();
+        colors.sort_by(|a, b| by.compare(*a, *b));
+        self.set_colors(colors.into_iter().map(Some).collect());
+        self.hover = [false; PALETTE_SIZE];
+    }
+
+    fn merge_duplicates(&mut self) -> usize {
+        let oklab = |[l, c, h]: [f32; 3]| {
+            let [l, a, b, _] = AlphaColor::<Oklch>::new([l, c, h, 1.0])
+                .convert::<Oklab>()
+                .components;
+            [l, a, b]
+        };
+        let mut kept: Vec<[f32; 3]> = Vec::new();
+        let mut removed = 0;
+        for cell in &mut self.colors {
+            let Some(color) = *cell else {
+                continue;
+            };
+            let lab = oklab(color);
+            let duplicate = kept.iter().any(|other| {
+                let distance = lab
+                    .iter()
+                    .zip(other)
+                    .map(|(x, y)| (x - y).powi(2))
+                    .sum::<f32>()
+                    .sqrt();
+                distance * 100.0 < DUPLICATE_DELTA_E
+            });
+            if duplicate {
+                *cell = None;
+                removed += 1;
+            } else {
+                kept.push(lab);
+            }
+        }
+        self.trim_empty_pages();
+        removed
+    }
+
+    fn page_cells(&self, page: usize) -> &[Option<[f32; 3]>] {
+        &self.colors[page * PALETTE_SIZE..(page + 1) * PALETTE_SIZE]
+    }
+
+    fn trim_empty_pages(&mut self) {
+        while self.page_count() > self.page + 1
+            && self
+                .page_cells(self.page_count() - 1)
+                .iter()
+                .all(Option::is_none)
+        {
+            self.colors.truncate(self.colors.len() - PALETTE_SIZE);
+        }
+    }
+
+    fn toggle_blend_end(&mut self, index: usize) {
+        if self.colors[index].is_none() {
+            return;
+        }
+        if let Some(position) = self.blend_ends.iter().position(|i| *i == index) {
+            self.blend_ends.remove(position);
+        } else {
+            self.blend_ends.push(index);
+            if self.blend_ends.len() > 2 {
+                self.blend_ends.remove(0);
+            }
+        }
+    }
+
+    fn blend_scale(&self) -> Option<Vec<[f32; 3]>> {
+        let [from, to] = self.blend_ends[..] else {
+            return None;
+        };
+        let (from, to) = (self.colors[from]?, self.colors[to]?);
+        let mut scale = vec![from];
+        scale.extend(blend::interpolate(from, to, self.blend_steps));
+        scale.push(to);
+        Some(scale)
+    }
+
+    fn fill_blend(&mut self) {
+        let (Some(scale), Some(&start)) = (self.blend_scale(), self.blend_ends.first()) else {
+            return;
+        };
+        let mut steps = scale[1..scale.len() - 1].iter();
+        for offset in 1..self.colors.len() {
+            let index = (start + offset) % self.colors.len();
+            if self.colors[index].is_none() {
+                let Some(color) = steps.next() else { break };
+                self.colors[index] = Some(*color);
+            }
         }
     }
 }
@@ -64,7 +460,88 @@ impl Default for PaletteState {
 struct SavedState {
     values: [f32; 3],
     dark_mode: bool,
+    // Absent in files written before the System option existed; `dark_mode` decides then.
+    #[serde(default)]
+    theme: Option<ThemePreference>,
     palette: Vec<Option<[f32; 3]>>,
+    #[serde(default)]
+    palette_labels: Vec<String>,
+    #[serde(default)]
+    mode: ColorMode,
+    #[serde(default = "default_alpha")]
+    alpha: f32,
+    #[serde(default)]
+    contrast: ContrastAlgorithm,
+    #[serde(default)]
+    gamut_mapping: GamutMapping,
+    #[serde(default)]
+    recent: Vec<[f32; 3]>,
+    #[serde(default)]
+    palette_insert: bool,
+    #[serde(default = "default_hotkey")]
+    hotkey: String,
+    #[serde(default)]
+    hotkey_dropper: bool,
+    #[serde(default)]
+    capture_space: CaptureSpace,
+    #[serde(default)]
+    ui_tint: UiTint,
+    #[serde(default = "default_ui_scale")]
+    ui_scale: f32,
+    #[serde(default = "default_drag_sensitivity")]
+    drag_sensitivity: f32,
+    // Only read, to move a token saved by an older version into its own file.
+    #[serde(default, skip_serializing)]
+    figma_token: String,
+    #[serde(default)]
+    figma_file: String,
+    #[serde(default)]
+    relative_base: Option<RelativeBase>,
+    #[serde(default)]
+    gradients: Vec<GradientPreset>,
+    #[serde(default = "default_swatch_size")]
+    swatch_size: u32,
+    #[serde(default = "default_swatch_label")]
+    swatch_label: bool,
+    // Files written before the walkthrough existed belong to people past their first launch.
+    #[serde(default = "default_onboarding_seen")]
+    onboarding_seen: bool,
+    #[serde(default)]
+    window_geometry: Option<WindowGeometry>,
+    #[serde(default)]
+    skipped_update: Option<Version>,
+    #[serde(default)]
+    updates_deferred_until: u64,
+    #[serde(default)]
+    updated_to: Option<Version>,
+}
+
+fn default_alpha() -> f32 {
+    1.0
+}
+
+fn default_ui_scale() -> f32 {
+    1.0
+}
+
+fn default_drag_sensitivity() -> f32 {
+    1.0
+}
+
+fn default_swatch_size() -> u32 {
+    512
+}
+
+fn default_swatch_label() -> bool {
+    true
+}
+
+fn default_onboarding_seen() -> bool {
+    true
+}
+
+fn default_hotkey() -> String {
+    hotkey::DEFAULT_HOTKEY.to_string()
 }
 
 const GRAY_0_D: Color = Color::from_rgb8(0x00, 0x00, 0x00);
@@ -84,52 +561,102 @@ enum Theme {
     Gray70,
 }
 
-const CHANNELS: [Channel; 3] = [
-    Channel {
-        label: "L",
-        min: 0.0,
-        max: 1.0,
-    },
-    Channel {
-        label: "C",
-        min: 0.0,
-        max: 0.4,
-    },
-    Channel {
-        label: "H",
-        min: 0.0,
-        max: 360.0,
-    },
-];
+fn css_value(text: &str) -> &str {
+    let value = text.trim().trim_end_matches(';').trim_end();
+    let value = value.strip_suffix("!important").unwrap_or(value);
+    let value = match value.split_once(':') {
+        Some((property, value)) if !property.contains('(') => value,
+        _ => value,
+    };
+    value.trim()
+}
 
-struct Channel {
-    label: &'static str,
-    min: f32,
-    max: f32,
+fn parse_code(text: &str) -> Option<(Option<ColorMode>, [f32; 3], f32)> {
+    let input = css_value(text);
+    if let Some((mode, values, alpha)) = parse_mode_code(input) {
+        return Some((Some(mode), normalize_values(values), normalize_alpha(alpha)));
+    }
+    let parsed = parse_color(input)
+        .ok()
+        .or_else(|| parse_color(&format!("#{input}")).ok())?;
+    let oklch: AlphaColor<Oklch> = match parsed.cs {
+        ColorSpaceTag::Oklch | ColorSpaceTag::Oklab | ColorSpaceTag::Lab | ColorSpaceTag::Lch => {
+            parsed.to_alpha_color()
+        }
+        _ => {
+            let srgb: AlphaColor<Srgb> = parsed.to_alpha_color();
+            srgb.convert()
+        }
+    };
+    let c = oklch.components;
+    Some((
+        ColorMode::for_tag(parsed.cs),
+        normalize_values([c[0], c[1], c[2]]),
+        normalize_alpha(c[3]),
+    ))
+}
+
+fn parse_color_list(text: &str) -> Option<Vec<[f32; 3]>> {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    let colors: Vec<[f32; 3]> = if lines.len() > 1 {
+        lines
+            .into_iter()
+            .filter_map(|line| parse_code(line.trim().trim_end_matches(',')))
+            .map(|(_, values, _)| values)
+            .collect()
+    } else {
+        let mut pieces = Vec::new();
+        let (mut depth, mut start) = (0usize, 0);
+        for (i, c) in text.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                ',' | ';' | ' ' | '\t' if depth == 0 => {
+                    pieces.push(&text[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        pieces.push(&text[start..]);
+        pieces
+            .into_iter()
+            .filter(|piece| !piece.trim().is_empty())
+            .map(|piece| parse_code(piece).map(|(_, values, _)| values))
+            .collect::<Option<_>>()?
+    };
+    (colors.len() > 1).then_some(colors)
+}
+
+fn normalize_alpha(alpha: f32) -> f32 {
+    if alpha.is_nan() {
+        1.0
+    } else {
+        alpha.clamp(0.0, 1.0)
+    }
 }
 
 fn normalize_values(values: [f32; 3]) -> [f32; 3] {
     [
         if values[0].is_nan() {
-            CHANNELS[0].min
+            OKLCH_CHANNELS[0].min
         } else {
-            values[0].clamp(CHANNELS[0].min, CHANNELS[0].max)
+            values[0].clamp(OKLCH_CHANNELS[0].min, OKLCH_CHANNELS[0].max)
         },
         if values[1].is_nan() {
-            CHANNELS[1].min
+            OKLCH_CHANNELS[1].min
         } else {
-            values[1].clamp(CHANNELS[1].min, CHANNELS[1].max)
+            values[1].clamp(OKLCH_CHANNELS[1].min, OKLCH_CHANNELS[1].max)
         },
         if values[2].is_finite() {
-            values[2].rem_euclid(CHANNELS[2].max)
+            values[2].rem_euclid(OKLCH_CHANNELS[2].max)
         } else {
-            CHANNELS[2].min
+            OKLCH_CHANNELS[2].min
         },
     ]
 }
 
 const COPY_ICON: &str = include_str!("assets/copy.svg");
-const CHECKMARK_ICON: &str = include_str!("assets/checkmark.svg");
 const PLUS_ICON: &str = include_str!("assets/plus.svg");
 const X_ICON: &str = include_str!("assets/x.svg");
 
@@ -138,11 +665,49 @@ const PALETTE_HEIGHT: usize = 3;
 const PALETTE_SIZE: usize = PALETTE_WIDTH * PALETTE_HEIGHT;
 const PALETTE_SWATCH_SIZE: f32 = 20.0;
 const PALETTE_SWATCH_GAP: f32 = 5.0;
+const PALETTE_MENU_WIDTH: f32 = 130.0;
+// ΔEOK scaled by 100, as in `named_colors::nearest`; a just-noticeable difference is
+// about 2.
+const DUPLICATE_DELTA_E: f32 = 1.0;
+const RECENT_SIZE: usize = 10;
+const ONBOARDING_STEPS: [(&str, &[&str], f32); 3] = [
+    (
+        "drag to adjust",
+        &[
+            "drag along any slider to change that channel",
+            "of the color; every format updates as you go",
+        ],
+        200.,
+    ),
+    (
+        "the palette",
+        &[
+            "click an empty cell to keep the current color and",
+            "click a swatch to bring it back. drag swatches to",
+            "rearrange them, or off the palette to delete one",
+        ],
+        320.,
+    ),
+    (
+        "paste anything",
+        &[
+            "paste hex, rgb(), oklch(), a css name or a whole",
+            "list of colors into any format field",
+        ],
+        60.,
+    ),
+];
+const SWATCH_SIZES: [u32; 4] = [256, 512, 1024, 2048];
+const HISTORY_ROWS: usize = 10;
+const EXPLORER_COLUMNS: usize = 24;
+const EXPLORER_ROWS: usize = 16;
+const MAX_GRADIENTS: usize = 8;
+const GRADIENT_THUMBNAIL_SAMPLES: usize = 24;
 
 #[cfg(test)]
 const TEST_FORMAT_OVERLAY_IDS: [u64; 3] = [30_003, 30_004, 30_005];
 #[cfg(test)]
-const TEST_CHANNEL_SLIDER_IDS: [u64; 3] = [30_006, 30_007, 30_008];
+const TEST_CHANNEL_SLIDER_IDS: [u64; MAX_CHANNELS] = [30_006, 30_007, 30_008, 30_009];
 
 fn btn_surface_color(btn: ButtonState, base: Color) -> Color {
     match (btn.depressed, btn.hovered) {
@@ -162,26 +727,125 @@ fn btn_label_color(btn: ButtonState, base: Color) -> Color {
 
 const SUN_ICON: &str = include_str!("assets/sun.svg");
 const MOON_ICON: &str = include_str!("assets/moon.svg");
+const MONITOR_ICON: &str = include_str!("assets/monitor.svg");
+
+const SYSTEM_THEME_POLL: tokio::time::Duration = tokio::time::Duration::from_secs(2);
+const UPDATE_CHECK_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(60 * 60 * 4);
+const UPDATE_REMIND_DAYS: u64 = 3;
+
+const WARNING_COLOR: Color = Color::from_rgb8(0xf5, 0xa6, 0x23);
 
-#[cfg(not(target_os = "windows"))]
 const DROPPER_ICON: &str = include_str!("assets/dropper.svg");
+const DICE_ICON: &str = include_str!("assets/dice.svg");
+const HISTORY_ICON: &str = include_str!("assets/history.svg");
+const LOCK_ICON: &str = include_str!("assets/lock.svg");
+const PIN_ICON: &str = include_str!("assets/pin.svg");
+
+const RANDOM_ATTEMPTS: usize = 32;
 
 struct State {
     tx: Sender<UiCallback>,
     rx: Receiver<UiCallback>,
     values: [f32; 3],
-    sliders: [SliderState; 3],
+    alpha: f32,
+    mode: ColorMode,
+    mode_button: ButtonState,
+    sliders: [SliderState; MAX_CHANNELS],
+    locks: [bool; MAX_CHANNELS],
+    lock_buttons: [ButtonState; MAX_CHANNELS],
+    alpha_slider: SliderState,
     format_fields: [TextState; 3],
     copy_buttons: [ButtonState; 3],
     text_popover: Option<TextPopover>,
+    copy_menu: Option<CopyMenu>,
     dark_mode: bool,
+    theme: ThemePreference,
+    system_dark: Option<bool>,
     dark_mode_button: ButtonState,
-    #[cfg(not(target_os = "windows"))]
+    ui_tint: UiTint,
+    tint_button: ButtonState,
+    ui_scale: f32,
+    zoom_button: ButtonState,
+    drag_sensitivity: f32,
+    sensitivity_button: ButtonState,
+    slider_drag: Option<SliderDrag>,
     dropper_button: ButtonState,
+    dice_button: ButtonState,
     update_button: ButtonState,
+    skipped_update: Option<Version>,
+    updates_deferred_until: u64,
+    skip_update_button: ButtonState,
+    remind_later_button: ButtonState,
+    contrast: ContrastAlgorithm,
+    contrast_button: ButtonState,
+    contrast_grid_open: bool,
+    grid_button: ButtonState,
+    grid_save_button: ButtonState,
+    named_color_button: ButtonState,
+    material_button: ButtonState,
+    theme_pair_button: ButtonState,
+    gamut_mapping: GamutMapping,
+    gamut_button: ButtonState,
+    explorer_open: bool,
+    pinned: Option<([f32; 3], f32)>,
+    pin_button: ButtonState,
     update_status: UpdateStatus,
     palette: PaletteState,
-    copied: Arc<Mutex<[bool; 3]>>,
+    export_menu_open: bool,
+    export_menu_button: ButtonState,
+    export_buttons: [ButtonState; PaletteExport::ALL.len()],
+    tidy_menu_open: bool,
+    tidy_menu_button: ButtonState,
+    sort_buttons: [ButtonState; PaletteSort::ALL.len()],
+    undo: UndoStack<UndoSnapshot>,
+    undo_color: ([f32; 3], f32),
+    undo_button: ButtonState,
+    merge_button: ButtonState,
+    label_rows_button: ButtonState,
+    labels_done_button: ButtonState,
+    label_fields: [TextState; PALETTE_HEIGHT],
+    tidy_report: Option<String>,
+    link_button: ButtonState,
+    tokens_button: ButtonState,
+    figma_button: ButtonState,
+    gradients_button: ButtonState,
+    gradient_panel: GradientPanel,
+    gradients: Vec<GradientPreset>,
+    figma: FigmaPanel,
+    swatch: SwatchPanel,
+    picker_settings: PickerSettingsPanel,
+    formats_open: bool,
+    formats_rows: [ButtonState; CopyFormat::CSS.len()],
+    blend_preview: BlendPreviewPanel,
+    swatch_size: u32,
+    swatch_label: bool,
+    blend_buttons: [ButtonState; BlendAction::ALL.len()],
+    page_buttons: [ButtonState; 2],
+    drop_mode_button: ButtonState,
+    recent: Vec<[f32; 3]>,
+    history: Vec<HistoryEntry>,
+    history_open: bool,
+    history_search: TextState,
+    history_button: ButtonState,
+    history_export_button: ButtonState,
+    history_rows: [ButtonState; HISTORY_ROWS],
+    relative_base: Option<RelativeBase>,
+    bulk_paste: Option<Vec<[f32; 3]>>,
+    bulk_fill_button: ButtonState,
+    bulk_dismiss_button: ButtonState,
+    format_origin: Option<String>,
+    edit_source: HistorySource,
+    picked_profile: Option<String>,
+    onboarding_step: Option<usize>,
+    onboarding_seen: bool,
+    onboarding_next_button: ButtonState,
+    window_geometry: Option<WindowGeometry>,
+    onboarding_skip_button: ButtonState,
+    hotkey: String,
+    hotkey_dropper: bool,
+    capture_space: CaptureSpace,
+    global_hotkey: Option<GlobalHotkey>,
+    toasts: Toasts,
 }
 
 impl State {
@@ -199,7 +863,7 @@ impl State {
         } else {
             self.dark_mode
         };
-        if dark {
+        let gray = if dark {
             match t {
                 Theme::Gray0 => GRAY_0_D,
                 Theme::Gray30 => GRAY_30_D,
@@ -213,49 +877,125 @@ impl State {
                 Theme::Gray50 => GRAY_50_L,
                 Theme::Gray70 => GRAY_70_L,
             }
+        };
+        self.ui_tint.apply(gray)
+    }
+
+    fn import_palette_link(&mut self, text: &str, app: &mut PaneState) -> bool {
+        let Some(cells) = palette_link::decode(text) else {
+            return false;
+        };
+        self.record_undo();
+        self.palette.import(&cells);
+        self.save_state(app);
+        app.redraw();
+        true
+    }
+
+    fn publish_to_figma(&mut self, app: &mut PaneState) {
+        if self.figma.status == FigmaStatus::Publishing {
+            return;
         }
+        let Some(file_key) = figma::file_key(&self.figma.file.text).map(str::to_string) else {
+            self.figma.status = FigmaStatus::Error("Paste a Figma file link or key".to_string());
+            return;
+        };
+        let token = self.figma.token.text.clone();
+        let colors = self
+            .palette
+            .colors
+            .iter()
+            .enumerate()
+            .filter_map(|(i, cell)| {
+                cell.map(|values| {
+                    (
+                        format!("palette/{:02}", i + 1),
+                        self.gamut_mapping.to_srgb(values),
+                    )
+                })
+            })
+            .collect();
+        self.figma.status = FigmaStatus::Publishing;
+        self.save_state(app);
+        app.redraw();
+
+        let tx = self.tx.clone();
+        let wake = app.waker();
+        tokio::spawn(async move {
+            if let Some(path) = Self::figma_token_path()
+                && let Err(e) = figma::save_token(path, token.clone()).await
+            {
+                log::error!("{e}");
+            }
+            let status = match figma::publish(token, file_key, colors).await {
+                Ok(count) => FigmaStatus::Published { count },
+                Err(e) => FigmaStatus::Error(e),
+            };
+            tx.send(Box::new(move |state: &mut State, app: &mut PaneState| {
+                state.figma.status = status;
+                app.redraw();
+            }))
+            .ok();
+            wake.wake();
+        });
     }
+
+    fn apply_theme(&mut self) {
+        self.dark_mode = match self.theme {
+            ThemePreference::Light => false,
+            ThemePreference::Dark => true,
+            ThemePreference::System => self.system_dark.unwrap_or(self.dark_mode),
+        };
+    }
+
     fn oklch(&self) -> AlphaColor<Oklch> {
-        AlphaColor::new([self.values[0], self.values[1], self.values[2], 1.0])
+        AlphaColor::new([self.values[0], self.values[1], self.values[2], self.alpha])
     }
 
     fn display_color(&self) -> Color {
-        self.oklch().convert::<Srgb>()
+        self.srgb()
     }
 
     fn srgb(&self) -> AlphaColor<Srgb> {
-        self.oklch().convert::<Srgb>()
+        let [r, g, b] = self.gamut_mapping.to_srgb(self.values);
+        AlphaColor::new([r, g, b, self.alpha])
     }
 
     fn format_hex(&self) -> String {
         let c = self.srgb().components;
-        format!(
+        let hex = format!(
             "#{:02x}{:02x}{:02x}",
             (c[0].clamp(0.0, 1.0) * 255.0) as u8,
             (c[1].clamp(0.0, 1.0) * 255.0) as u8,
             (c[2].clamp(0.0, 1.0) * 255.0) as u8,
-        )
+        );
+        if self.alpha < 1.0 {
+            format!("{hex}{:02x}", (self.alpha * 255.0).round() as u8)
+        } else {
+            hex
+        }
     }
 
     fn format_rgb(&self) -> String {
         let c = self.srgb().components;
-        format!(
-            "rgb({}, {}, {})",
+        let [r, g, b] = [
             (c[0].clamp(0.0, 1.0) * 255.0) as u8,
             (c[1].clamp(0.0, 1.0) * 255.0) as u8,
             (c[2].clamp(0.0, 1.0) * 255.0) as u8,
-        )
-    }
-
-    fn format_oklch(&self) -> String {
-        format!(
-            "oklch({:.2} {:.3} {:.1})",
-            self.values[0], self.values[1], self.values[2],
-        )
+        ];
+        if self.alpha < 1.0 {
+            format!("rgba({r}, {g}, {b}, {:.2})", self.alpha)
+        } else {
+            format!("rgb({r}, {g}, {b})")
+        }
     }
 
     fn formats(&self) -> [String; 3] {
-        [self.format_hex(), self.format_rgb(), self.format_oklch()]
+        [
+            self.format_hex(),
+            self.format_rgb(),
+            self.mode.format(self.values, self.alpha),
+        ]
     }
 
     fn update_format_fields(&mut self) {
@@ -281,86 +1021,735 @@ impl State {
         }
     }
 
-    fn set_values(&mut self, values: [f32; 3], app: &mut PaneState) {
-        self.values = normalize_values(values);
-        self.end_format_editing(app);
-        self.update_ui();
+    fn remember_recent(&mut self) {
+        let rgba = palette_color(self.values).to_rgba8();
+        self.recent
+            .retain(|values| palette_color(*values).to_rgba8() != rgba);
+        self.recent.insert(0, self.values);
+        self.recent.truncate(RECENT_SIZE);
     }
 
-    fn parse_format(&mut self, text: &str) -> bool {
-        let input = text.trim();
-        let parsed = parse_color(input)
-            .ok()
-            .or_else(|| parse_color(&format!("#{input}")).ok());
-        let Some(parsed) = parsed else {
-            return false;
-        };
-        let oklch: AlphaColor<Oklch> = match parsed.cs {
-            ColorSpaceTag::Oklch => parsed.to_alpha_color(),
-            _ => {
-                let srgb: AlphaColor<Srgb> = parsed.to_alpha_color();
-                srgb.convert()
+    fn toast(&mut self, kind: ToastKind, message: impl Into<String>, app: &mut PaneState) {
+        let id = self.toasts.push(kind, message);
+        app.redraw();
+        // Tests drive the view without a runtime; their toasts just stay up.
+        if !cfg!(test) {
+            let tx = self.tx.clone();
+            let wake = app.waker();
+            tokio::spawn(async move {
+                tokio::time::sleep(kind.duration()).await;
+                tx.send(Box::new(move |state: &mut State, app: &mut PaneState| {
+                    state.toasts.dismiss(id);
+                    app.redraw();
+                }))
+                .ok();
+                wake.wake();
+            });
+        }
+    }
+
+    fn save_swatch(&mut self, app: &mut PaneState) {
+        let rgb = self.gamut_mapping.to_srgb(self.values);
+        let [r, g, b] = rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        let hex = format!("#{r:02X}{g:02X}{b:02X}");
+        let label = self.swatch_label.then_some(hex.as_str());
+        match palette_png::encode_swatch(rgb, self.alpha, self.swatch_size, label) {
+            Ok(bytes) => {
+                let file_name = format!("{}.png", hex.trim_start_matches('#').to_lowercase());
+                self.save_in_background(
+                    async move { export::save_file(bytes, &file_name, "PNG image", "png").await },
+                    app,
+                );
             }
+            Err(e) => self.toast(ToastKind::Error, e, app),
+        }
+        self.swatch.open = false;
+        app.redraw();
+    }
+
+    fn start_labeling(&mut self) {
+        self.palette.labeling = true;
+        self.palette.blending = false;
+        self.label_fields = from_fn(|row| TextState::new(self.palette.row_label(row)));
+    }
+
+    fn finish_labeling(&mut self, app: &mut PaneState) {
+        for row in 0..PALETTE_HEIGHT {
+            let label = self.label_fields[row].text.clone();
+            self.palette.set_row_label(row, &label);
+        }
+        self.palette.labeling = false;
+        self.save_state(app);
+    }
+
+    fn toggle_pin(&mut self) {
+        self.pinned = match self.pinned {
+            Some(_) => None,
+            None => Some((self.values, self.alpha)),
         };
-        let c = oklch.components;
-        self.values = normalize_values([c[0], c[1], c[2]]);
-        self.update_ui();
-        true
     }
 
-    fn update_sliders(&mut self) {
-        for i in 0..3 {
-            self.sliders[i].value = self.values[i];
+    fn add_gradient_stop(&mut self) {
+        if self.gradient_panel.draft.len() < gradient::MAX_STOPS {
+            self.gradient_panel.draft.push(self.values);
         }
     }
 
-    fn update_ui(&mut self) {
-        self.update_format_fields();
-        self.update_sliders();
+    fn save_gradient(&mut self, app: &mut PaneState) {
+        if self.gradient_panel.draft.len() < 2 {
+            return;
+        }
+        if self.gradients.len() == MAX_GRADIENTS {
+            self.toast(
+                ToastKind::Error,
+                format!("Only {MAX_GRADIENTS} gradients fit; delete one first"),
+                app,
+            );
+            return;
+        }
+        let stops = std::mem::take(&mut self.gradient_panel.draft);
+        self.gradients.insert(0, GradientPreset { stops });
+        self.save_state(app);
     }
-    fn config_path() -> Option<std::path::PathBuf> {
-        directories::ProjectDirs::from("com", "cyy", "idle-hue")
-            .map(|p| p.config_dir().join("state.json"))
+
+    fn blend_preview_result(&self) -> Option<[f32; 3]> {
+        let background = parse_color(self.blend_preview.background.text.trim())
+            .ok()?
+            .to_alpha_color::<Srgb>()
+            .components;
+        Some(self.blend_preview.mode.composite(
+            self.gamut_mapping.to_srgb(self.values),
+            self.blend_preview.alpha,
+            [background[0], background[1], background[2]],
+        ))
     }
 
-    fn save_state(&self, _app: &mut PaneState) {
-        let saved = SavedState {
-            values: self.values,
-            dark_mode: self.dark_mode,
-            palette: self.palette.colors.to_vec(),
+    fn open_links(&mut self, app: &mut PaneState) {
+        let Some(code) = url_scheme::take_pending().pop() else {
+            return;
         };
+        hotkey::bring_to_front();
+        if self.parse_format(&code) {
+            self.commit_color(HistorySource::Linked);
+            app.redraw();
+        } else {
+            self.toast(
+                ToastKind::Error,
+                format!("Couldn't read \"{code}\" as a color"),
+                app,
+            );
+        }
+    }
+
+    // Being up to date is only worth a toast when someone asked.
+    fn set_update_status(&mut self, status: UpdateStatus, requested: bool, app: &mut PaneState) {
+        match &status {
+            UpdateStatus::Available { version, .. }
+                if self.skipped_update.as_ref() == Some(version) =>
+            {
+                self.update_status = UpdateStatus::Idle;
+                return;
+            }
+            UpdateStatus::Available { version, elevate } => {
+                let message = if *elevate {
+                    format!(
+                        "idle-hue {version} is available; installing it needs administrator rights"
+                    )
+                } else {
+                    format!("idle-hue {version} is available")
+                };
+                self.toast(ToastKind::Info, message, app)
+            }
+            UpdateStatus::Updated { version } => self.toast(
+                ToastKind::Info,
+                format!("installed {version}, restart to update"),
+                app,
+            ),
+            UpdateStatus::UpToDate { .. } if requested => {
+                self.toast(ToastKind::Info, "idle-hue is up to date", app)
+            }
+            UpdateStatus::Error(e) => self.toast(ToastKind::Error, e.clone(), app),
+            _ => {}
+        }
+        self.update_status = status;
+        app.redraw();
+    }
+
+    // Checks someone asked for install right away; background checks only offer the update.
+    fn check_for_updates(&mut self, requested: bool, app: &mut PaneState) {
+        if requested {
+            self.update_status = UpdateStatus::Checking;
+            app.redraw();
+        } else if !self.background_update_check_due(history::now()) {
+            return;
+        }
+        let tx = self.tx.clone();
+        let wake = app.waker();
         tokio::spawn(async move {
-            if let Some(path) = Self::config_path() {
-                if let Some(parent) = path.parent() {
-                    let _ = tokio::fs::create_dir_all(parent).await;
-                }
-                if let Ok(json) = serde_json::to_string_pretty(&saved) {
-                    let _ = tokio::fs::write(path, json).await;
-                }
+            let mut updater = AutoUpdater::new();
+            if !requested {
+                updater = updater.in_background();
             }
+            updater
+                .check_and_install_updates_with_callback(Some(move |new_status: UpdateStatus| {
+                    let tx = tx.clone();
+                    let wake = wake.clone();
+                    async move {
+                        tx.send(Box::new(move |state: &mut State, app: &mut PaneState| {
+                            state.set_update_status(new_status, requested, app);
+                        }))
+                        .ok();
+                        wake.wake();
+                    }
+                }))
+                .await;
         });
     }
-}
 
-impl Default for State {
-    fn default() -> Self {
-        let (tx, rx) = channel();
-        let mut s = Self {
-            tx,
-            rx,
-            values: [0.7, 0.15, 180.0],
-            sliders: Default::default(),
-            format_fields: Default::default(),
-            copy_buttons: Default::default(),
+    fn background_update_check_due(&self, now: u64) -> bool {
+        matches!(self.update_status, UpdateStatus::Idle) && now >= self.updates_deferred_until
+    }
+
+    fn skip_update(&mut self) {
+        if let UpdateStatus::Available { version, .. } = &self.update_status {
+            self.skipped_update = Some(version.clone());
+        }
+        self.update_status = UpdateStatus::Idle;
+    }
+
+    fn remind_later(&mut self) {
+        self.updates_deferred_until = history::now() + UPDATE_REMIND_DAYS * 24 * 60 * 60;
+        self.update_status = UpdateStatus::Idle;
+    }
+
+    fn copy_text(&mut self, text: String, app: &mut PaneState) {
+        let preview = text.lines().next().unwrap_or_default();
+        let preview = if preview.chars().count() > 32 || text.lines().count() > 1 {
+            format!("{}…", preview.chars().take(31).collect::<String>())
+        } else {
+            preview.to_string()
+        };
+        match Clipboard::new().and_then(|mut cb| cb.set_text(text)) {
+            Ok(()) => self.toast(ToastKind::Info, format!("copied {preview}"), app),
+            Err(e) => self.toast(ToastKind::Error, format!("Couldn't copy: {e}"), app),
+        }
+    }
+
+    fn save_in_background(
+        &self,
+        save: impl Future<Output = Result<Option<String>, String>> + Send + 'static,
+        app: &mut PaneState,
+    ) {
+        let tx = self.tx.clone();
+        let wake = app.waker();
+        tokio::spawn(async move {
+            let saved = save.await;
+            tx.send(Box::new(
+                move |state: &mut State, app: &mut PaneState| match saved {
+                    Ok(Some(name)) => state.toast(ToastKind::Info, format!("saved {name}"), app),
+                    Ok(None) => {}
+                    Err(e) => {
+                        log::error!("{e}");
+                        state.toast(ToastKind::Error, e, app);
+                    }
+                },
+            ))
+            .ok();
+            wake.wake();
+        });
+    }
+
+    // Every filled palette slot, across all pages, as the sRGB the grid shows.
+    fn contrast_grid_colors(&self) -> Vec<[f32; 3]> {
+        self.palette
+            .colors
+            .iter()
+            .flatten()
+            .map(|values| self.gamut_mapping.to_srgb(*values))
+            .collect()
+    }
+
+    fn commit_color(&mut self, source: HistorySource) {
+        self.remember_recent();
+        if self.undo_color != (self.values, self.alpha) {
+            let (values, alpha) = self.undo_color;
+            self.record_undo_snapshot(UndoSnapshot {
+                values,
+                alpha,
+                ..self.undo_snapshot()
+            });
+        }
+        let hex = self.format_hex().to_uppercase();
+        if self
+            .history
+            .last()
+            .is_some_and(|last| last.hex == hex && last.source == source)
+        {
+            return;
+        }
+        let mut entry = HistoryEntry::new(self.values, self.alpha, hex, source);
+        if source == HistorySource::Picked {
+            entry.profile = self.picked_profile.clone();
+        }
+        self.history.push(entry.clone());
+        // Tests drive the view without a runtime and must not touch the real log.
+        if !cfg!(test)
+            && let Some(path) = Self::history_path()
+        {
+            tokio::spawn(async move {
+                if let Err(e) = history::append(path, entry).await {
+                    log::error!("{e}");
+                }
+            });
+        }
+    }
+
+    fn sample_with_dropper(&self, app: &mut PaneState) {
+        let tx = self.tx.clone();
+        let wake = app.waker();
+        tokio::spawn(async move {
+            if let Ok(Some(sample)) = dropper::sample_color().await {
+                tx.send(Box::new(move |state: &mut State, app: &mut PaneState| {
+                    let [r, g, b] = sample.rgb;
+                    let picked = AlphaColor::<Srgb>::new([r, g, b, 1.0]).convert::<Oklch>();
+                    let values = [
+                        picked.components[0],
+                        picked.components[1],
+                        picked.components[2],
+                    ];
+                    let captured = match state.capture_space {
+                        CaptureSpace::DisplayP3 if sample.wide_gamut => {
+                            let [r, g, b] = gamut::to_display_p3(values);
+                            AlphaColor::<DisplayP3>::new([r, g, b, 1.0]).convert::<Oklch>()
+                        }
+                        _ => {
+                            let [r, g, b] = state.gamut_mapping.to_srgb(values);
+                            AlphaColor::<Srgb>::new([r, g, b, 1.0]).convert::<Oklch>()
+                        }
+                    };
+                    let c = captured.components;
+                    state.set_values([c[0], c[1], c[2]], app);
+                    state.picked_profile = sample.profile;
+                    state.commit_color(HistorySource::Picked);
+                    app.redraw();
+                }))
+                .ok();
+                wake.wake();
+            }
+        });
+    }
+
+    fn register_hotkey(&mut self, app: &mut PaneState) {
+        self.global_hotkey = None;
+        if self.hotkey.is_empty() {
+            return;
+        }
+        let channel = std::sync::Mutex::new((self.tx.clone(), app.waker()));
+        let registered = GlobalHotkey::register(&self.hotkey, move || {
+            let guard = channel.lock().unwrap_or_else(|e| e.into_inner());
+            let (tx, wake) = &*guard;
+            tx.send(Box::new(|state: &mut State, app: &mut PaneState| {
+                hotkey::bring_to_front();
+                if state.hotkey_dropper {
+                    state.sample_with_dropper(app);
+                }
+            }))
+            .ok();
+            wake.wake();
+        });
+        match registered {
+            Ok(hotkey) => self.global_hotkey = Some(hotkey),
+            Err(e) => log::error!("{e}"),
+        }
+    }
+
+    fn set_hotkey(&mut self, app: &mut PaneState) {
+        let typed = self.picker_settings.hotkey.text.trim().to_string();
+        if typed != self.hotkey {
+            let previous = std::mem::replace(&mut self.hotkey, typed);
+            self.register_hotkey(app);
+            if self.global_hotkey.is_none() && !self.hotkey.is_empty() {
+                self.toast(
+                    ToastKind::Error,
+                    format!("Couldn't use \"{}\" as the shortcut", self.hotkey),
+                    app,
+                );
+                self.hotkey = previous;
+                self.register_hotkey(app);
+            } else {
+                self.save_state(app);
+            }
+        }
+        self.picker_settings.hotkey = TextState::new(self.hotkey.clone());
+    }
+
+    fn set_values(&mut self, values: [f32; 3], app: &mut PaneState) {
+        self.values = normalize_values(values);
+        self.end_format_editing(app);
+        self.update_ui();
+    }
+
+    // Keeps the sliders where they were dragged; re-deriving them would lose hue on grays.
+    fn set_components(&mut self, components: Components, app: &mut PaneState) {
+        self.values = normalize_values(self.mode.to_oklch(components));
+        self.end_format_editing(app);
+        self.update_format_fields();
+        for (slider, component) in self.sliders.iter_mut().zip(components) {
+            slider.value = component;
+        }
+    }
+
+    fn set_alpha(&mut self, alpha: f32, app: &mut PaneState) {
+        self.alpha = normalize_alpha(alpha);
+        self.end_format_editing(app);
+        self.update_format_fields();
+        self.alpha_slider.value = self.alpha;
+    }
+
+    fn drag_value(&mut self, slider: usize, pointer: f32, range: (f32, f32)) -> f32 {
+        let mut scale = self.drag_sensitivity;
+        if drag::fine_adjust_held() {
+            scale *= drag::FINE_ADJUST;
+        }
+        match &mut self.slider_drag {
+            Some(drag) if drag.slider == slider => drag.follow(pointer, scale, range),
+            _ => {
+                self.slider_drag = Some(SliderDrag::start(slider, pointer, scale));
+                pointer
+            }
+        }
+    }
+
+    fn slider_components(&self) -> Components {
+        from_fn(|i| self.sliders[i].value)
+    }
+
+    fn randomize(&mut self, app: &mut PaneState) {
+        let components = self.random_components();
+        self.set_components(components, app);
+        self.commit_color(HistorySource::Random);
+    }
+
+    fn random_components(&self) -> Components {
+        let channels = self.mode.channels();
+        let current = self.slider_components();
+        let mut rng = rand::rng();
+        let mut components = current;
+        for _ in 0..RANDOM_ATTEMPTS {
+            components = from_fn(|i| match channels.get(i) {
+                Some(ch) if !self.locks[i] => rng.random_range(ch.min..=ch.max),
+                _ => current[i],
+            });
+            if in_srgb_gamut(self.mode.to_oklch(components)) {
+                break;
+            }
+        }
+        components
+    }
+
+    fn set_mode(&mut self, mode: ColorMode, app: &mut PaneState) {
+        self.mode = mode;
+        self.end_format_editing(app);
+        self.update_ui();
+    }
+
+    fn parse_format(&mut self, text: &str) -> bool {
+        let Some((mode, values, alpha)) = parse_code(text) else {
+            return false;
+        };
+        if let Some(mode) = mode {
+            self.mode = mode;
+        }
+        self.values = values;
+        self.alpha = alpha;
+        self.update_ui();
+        true
+    }
+
+    fn advance_onboarding(&mut self, skip: bool, app: &mut PaneState) {
+        self.onboarding_step = self
+            .onboarding_step
+            .map(|step| step + 1)
+            .filter(|step| !skip && *step < ONBOARDING_STEPS.len());
+        if self.onboarding_step.is_none() {
+            self.onboarding_seen = true;
+            self.save_state(app);
+        }
+        app.redraw();
+    }
+
+    fn fill_bulk_paste(&mut self, app: &mut PaneState) {
+        let Some(colors) = self.bulk_paste.take() else {
+            return;
+        };
+        self.record_undo();
+        self.palette.fill_empty(&colors);
+        self.save_state(app);
+        app.redraw();
+    }
+
+    fn update_sliders(&mut self) {
+        let components = self.mode.components(self.values);
+        for (slider, component) in self.sliders.iter_mut().zip(components) {
+            slider.value = component;
+        }
+        self.alpha_slider.value = self.alpha;
+    }
+
+    fn update_ui(&mut self) {
+        self.update_format_fields();
+        self.update_sliders();
+    }
+    fn config_path() -> Option<std::path::PathBuf> {
+        directories::ProjectDirs::from("com", "cyy", "idle-hue")
+            .map(|p| p.config_dir().join("state.json"))
+    }
+
+    fn history_path() -> Option<std::path::PathBuf> {
+        directories::ProjectDirs::from("com", "cyy", "idle-hue")
+            .map(|p| p.config_dir().join("history.jsonl"))
+    }
+
+    fn figma_token_path() -> Option<std::path::PathBuf> {
+        directories::ProjectDirs::from("com", "cyy", "idle-hue")
+            .map(|p| p.config_dir().join("figma-token"))
+    }
+
+    fn saved_state(&self) -> SavedState {
+        SavedState {
+            values: self.values,
+            dark_mode: self.dark_mode,
+            theme: Some(self.theme),
+            palette: self.palette.colors.clone(),
+            palette_labels: self.palette.labels.clone(),
+            recent: self.recent.clone(),
+            palette_insert: self.palette.insert_on_drop,
+            hotkey: self.hotkey.clone(),
+            hotkey_dropper: self.hotkey_dropper,
+            capture_space: self.capture_space,
+            ui_tint: self.ui_tint,
+            ui_scale: self.ui_scale,
+            drag_sensitivity: self.drag_sensitivity,
+            figma_token: String::new(),
+            figma_file: self.figma.file.text.clone(),
+            relative_base: self.relative_base.clone(),
+            gradients: self.gradients.clone(),
+            swatch_size: self.swatch_size,
+            swatch_label: self.swatch_label,
+            onboarding_seen: self.onboarding_seen,
+            window_geometry: window_geometry::current().or(self.window_geometry),
+            skipped_update: self.skipped_update.clone(),
+            updates_deferred_until: self.updates_deferred_until,
+            updated_to: None,
+            mode: self.mode,
+            alpha: self.alpha,
+            contrast: self.contrast,
+            gamut_mapping: self.gamut_mapping,
+        }
+    }
+
+    fn save_state(&self, _app: &mut PaneState) {
+        let saved = self.saved_state();
+        tokio::spawn(async move {
+            write_saved_state(&saved).await;
+        });
+    }
+
+    fn record_undo(&mut self) {
+        let snapshot = self.undo_snapshot();
+        self.record_undo_snapshot(snapshot);
+    }
+
+    fn undo_snapshot(&self) -> UndoSnapshot {
+        UndoSnapshot {
+            values: self.values,
+            alpha: self.alpha,
+            palette: self.palette.colors.clone(),
+        }
+    }
+
+    fn record_undo_snapshot(&mut self, snapshot: UndoSnapshot) {
+        self.undo.record(snapshot);
+        self.undo_color = (self.values, self.alpha);
+    }
+
+    fn undo(&mut self, app: &mut PaneState) {
+        let Some(snapshot) = self.undo.undo() else {
+            return;
+        };
+        self.set_alpha(snapshot.alpha, app);
+        self.set_values(snapshot.values, app);
+        self.undo_color = (self.values, self.alpha);
+        self.palette.colors = snapshot.palette;
+        self.tidy_report = None;
+        self.palette.page = self.palette.page.min(self.palette.page_count() - 1);
+        self.palette.hover = [false; PALETTE_SIZE];
+        self.palette.blend_ends.clear();
+        self.save_state(app);
+        app.redraw();
+    }
+
+    fn sort_palette(&mut self, by: PaletteSort, app: &mut PaneState) {
+        self.record_undo();
+        self.palette.sort(by);
+        self.save_state(app);
+        app.redraw();
+    }
+
+    fn merge_duplicates(&mut self, app: &mut PaneState) {
+        let before = self.undo_snapshot();
+        let removed = self.palette.merge_duplicates();
+        self.tidy_report = Some(match removed {
+            0 => "no duplicates".to_string(),
+            1 => "removed 1 duplicate".to_string(),
+            n => format!("removed {n} duplicates"),
+        });
+        if removed > 0 {
+            self.record_undo_snapshot(before);
+            self.save_state(app);
+        }
+        app.redraw();
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        let (tx, rx) = channel();
+        let mut s = Self {
+            tx,
+            rx,
+            values: [0.7, 0.15, 180.0],
+            alpha: 1.0,
+            mode: ColorMode::default(),
+            mode_button: Default::default(),
+            sliders: Default::default(),
+            locks: [false; MAX_CHANNELS],
+            lock_buttons: Default::default(),
+            alpha_slider: Default::default(),
+            format_fields: Default::default(),
+            copy_buttons: Default::default(),
             text_popover: None,
+            copy_menu: None,
             dark_mode: true,
+            theme: ThemePreference::Dark,
+            system_dark: None,
             dark_mode_button: Default::default(),
-            #[cfg(not(target_os = "windows"))]
+            ui_tint: UiTint::default(),
+            tint_button: Default::default(),
+            ui_scale: 1.0,
+            zoom_button: Default::default(),
+            drag_sensitivity: default_drag_sensitivity(),
+            sensitivity_button: Default::default(),
+            slider_drag: None,
             dropper_button: Default::default(),
+            dice_button: Default::default(),
             update_button: Default::default(),
+            contrast: ContrastAlgorithm::default(),
+            contrast_button: Default::default(),
+            contrast_grid_open: false,
+            grid_button: Default::default(),
+            grid_save_button: Default::default(),
+            named_color_button: Default::default(),
+            material_button: Default::default(),
+            theme_pair_button: Default::default(),
+            gamut_mapping: GamutMapping::default(),
+            gamut_button: Default::default(),
+            explorer_open: false,
+            pinned: None,
+            pin_button: Default::default(),
             update_status: UpdateStatus::Idle,
+            skipped_update: None,
+            updates_deferred_until: 0,
+            skip_update_button: Default::default(),
+            remind_later_button: Default::default(),
             palette: PaletteState::default(),
-            copied: Arc::new(Mutex::new([false; 3])),
+            export_menu_open: false,
+            export_menu_button: Default::default(),
+            export_buttons: Default::default(),
+            tidy_menu_open: false,
+            tidy_menu_button: Default::default(),
+            sort_buttons: Default::default(),
+            undo: UndoStack::default(),
+            undo_color: ([0.7, 0.15, 180.0], 1.0),
+            undo_button: Default::default(),
+            merge_button: Default::default(),
+            label_rows_button: Default::default(),
+            labels_done_button: Default::default(),
+            label_fields: from_fn(|_| TextState::new(String::new())),
+            tidy_report: None,
+            link_button: Default::default(),
+            tokens_button: Default::default(),
+            figma_button: Default::default(),
+            gradients_button: Default::default(),
+            gradient_panel: GradientPanel {
+                open: false,
+                draft: Vec::new(),
+                add_button: Default::default(),
+                clear_button: Default::default(),
+                save_button: Default::default(),
+                copy_buttons: Default::default(),
+                delete_buttons: Default::default(),
+            },
+            gradients: Vec::new(),
+            figma: FigmaPanel {
+                open: false,
+                token: TextState::new(String::new()),
+                file: TextState::new(String::new()),
+                publish_button: Default::default(),
+                status: FigmaStatus::Idle,
+            },
+            swatch: SwatchPanel {
+                open: false,
+                size_buttons: Default::default(),
+                label_button: Default::default(),
+                save_button: Default::default(),
+            },
+            picker_settings: PickerSettingsPanel {
+                open: false,
+                hotkey: TextState::new(default_hotkey()),
+                hotkey_dropper_button: Default::default(),
+                capture_space_buttons: Default::default(),
+            },
+            formats_open: false,
+            formats_rows: Default::default(),
+            blend_preview: BlendPreviewPanel {
+                open: false,
+                background: TextState::new("#FFFFFF"),
+                mode: BlendMode::Multiply,
+                alpha: 1.0,
+                mode_buttons: Default::default(),
+                alpha_buttons: Default::default(),
+                copy_button: Default::default(),
+            },
+            swatch_size: default_swatch_size(),
+            swatch_label: default_swatch_label(),
+            blend_buttons: Default::default(),
+            page_buttons: Default::default(),
+            drop_mode_button: Default::default(),
+            recent: Vec::new(),
+            history: Vec::new(),
+            history_open: false,
+            history_search: TextState::new(String::new()),
+            history_button: Default::default(),
+            history_export_button: Default::default(),
+            history_rows: Default::default(),
+            relative_base: None,
+            bulk_paste: None,
+            bulk_fill_button: Default::default(),
+            bulk_dismiss_button: Default::default(),
+            format_origin: None,
+            edit_source: HistorySource::Edited,
+            picked_profile: None,
+            onboarding_step: None,
+            onboarding_seen: false,
+            onboarding_next_button: Default::default(),
+            window_geometry: None,
+            onboarding_skip_button: Default::default(),
+            hotkey: default_hotkey(),
+            hotkey_dropper: false,
+            capture_space: CaptureSpace::default(),
+            global_hotkey: None,
+            toasts: Toasts::default(),
         };
         s.update_ui();
         s
@@ -369,15 +1758,42 @@ impl Default for State {
 
 #[tokio::main]
 async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some(auto_update::INSTALL_UPDATE_ARG) {
+        let result = args.get(1).map(String::as_str);
+        std::process::exit(AutoUpdater::install_as_helper(result).await);
+    }
+    let listener = match single_instance::start(&args) {
+        single_instance::Launch::First(listener) => listener,
+        single_instance::Launch::HandedOff => return,
+    };
+
     #[cfg(target_os = "windows")]
     set_app_user_model_id();
+    #[cfg(target_os = "windows")]
+    if let Err(e) = url_scheme::register() {
+        log::error!("{e}");
+    }
+    #[cfg(target_os = "macos")]
+    url_scheme::listen();
+    for arg in &args {
+        url_scheme::receive(arg);
+    }
+    if let Some(listener) = listener {
+        single_instance::serve(listener);
+    }
+
+    let scale = load_saved_state()
+        .await
+        .map_or(1.0, |saved| ui_scale::clamp(saved.ui_scale));
+    let size = |length: f32| (length * scale).round() as u32;
 
     WinitApp::new(State::default())
         .window_icon(app_icon())
         .pane(
             PaneBuilder::new("main", view)
                 .title("idle-hue")
-                .inner_size(400, 420)
+                .inner_size(size(400.), size(605.))
                 .on_start(on_start)
                 .on_wake(on_wake)
                 .on_exit(|state, app| {
@@ -387,6 +1803,13 @@ async fn main() {
         .run()
 }
 
+fn focus_for_handoff(app: &mut PaneState) {
+    if single_instance::take_focus_request() {
+        hotkey::bring_to_front();
+        app.redraw();
+    }
+}
+
 #[cfg(target_os = "windows")]
 fn set_app_user_model_id() {
     let id: Vec<u16> = "cyy.apps.idle-hue"
@@ -419,52 +1842,213 @@ fn png_app_icon() -> Icon {
     Icon::from_rgba(image.into_raw(), width, height).expect("icon32.png should be a valid icon")
 }
 
+async fn write_saved_state(saved: &SavedState) {
+    if let Some(path) = State::config_path() {
+        write_saved_state_at(&path, saved).await;
+    }
+}
+
+async fn write_saved_state_at(path: &std::path::Path, saved: &SavedState) {
+    if let Some(parent) = path.parent() {
+        let _ = tokio::fs::create_dir_all(parent).await;
+    }
+    if let Ok(json) = serde_json::to_string_pretty(saved) {
+        let _ = tokio::fs::write(path, json).await;
+    }
+}
+
+// If the restart fails this version keeps going, so `updated_to` is taken back out
+// rather than announced on the next launch.
+async fn restart_into_update<T, E>(
+    path: &std::path::Path,
+    mut saved: SavedState,
+    version: Version,
+    restart: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    saved.updated_to = Some(version);
+    write_saved_state_at(path, &saved).await;
+    let restarted = restart.await;
+    if restarted.is_err() {
+        saved.updated_to = None;
+        write_saved_state_at(path, &saved).await;
+    }
+    restarted
+}
+
+async fn load_saved_state() -> Option<SavedState> {
+    let content = tokio::fs::read_to_string(State::config_path()?)
+        .await
+        .ok()?;
+    serde_json::from_str(&content).ok()
+}
+
 fn on_start(state: &mut State, app: &mut PaneState) {
+    if let Some(path) = State::history_path() {
+        let tx = state.tx.clone();
+        let wake = app.waker();
+        tokio::spawn(async move {
+            let mut loaded = history::load(path).await;
+            tx.send(Box::new(move |state: &mut State, _app: &mut PaneState| {
+                loaded.append(&mut state.history);
+                state.history = loaded;
+            }))
+            .ok();
+            wake.wake();
+        });
+    }
+
     let tx = state.tx.clone();
     let wake = app.waker();
     tokio::spawn(async move {
-        if let Some(path) = State::config_path()
-            && let Ok(content) = tokio::fs::read_to_string(&path).await
-            && let Ok(saved) = serde_json::from_str::<SavedState>(&content)
-        {
-            tx.send(Box::new(move |state: &mut State, app: &mut PaneState| {
+        let mut saved = load_saved_state().await;
+        let legacy_token = saved
+            .as_mut()
+            .map(|saved| std::mem::take(&mut saved.figma_token))
+            .filter(|token| !token.is_empty());
+        let figma_token = match State::figma_token_path() {
+            Some(path) => figma::load_token(path).await,
+            None => None,
+        };
+        tx.send(Box::new(move |state: &mut State, app: &mut PaneState| {
+            if let Some(mut saved) = saved {
+                let updated_to = saved.updated_to.take();
+                state.mode = saved.mode;
+                state.alpha = normalize_alpha(saved.alpha);
                 state.set_values(saved.values, app);
                 state.dark_mode = saved.dark_mode;
-                for (i, color) in saved.palette.into_iter().enumerate() {
-                    if i < PALETTE_SIZE {
-                        state.palette.colors[i] = color.map(normalize_values);
+                state.theme = saved.theme.unwrap_or(if saved.dark_mode {
+                    ThemePreference::Dark
+                } else {
+                    ThemePreference::Light
+                });
+                state.apply_theme();
+                state.contrast = saved.contrast;
+                state.gamut_mapping = saved.gamut_mapping;
+                state.recent = saved
+                    .recent
+                    .into_iter()
+                    .take(RECENT_SIZE)
+                    .map(normalize_values)
+                    .collect();
+                state.hotkey = saved.hotkey;
+                state.picker_settings.hotkey = TextState::new(state.hotkey.clone());
+                state.palette.insert_on_drop = saved.palette_insert;
+                state.hotkey_dropper = saved.hotkey_dropper;
+                state.capture_space = saved.capture_space;
+                state.ui_tint = saved.ui_tint;
+                state.figma.file = TextState::new(saved.figma_file);
+                state.relative_base = saved.relative_base;
+                state.gradients = saved.gradients;
+                state.gradients.truncate(MAX_GRADIENTS);
+                state.onboarding_seen = saved.onboarding_seen;
+                state.window_geometry = saved.window_geometry;
+                state.skipped_update = saved.skipped_update;
+                state.updates_deferred_until = saved.updates_deferred_until;
+                state.swatch_size = saved.swatch_size;
+                state.swatch_label = saved.swatch_label;
+                state.palette.labels = saved.palette_labels;
+                state.drag_sensitivity = drag::clamp(saved.drag_sensitivity);
+                state.ui_scale = ui_scale::clamp(saved.ui_scale);
+                ui_scale::set(state.ui_scale);
+                state.palette.set_colors(
+                    saved
+                        .palette
+                        .into_iter()
+                        .map(|color| color.map(normalize_values))
+                        .collect(),
+                );
+                state.undo_color = (state.values, state.alpha);
+                if let Some(version) = updated_to {
+                    state.toast(ToastKind::Info, format!("updated to {version}"), app);
+                }
+            }
+            match (figma_token, legacy_token) {
+                (Some(token), _) => state.figma.token = TextState::new(token),
+                (None, Some(token)) => {
+                    state.figma.token = TextState::new(&token);
+                    if let Some(path) = State::figma_token_path() {
+                        tokio::spawn(async move {
+                            if let Err(e) = figma::save_token(path, token).await {
+                                log::error!("{e}");
+                            }
+                        });
                     }
+                    // Rewrites state.json without the token.
+                    state.save_state(app);
                 }
-                app.redraw();
-            }))
-            .ok();
-            wake.wake();
+                (None, None) => {}
+            }
+            if let Some(geometry) = state.window_geometry {
+                window_geometry::restore(geometry);
+            }
+            if !state.onboarding_seen {
+                state.onboarding_step = Some(0);
+            }
+            let channel = std::sync::Mutex::new((state.tx.clone(), app.waker()));
+            url_scheme::on_receive(move || {
+                let guard = channel.lock().unwrap_or_else(|e| e.into_inner());
+                let (tx, wake) = &*guard;
+                tx.send(Box::new(|state: &mut State, app: &mut PaneState| {
+                    state.open_links(app);
+                }))
+                .ok();
+                wake.wake();
+            });
+            let channel = std::sync::Mutex::new((state.tx.clone(), app.waker()));
+            single_instance::on_handoff(move || {
+                let guard = channel.lock().unwrap_or_else(|e| e.into_inner());
+                let (tx, wake) = &*guard;
+                tx.send(Box::new(|_: &mut State, app: &mut PaneState| {
+                    focus_for_handoff(app);
+                }))
+                .ok();
+                wake.wake();
+            });
+            state.open_links(app);
+            focus_for_handoff(app);
+            state.register_hotkey(app);
+            app.redraw();
+        }))
+        .ok();
+        wake.wake();
+    });
+
+    let tx = state.tx.clone();
+    let wake = app.waker();
+    tokio::spawn(async move {
+        let mut last = None;
+        loop {
+            let detected = tokio::task::spawn_blocking(dark_light::detect).await;
+            let system_dark = match detected {
+                Ok(Ok(dark_light::Mode::Dark)) => Some(true),
+                Ok(Ok(dark_light::Mode::Light)) => Some(false),
+                _ => None,
+            };
+            if system_dark.is_some() && system_dark != last {
+                last = system_dark;
+                tx.send(Box::new(move |state: &mut State, app: &mut PaneState| {
+                    state.system_dark = system_dark;
+                    state.apply_theme();
+                    app.redraw();
+                }))
+                .ok();
+                wake.wake();
+            }
+            tokio::time::sleep(SYSTEM_THEME_POLL).await;
         }
     });
 
     let tx = state.tx.clone();
     let wake = app.waker();
     tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60 * 60 * 4));
+        let mut interval = tokio::time::interval(UPDATE_CHECK_INTERVAL);
         loop {
             interval.tick().await;
-            let updater = AutoUpdater::new();
-            let tx = tx.clone();
-            let wake = wake.clone();
-            updater
-                .check_and_install_updates_with_callback(Some(move |new_status: UpdateStatus| {
-                    let tx = tx.clone();
-                    let wake = wake.clone();
-                    async move {
-                        tx.send(Box::new(move |state: &mut State, app: &mut PaneState| {
-                            state.update_status = new_status;
-                            app.redraw();
-                        }))
-                        .ok();
-                        wake.wake();
-                    }
-                }))
-                .await;
+            tx.send(Box::new(|state: &mut State, app: &mut PaneState| {
+                state.check_for_updates(false, app);
+            }))
+            .ok();
+            wake.wake();
         }
     });
 }
@@ -495,19 +2079,117 @@ fn view<'a>(s: &'a State, app: &mut PaneState) -> View<'a, State> {
         rect(id!()).fill(bg).corner_rounding(0.).build(app),
         column(vec![
             column_spaced(
-                10.,
+                px(10.),
                 vec![
-                    row_spaced(10., {
-                        let mut buttons: Vec<View<'_, State>> = vec![space().inert_y()];
-                        #[cfg(not(target_os = "windows"))]
+                    row_spaced(px(10.), {
+                        let mut buttons: Vec<View<'_, State>> = vec![
+                            button(id!(), binding!(s.mode_button))
+                                .surface(move |btn, ctx| {
+                                    rect(id!())
+                                        .fill(btn_surface_color(btn, field_bg))
+                                        .stroke(field_border, Stroke::new(1.))
+                                        .corner_rounding(7.)
+                                        .build(ctx)
+                                })
+                                .label(move |btn, ctx| {
+                                    text(id!(), s.mode.label())
+                                        .font_size(font(13))
+                                        .font_weight(FontWeight::BOLD)
+                                        .fill(btn_label_color(btn, label_color))
+                                        .build(ctx)
+                                })
+                                .on_click(|state, app| {
+                                    let mode = state.mode.next();
+                                    state.set_mode(mode, app);
+                                })
+                                .build(app)
+                                .height(px(30.))
+                                .width(px(64.)),
+                            space().inert_y(),
+                        ];
+                        buttons.push(
+                            stack(vec![
+                                button(
+                                    id!(),
+                                    (
+                                        &s.dropper_button,
+                                        Binding::new(
+                                            |s: &State| &s.dropper_button,
+                                            |s: &mut State| &mut s.dropper_button,
+                                        ),
+                                    ),
+                                )
+                                .surface(move |btn, ctx| {
+                                    rect(id!())
+                                        .fill(btn_surface_color(btn, field_bg))
+                                        .stroke(field_border, Stroke::new(1.))
+                                        .corner_rounding(7.)
+                                        .build(ctx)
+                                })
+                                .label(move |btn, ctx| {
+                                    svg(id!(), DROPPER_ICON)
+                                        .fill(Brush::Solid(btn_label_color(btn, label_color)))
+                                        .finish(ctx)
+                                        .pad(px(6.))
+                                })
+                                .on_click(|state, app| state.sample_with_dropper(app))
+                                .build(app),
+                                picker_settings_sensor(app),
+                            ])
+                            .height(px(30.))
+                            .width(px(30.)),
+                        );
+                        buttons.push(
+                            button(id!(), binding!(s.dice_button))
+                                .surface(move |btn, ctx| {
+                                    rect(id!())
+                                        .fill(btn_surface_color(btn, field_bg))
+                                        .stroke(field_border, Stroke::new(1.))
+                                        .corner_rounding(7.)
+                                        .build(ctx)
+                                })
+                                .label(move |btn, ctx| {
+                                    svg(id!(), DICE_ICON)
+                                        .fill(Brush::Solid(btn_label_color(btn, label_color)))
+                                        .finish(ctx)
+                                        .pad(px(6.))
+                                })
+                                .on_click(|state, app| state.randomize(app))
+                                .build(app)
+                                .height(px(30.))
+                                .width(px(30.)),
+                        );
+                        buttons.push(
+                            button(id!(), binding!(s.history_button))
+                                .surface(move |btn, ctx| {
+                                    rect(id!())
+                                        .fill(btn_surface_color(btn, field_bg))
+                                        .stroke(field_border, Stroke::new(1.))
+                                        .corner_rounding(7.)
+                                        .build(ctx)
+                                })
+                                .label(move |btn, ctx| {
+                                    svg(id!(), HISTORY_ICON)
+                                        .fill(Brush::Solid(btn_label_color(btn, label_color)))
+                                        .finish(ctx)
+                                        .pad(px(6.))
+                                })
+                                .on_click(|state, app| {
+                                    state.history_open = !state.history_open;
+                                    app.redraw();
+                                })
+                                .build(app)
+                                .height(px(30.))
+                                .width(px(30.)),
+                        );
                         buttons.push(
                             button(
                                 id!(),
                                 (
-                                    &s.dropper_button,
+                                    &s.dark_mode_button,
                                     Binding::new(
-                                        |s: &State| &s.dropper_button,
-                                        |s: &mut State| &mut s.dropper_button,
+                                        |s: &State| &s.dark_mode_button,
+                                        |s: &mut State| &mut s.dark_mode_button,
                                     ),
                                 ),
                             )
@@ -519,84 +2201,69 @@ fn view<'a>(s: &'a State, app: &mut PaneState) -> View<'a, State> {
                                     .build(ctx)
                             })
                             .label(move |btn, ctx| {
-                                svg(id!(), DROPPER_ICON)
+                                svg(id!(), s.theme.next().icon())
                                     .fill(Brush::Solid(btn_label_color(btn, label_color)))
                                     .finish(ctx)
-                                    .pad(6.)
+                                    .pad(px(6.))
                             })
                             .on_click(|state, app| {
-                                let tx = state.tx.clone();
-                                let wake = app.waker();
-                                tokio::spawn(async move {
-                                    if let Ok(Some(rgb)) = dropper::sample_color().await {
-                                        tx.send(Box::new(
-                                            move |state: &mut State, app: &mut PaneState| {
-                                                let srgb = AlphaColor::<Srgb>::new([
-                                                    rgb[0], rgb[1], rgb[2], 1.0,
-                                                ]);
-                                                let oklch: AlphaColor<Oklch> = srgb.convert();
-                                                let c = oklch.components;
-                                                state.set_values([c[0], c[1], c[2]], app);
-                                                app.redraw();
-                                            },
-                                        ))
-                                        .ok();
-                                        wake.wake();
-                                    }
-                                });
+                                state.theme = state.theme.next();
+                                state.apply_theme();
+                                state.save_state(app);
                             })
                             .build(app)
-                            .height(30.)
-                            .width(30.),
+                            .height(px(30.))
+                            .width(px(30.)),
                         );
                         buttons.push(
-                            button(
-                                id!(),
-                                (
-                                    &s.dark_mode_button,
-                                    Binding::new(
-                                        |s: &State| &s.dark_mode_button,
-                                        |s: &mut State| &mut s.dark_mode_button,
-                                    ),
-                                ),
-                            )
-                            .surface(move |btn, ctx| {
-                                rect(id!())
-                                    .fill(btn_surface_color(btn, field_bg))
-                                    .stroke(field_border, Stroke::new(1.))
-                                    .corner_rounding(7.)
-                                    .build(ctx)
-                            })
-                            .label(move |btn, ctx| {
-                                svg(id!(), if s.dark_mode { SUN_ICON } else { MOON_ICON })
-                                    .fill(Brush::Solid(btn_label_color(btn, label_color)))
-                                    .finish(ctx)
-                                    .pad(6.)
-                            })
-                            .on_click(|state, _| {
-                                state.dark_mode = !state.dark_mode;
-                            })
-                            .build(app)
-                            .height(30.)
-                            .width(30.),
+                            button(id!(), binding!(s.tint_button))
+                                .surface(move |btn, ctx| {
+                                    rect(id!())
+                                        .fill(btn_surface_color(btn, field_bg))
+                                        .stroke(field_border, Stroke::new(1.))
+                                        .corner_rounding(7.)
+                                        .build(ctx)
+                                })
+                                .label(move |_, ctx| {
+                                    rect(id!())
+                                        .fill(s.ui_tint.preview())
+                                        .stroke(field_border, Stroke::new(1.))
+                                        .corner_rounding(7.)
+                                        .build(ctx)
+                                        .height(px(14.))
+                                        .width(px(14.))
+                                })
+                                .on_click(|state, app| {
+                                    let hue = state.oklch().components[2];
+                                    let hue = if hue.is_finite() { hue } else { 0. };
+                                    state.ui_tint = state.ui_tint.next(hue);
+                                    state.save_state(app);
+                                })
+                                .build(app)
+                                .height(px(30.))
+                                .width(px(30.)),
                         );
+                        buttons.push(zoom_button(s, field_bg, field_border, label_color, app));
+                        buttons.push(sensitivity_button(
+                            s,
+                            field_bg,
+                            field_border,
+                            label_color,
+                            app,
+                        ));
                         buttons
                     }),
                     row_spaced(
-                        10.,
+                        px(10.),
                         vec![
-                            rect(id!())
-                                .fill(s.display_color())
-                                .stroke(field_border, Stroke::new(1.))
-                                .corner_rounding(8.)
-                                .build(app)
+                            color_swatch(s, field_bg, field_border, label_color, app)
                                 .inert_y()
                                 .aspect_width(1.),
                             row_spaced(
-                                10.,
+                                px(10.),
                                 vec![
                                     column_spaced(
-                                        10.,
+                                        px(10.),
                                         (0..3)
                                             .map(|i| {
                                                 let field_id = id!(i as u64);
@@ -619,19 +2286,50 @@ fn view<'a>(s: &'a State, app: &mut PaneState) -> View<'a, State> {
                                                             ),
                                                         ),
                                                     )
-                                                    .font_size(16)
+                                                    .font_size(font(16))
                                                     .text_fill(label_color)
                                                     .cursor_fill(label_color)
                                                     .highlight_fill(highlight_color)
                                                     .singleline()
                                                     .enter_end_editing()
                                                     .esc_end_editing()
-                                                    .on_edit(move |state, _app, edit| match edit {
-                                                        EditInteraction::Start => {}
+                                                    .on_edit(move |state, app, edit| match edit {
+                                                        EditInteraction::Start => {
+                                                            state.format_origin =
+                                                                Some(state.formats()[i].clone());
+                                                        }
                                                         EditInteraction::Update(text) => {
-                                                            state.parse_format(&text);
+                                                            state.bulk_paste = parse_color_list(&text);
+                                                            if !state.parse_format(&text)
+                                                                && state.import_palette_link(&text, app)
+                                                            {
+                                                                state.format_fields[i] = TextState::new(
+                                                                    state.formats()[i].clone(),
+                                                                );
+                                                            }
                                                         }
                                                         EditInteraction::End => {
+                                                            let origin = state
+                                                                .format_origin
+                                                                .take()
+                                                                .unwrap_or_else(|| state.formats()[i].clone());
+                                                            let typed = state.format_fields[i].text.clone();
+                                                            let parsed = match expr::expand(&typed, &origin) {
+                                                                Some(code) => state.parse_format(&code),
+                                                                None => parse_code(&typed).is_some(),
+                                                            };
+                                                            if !parsed
+                                                                && !typed.trim().is_empty()
+                                                                && state.bulk_paste.is_none()
+                                                            {
+                                                                state.toast(
+                                                                    ToastKind::Error,
+                                                                    format!("Couldn't read \"{}\" as a color", typed.trim()),
+                                                                    app,
+                                                                );
+                                                            }
+                                                            state.commit_color(state.edit_source);
+                                                            state.edit_source = HistorySource::Edited;
                                                             let val = state.formats()[i].clone();
                                                             state.format_fields[i] =
                                                                 TextState::new(if i == 0 {
@@ -648,10 +2346,10 @@ fn view<'a>(s: &'a State, app: &mut PaneState) -> View<'a, State> {
                                                             .corner_rounding(6.)
                                                             .build(ctx)
                                                     })
-                                                    .padding(5.)
+                                                    .padding(px(5.))
                                                     .build(app)
                                                     .expand_x()
-                                                    .height(30.),
+                                                    .height(px(30.)),
                                                     rect(overlay_id)
                                                         .fill(Color::TRANSPARENT)
                                                         .view()
@@ -690,25 +2388,19 @@ fn view<'a>(s: &'a State, app: &mut PaneState) -> View<'a, State> {
                                                         )
                                                         .build(app)
                                                         .expand_x()
-                                                        .height(30.),
+                                                        .height(px(30.)),
                                                 ])
                                                 .expand_x()
-                                                .height(30.)
+                                                .height(px(30.))
                                             })
                                             .collect(),
                                     )
                                     .expand_x(),
                                     column_spaced(
-                                        10.,
+                                        px(10.),
                                         (0..3)
                                             .map(|i| {
-                                                let copied = s
-                                                    .copied
-                                                    .try_lock()
-                                                    .map(|c| c[i])
-                                                    .unwrap_or(false);
-                                                let copied_state = s.copied.clone();
-                                                button(
+                                                let copy_button = button(
                                                     id!(i as u64),
                                                     (
                                                         &s.copy_buttons[i],
@@ -732,44 +2424,22 @@ fn view<'a>(s: &'a State, app: &mut PaneState) -> View<'a, State> {
                                                 })
                                                 .label(move |btn, ctx| {
                                                     let c = btn_label_color(btn, label_color);
-                                                    if copied {
-                                                        svg(id!(i as u64), CHECKMARK_ICON)
-                                                            .fill(Brush::Solid(c))
-                                                            .finish(ctx)
-                                                            .width(14.)
-                                                            .height(14.)
-                                                            .pad(5.)
-                                                    } else {
-                                                        svg(id!(i as u64), COPY_ICON)
-                                                            .fill(Brush::Solid(c))
-                                                            .finish(ctx)
-                                                            .width(14.)
-                                                            .height(14.)
-                                                            .pad(5.)
-                                                    }
+                                                    svg(id!(i as u64), COPY_ICON)
+                                                        .fill(Brush::Solid(c))
+                                                        .finish(ctx)
+                                                        .width(px(14.))
+                                                        .height(px(14.))
+                                                        .pad(px(5.))
                                                 })
                                                 .on_click(move |state, app| {
+                                                    state.commit_color(HistorySource::Copied);
                                                     let text = state.formats()[i].clone();
-                                                    if let Ok(mut cb) = Clipboard::new() {
-                                                        let _ = cb.set_text(text);
-                                                    }
-                                                    if let Ok(mut c) = copied_state.try_lock() {
-                                                        c[i] = true;
-                                                    }
-                                                    let copied_reset = copied_state.clone();
-                                                    let wake = app.waker();
-                                                    tokio::spawn(async move {
-                                                        tokio::time::sleep(
-                                                            tokio::time::Duration::from_secs(2),
-                                                        )
-                                                        .await;
-                                                        copied_reset.lock().await[i] = false;
-                                                        wake.wake();
-                                                    });
+                                                    state.copy_text(text, app);
                                                 })
-                                                .build(app)
-                                                .width(30.)
-                                                .height(30.)
+                                                .build(app);
+                                                stack(vec![copy_button, copy_menu_sensor(i, app)])
+                                                    .width(px(30.))
+                                                    .height(px(30.))
                                             })
                                             .collect(),
                                     ),
@@ -777,49 +2447,64 @@ fn view<'a>(s: &'a State, app: &mut PaneState) -> View<'a, State> {
                             ),
                         ],
                     ),
+                    named_color_button(s, label_color, app),
+                    theme_pair_row(s, field_border, label_color, app),
+                    recent_strip(s, field_border, app),
                     row_spaced(
-                        10.,
+                        px(10.),
                         vec![
-                            column_spaced(
-                                8.,
-                                (0..3)
-                                    .map(|i| {
-                                        stack(vec![
-                                            text(id!(i as u64), CHANNELS[i].label)
-                                                .font_size(16)
-                                                .font_weight(FontWeight::BOLD)
-                                                .fill(label_color)
-                                                .build(app),
-                                        ])
-                                        .height(30.)
-                                    })
-                                    .collect(),
-                            )
-                            .width(20.),
-                            column_spaced(
-                                8.,
-                                (0..3)
-                                    .map(|i| {
-                                        channel_slider(
-                                            id!(i as u64),
-                                            i,
-                                            binding!(s.sliders),
-                                            s.values,
-                                            s.theme_inverted(Theme::Gray0),
-                                            app,
-                                        )
-                                    })
-                                    .collect(),
-                            )
+                            column_spaced(px(8.), {
+                                let mut labels: Vec<View<'_, State>> = s
+                                    .mode
+                                    .channels()
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(i, ch)| channel_lock(s, i, ch.label, label_color, app))
+                                    .collect();
+                                labels.push(
+                                    stack(vec![
+                                        text(id!(), "A")
+                                            .font_size(font(16))
+                                            .font_weight(FontWeight::BOLD)
+                                            .fill(label_color)
+                                            .build(app),
+                                    ])
+                                    .height(px(30.)),
+                                );
+                                labels
+                            })
+                            .width(px(34.)),
+                            stack(vec![
+                                slider_drag_sensor(app),
+                                column_spaced(px(8.), {
+                                    let channel_count = s.mode.channels().len();
+                                    let mut sliders: Vec<View<'_, State>> = (0..channel_count)
+                                        .map(|i| {
+                                            channel_slider(
+                                                id!(i as u64),
+                                                i,
+                                                &s.sliders[i],
+                                                s.mode,
+                                                s.slider_components(),
+                                                s.theme_inverted(Theme::Gray0),
+                                                app,
+                                            )
+                                        })
+                                        .collect();
+                                    sliders.push(alpha_slider(s, app));
+                                    sliders
+                                }),
+                            ])
                             .width_range(200.0..),
                         ],
                     ),
-                    palette_grid(s, app),
+                    palette_grid(s, label_color, highlight_color, app),
+                    palette_actions(s, label_color, app),
                 ],
             )
-            .pad_x(20.)
-            .pad_top(20.)
-            .pad_bottom(10.)
+            .pad_x(px(20.))
+            .pad_top(px(20.))
+            .pad_bottom(px(10.))
             .expand_y(),
             space(),
             rect(id!())
@@ -827,12 +2512,286 @@ fn view<'a>(s: &'a State, app: &mut PaneState) -> View<'a, State> {
                 .corner_rounding(0.)
                 .build(app)
                 .height(1.),
-            row(vec![update_button(s, label_color, app), space().inert_y()])
-                .pad_x(20.)
-                .pad_y(6.),
+            row(vec![
+                update_button(s, label_color, app),
+                space().inert_y(),
+                contrast_button(s, label_color, app),
+            ])
+                .pad_x(px(20.))
+                .pad_y(px(6.)),
         ]),
         text_popover_layer(s, field_bg, field_border, label_color, app),
+        copy_menu_layer(s, field_bg, field_border, label_color, app),
+        contrast_grid_layer(s, field_bg, field_border, label_color, app),
+        figma_layer(s, field_bg, field_border, label_color, highlight_color, app),
+        history_layer(s, field_bg, field_border, label_color, highlight_color, app),
+        swatch_layer(s, field_bg, field_border, label_color, app),
+        picker_settings_layer(s, field_bg, field_border, label_color, highlight_color, app),
+        explorer_layer(s, field_bg, field_border, label_color, app),
+        gradient_layer(s, field_bg, field_border, label_color, app),
+        formats_layer(s, field_bg, field_border, label_color, app),
+        blend_preview_layer(s, field_bg, field_border, label_color, highlight_color, app),
+        bulk_paste_layer(s, field_bg, field_border, label_color, app),
+        onboarding_layer(s, field_bg, field_border, label_color, app),
+        toast_layer(s, field_bg, field_border, label_color, app),
+    ])
+}
+
+fn slider_drag_sensor(app: &mut PaneState) -> View<'static, State> {
+    rect(id!())
+        .fill(Color::TRANSPARENT)
+        .view()
+        .gesture(
+            gesture::drag(id!())
+                .anywhere()
+                .button(MouseButton::Left)
+                .observe()
+                .run(|state: &mut State, _app, event| {
+                    if matches!(event, DragPhase::Completed { .. }) {
+                        state.slider_drag = None;
+                    }
+                }),
+        )
+        .build(app)
+}
+
+fn picker_settings_sensor(app: &mut PaneState) -> View<'static, State> {
+    rect(id!())
+        .fill(Color::TRANSPARENT)
+        .view()
+        .gesture(gesture::click(id!()).button(MouseButton::Right).run(
+            |state: &mut State, app, event| {
+                if matches!(event.state, ClickPhase::Completed) {
+                    state.picker_settings.open = !state.picker_settings.open;
+                    app.redraw();
+                }
+            },
+        ))
+        .build(app)
+}
+
+fn copy_menu_sensor(index: usize, app: &mut PaneState) -> View<'static, State> {
+    rect(id!(index as u64))
+        .fill(Color::TRANSPARENT)
+        .view()
+        .gesture(
+            gesture::click(id!(index as u64))
+                .button(MouseButton::Right)
+                .run(move |state: &mut State, _app, event| {
+                    if matches!(event.state, ClickPhase::Completed) {
+                        state.text_popover = None;
+                        state.copy_menu = Some(CopyMenu {
+                            position: event.location.global(),
+                            buttons: Default::default(),
+                            base_buttons: Default::default(),
+                        });
+                    }
+                }),
+        )
+        .build(app)
+}
+
+fn copy_menu_layer<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    const MENU_WIDTH: f32 = 110.;
+    let Some(menu) = s.copy_menu.as_ref() else {
+        return empty();
+    };
+    let x = menu.position.x as f32 - px(MENU_WIDTH);
+    let y = menu.position.y as f32;
+
+    let mut buttons: Vec<_> = CopyFormat::ALL
+        .into_iter()
+        .enumerate()
+        .map(|(i, format)| {
+            button(
+                id!(i as u64),
+                (
+                    &menu.buttons[i],
+                    Binding::new(
+                        move |s: &State| &s.copy_menu.as_ref().unwrap().buttons[i],
+                        move |s: &mut State| &mut s.copy_menu.as_mut().unwrap().buttons[i],
+                    ),
+                ),
+            )
+            .surface(move |btn, ctx| {
+                rect(id!(i as u64))
+                    .fill(btn_surface_color(btn, field_bg))
+                    .corner_rounding(5.)
+                    .build(ctx)
+            })
+            .label(move |btn, ctx| {
+                row(vec![
+                    text(id!(i as u64), format.label())
+                        .font_size(font(13))
+                        .fill(btn_label_color(btn, label_color))
+                        .build(ctx),
+                    space().inert_y(),
+                ])
+                .pad_x(px(8.))
+            })
+            .on_click(move |state, app| {
+                let [r, g, b, _] = state.srgb().components;
+                let text = format.format([r, g, b], state.values, state.alpha);
+                state.copy_text(text, app);
+                state.commit_color(HistorySource::Copied);
+                state.copy_menu = None;
+            })
+            .build(app)
+            .width(px(MENU_WIDTH - 6.))
+            .height(px(24.))
+        })
+        .collect();
+
+    let base_entry = move |i: usize,
+                           label: &'static str,
+                           on_click: fn(&mut State, &mut PaneState),
+                           app: &mut PaneState| {
+        let key = 100 + i as u64;
+        button(
+            id!(key),
+            (
+                &menu.base_buttons[i],
+                Binding::new(
+                    move |s: &State| &s.copy_menu.as_ref().unwrap().base_buttons[i],
+                    move |s: &mut State| &mut s.copy_menu.as_mut().unwrap().base_buttons[i],
+                ),
+            ),
+        )
+        .surface(move |btn, ctx| {
+            rect(id!(key))
+                .fill(btn_surface_color(btn, field_bg))
+                .corner_rounding(5.)
+                .build(ctx)
+        })
+        .label(move |btn, ctx| {
+            row(vec![
+                text(id!(key), label)
+                    .font_size(font(13))
+                    .fill(btn_label_color(btn, label_color))
+                    .build(ctx),
+                space().inert_y(),
+            ])
+            .pad_x(px(8.))
+        })
+        .on_click(move |state, app| {
+            on_click(state, app);
+            state.copy_menu = None;
+        })
+        .build(app)
+        .width(px(MENU_WIDTH - 6.))
+        .height(px(24.))
+    };
+    if let Some(base) = &s.relative_base {
+        buttons.push(base_entry(
+            0,
+            "from base",
+            |state, app| {
+                if let Some(base) = &state.relative_base {
+                    let code = base.relative(state.values, state.alpha);
+                    state.copy_text(code, app);
+                }
+                state.commit_color(HistorySource::Copied);
+            },
+            app,
+        ));
+        if base.mix(s.values).is_some() {
+            buttons.push(base_entry(
+                1,
+                "mix of base",
+                |state, app| {
+                    if let Some(code) = state
+                        .relative_base
+                        .as_ref()
+                        .and_then(|b| b.mix(state.values))
+                    {
+                        state.copy_text(code, app);
+                    }
+                    state.commit_color(HistorySource::Copied);
+                },
+                app,
+            ));
+        }
+    }
+    buttons.push(base_entry(
+        2,
+        "set as base",
+        |state, app| {
+            let var = state
+                .relative_base
+                .take()
+                .map_or_else(|| "--brand".to_string(), |base| base.var);
+            state.relative_base = Some(RelativeBase {
+                var,
+                oklch: state.values,
+            });
+            state.save_state(app);
+        },
+        app,
+    ));
+    buttons.push(base_entry(
+        3,
+        "save swatch…",
+        |state, app| {
+            state.swatch.open = true;
+            app.redraw();
+        },
+        app,
+    ));
+    buttons.push(base_entry(
+        4,
+        "all formats…",
+        |state, app| {
+            state.formats_open = true;
+            app.redraw();
+        },
+        app,
+    ));
+    buttons.push(base_entry(
+        5,
+        "blend preview…",
+        |state, app| {
+            state.blend_preview.open = true;
+            state.blend_preview.alpha = state.alpha;
+            app.redraw();
+        },
+        app,
+    ));
+
+    let close_outside = gesture::click(id!())
+        .anywhere()
+        .button(MouseButton::Left)
+        .observe()
+        .run(|state: &mut State, _app, event| {
+            if matches!(event.state, ClickPhase::Completed) {
+                state.copy_menu = None;
+            }
+        });
+
+    stack(vec![
+        stack(vec![
+            shadow(id!()).build(app).offset(0., 5.),
+            rect(id!())
+                .fill(field_bg)
+                .stroke(field_border, Stroke::new(1.))
+                .corner_rounding(6.)
+                .view()
+                .occlude(&close_outside)
+                .build(app),
+            column_spaced(px(2.), buttons).pad(px(3.)),
+        ])
+        .align(Align::TopLeading),
     ])
+    .width(1.)
+    .height(1.)
+    .offset(x, y)
+    .align(Align::TopLeading)
+    .layer(10)
 }
 
 fn text_popover_layer<'a>(
@@ -905,6 +2864,7 @@ fn text_popover_layer<'a>(
         label_color,
         app,
         move |state, app| {
+            state.edit_source = HistorySource::Pasted;
             state.format_fields[field].paste_text(app);
         },
     );
@@ -948,7 +2908,7 @@ fn text_popover_layer<'a>(
                 .occlude(&close_outside)
                 .occlude(&close_drag_outside)
                 .build(app),
-            column_spaced(2., vec![cut_button, copy_button, paste_button]).pad(3.),
+            column_spaced(px(2.), vec![cut_button, copy_button, paste_button]).pad(px(3.)),
         ])
         .align(Align::TopLeading),
     ])
@@ -983,7 +2943,7 @@ fn context_menu_button<'a>(
         .label(move |btn, ctx| {
             row(vec![
                 text(id!(action_id, 2_u64), label)
-                    .font_size(13)
+                    .font_size(font(13))
                     .fill(btn_label_color(btn, label_color))
                     .build(ctx),
                 space().inert_y(),
@@ -994,15 +2954,15 @@ fn context_menu_button<'a>(
                     ctx,
                 ),
             ])
-            .pad_x(8.)
+            .pad_x(px(8.))
         })
         .on_click(move |state, app| {
             on_click(state, app);
             state.text_popover = None;
         })
         .build(app)
-        .width(90.)
-        .height(28.)
+        .width(px(90.))
+        .height(px(28.))
 }
 
 fn shortcut_view<'a>(
@@ -1014,27 +2974,77 @@ fn shortcut_view<'a>(
     let color = color.with_alpha(0.7);
     if cfg!(target_os = "macos") {
         row_spaced(
-            3.,
+            px(3.),
             vec![
                 text(id!(action_id, 3_u64), "⌘")
                     .font_family("Apple Symbols")
-                    .font_size(12)
+                    .font_size(font(12))
                     .fill(color)
                     .build(app),
                 text(id!(action_id, 4_u64), key)
-                    .font_size(12)
+                    .font_size(font(12))
                     .fill(color)
                     .build(app),
             ],
         )
     } else {
         text(id!(action_id, 5_u64), format!("Ctrl {key}"))
-            .font_size(12)
+            .font_size(font(12))
             .fill(color)
             .build(app)
     }
 }
 
+fn channel_lock<'a>(
+    s: &'a State,
+    i: usize,
+    label: &'static str,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    let locked = s.locks[i];
+    let gray = s.theme(Theme::Gray70);
+    button(
+        id!(i as u64),
+        (
+            &s.lock_buttons[i],
+            Binding::new(
+                move |s: &State| &s.lock_buttons[i],
+                move |s: &mut State| &mut s.lock_buttons[i],
+            ),
+        ),
+    )
+    .surface(move |_, _ctx| space().height(0.).width(0.))
+    .label(move |btn, ctx| {
+        let lock_color = if locked {
+            label_color
+        } else if btn.hovered {
+            gray
+        } else {
+            TRANSPARENT
+        };
+        row_spaced(
+            px(2.),
+            vec![
+                text(id!(i as u64), label)
+                    .font_size(font(16))
+                    .font_weight(FontWeight::BOLD)
+                    .fill(label_color)
+                    .build(ctx),
+                svg(id!(i as u64), LOCK_ICON)
+                    .fill(Brush::Solid(lock_color))
+                    .finish(ctx)
+                    .width(px(11.))
+                    .height(px(11.)),
+                space().inert_y(),
+            ],
+        )
+    })
+    .on_click(move |state, _app| state.locks[i] = !state.locks[i])
+    .build(app)
+    .height(px(30.))
+}
+
 fn update_button<'a>(s: &'a State, label_color: Color, app: &mut PaneState) -> View<'a, State> {
     let status = &s.update_status;
     let btn = s.update_button;
@@ -1046,149 +3056,2728 @@ fn update_button<'a>(s: &'a State, label_color: Color, app: &mut PaneState) -> V
         UpdateStatus::Installing { .. } => "installing...".to_string(),
         UpdateStatus::Updated { .. } => "restart to update".to_string(),
         UpdateStatus::UpToDate { .. } => "up to date :)".to_string(),
+        UpdateStatus::Available { version, .. } => format!("update to {version}"),
         UpdateStatus::Error(msg) => {
-            if msg.len() > 30 {
-                format!("{}...", &msg[..27])
+            if msg.chars().count() > 30 {
+                format!("{}...", msg.chars().take(27).collect::<String>())
             } else {
                 msg.clone()
             }
         }
     };
     let gray = s.theme(Theme::Gray70);
-    button(id!(), binding!(s.update_button))
+    let update = button(id!(), binding!(s.update_button))
         .surface(move |_, _ctx| space().height(0.).width(0.))
         .label(move |btn, ctx| {
             let c = if btn.hovered { label_color } else { gray };
-            text(id!(), &label_text).font_size(13).fill(c).build(ctx)
+            text(id!(), &label_text)
+                .font_size(font(13))
+                .fill(c)
+                .build(ctx)
         })
         .on_click(move |state, app| {
-            if matches!(state.update_status, UpdateStatus::Updated { .. }) {
+            if let UpdateStatus::Updated { version } = &state.update_status {
+                let version = version.clone();
+                let saved = state.saved_state();
                 tokio::spawn(async move {
-                    if let Err(e) = restart_application().await {
+                    let restarted = match State::config_path() {
+                        Some(path) => {
+                            restart_into_update(&path, saved, version, restart_application()).await
+                        }
+                        None => restart_application().await,
+                    };
+                    if let Err(e) = restarted {
                         log::error!("Failed to restart: {e}");
                     }
                 });
             } else if !matches!(state.update_status, UpdateStatus::Checking) {
-                state.update_status = UpdateStatus::Checking;
-                app.redraw();
-                let tx = state.tx.clone();
-                let wake = app.waker();
-                tokio::spawn(async move {
-                    let updater = AutoUpdater::new();
-                    updater
-                        .check_and_install_updates_with_callback(Some(
-                            move |new_status: UpdateStatus| {
-                                let tx = tx.clone();
-                                let wake = wake.clone();
-                                async move {
-                                    tx.send(Box::new(
-                                        move |state: &mut State, app: &mut PaneState| {
-                                            state.update_status = new_status;
-                                            app.redraw();
-                                        },
-                                    ))
-                                    .ok();
-                                    wake.wake();
-                                }
-                            },
-                        ))
-                        .await;
-                });
+                state.check_for_updates(true, app);
             }
         })
         .build(app)
-        .height(25.)
+        .height(px(25.));
+    if !matches!(status, UpdateStatus::Available { .. }) {
+        return update;
+    }
+    let skip_button = button(id!(), binding!(s.skip_update_button))
+        .surface(move |_, _ctx| space().height(0.).width(0.))
+        .label(move |btn, ctx| {
+            let c = if btn.hovered { label_color } else { gray };
+            text(id!(), "skip").font_size(font(13)).fill(c).build(ctx)
+        })
+        .on_click(|state, app| {
+            state.skip_update();
+            state.save_state(app);
+            app.redraw();
+        })
+        .build(app)
+        .height(px(25.));
+    let later_button = button(id!(), binding!(s.remind_later_button))
+        .surface(move |_, _ctx| space().height(0.).width(0.))
+        .label(move |btn, ctx| {
+            let c = if btn.hovered { label_color } else { gray };
+            text(id!(), "later").font_size(font(13)).fill(c).build(ctx)
+        })
+        .on_click(|state, app| {
+            state.remind_later();
+            state.save_state(app);
+            app.redraw();
+        })
+        .build(app)
+        .height(px(25.));
+    row_spaced(px(10.), vec![update, skip_button, later_button])
 }
 
-fn channel_slider<'a>(
-    key: u64,
-    i: usize,
-    binding: (&[SliderState; 3], Binding<State, [SliderState; 3]>),
-    values: [f32; 3],
-    knob_color: Color,
+fn color_swatch<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
     app: &mut PaneState,
 ) -> View<'a, State> {
-    let ch = &CHANNELS[i];
-    #[cfg(test)]
-    let slider_id = TEST_CHANNEL_SLIDER_IDS[i];
-    #[cfg(not(test))]
-    let slider_id = id!(key);
-    let stops: Vec<Color> = (0..=16)
-        .map(|step| {
-            let t = step as f32 / 16.0;
-            let val = ch.min + t * (ch.max - ch.min);
-            let mut v = values;
-            v[i] = val;
-            let oklch = AlphaColor::<Oklch>::new([v[0], v[1], v[2], 1.0]);
-            oklch.convert::<Srgb>()
-        })
-        .collect();
-    slider(
-        slider_id,
-        (
-            &binding.0[i],
-            Binding::new(
-                move |s: &State| &s.sliders[i],
-                move |s: &mut State| &mut s.sliders[i],
-            ),
-        ),
-    )
-    .range(ch.min, ch.max)
-    .background(move |_, area, ctx| {
-        rect(id!(key))
-            .fill(
-                Gradient::new_linear(
-                    (area.x as f64, area.y as f64),
-                    (area.x as f64 + area.width as f64, area.y as f64),
-                )
-                .with_stops(stops.as_slice()),
-            )
-            .corner_rounding(area.height)
-            .build(ctx)
-    })
-    .track(move |_, area, ctx| {
-        rect(id!(key))
-            .fill(Color::TRANSPARENT)
-            .corner_rounding(area.height)
-            .build(ctx)
-    })
-    .traveled_track(move |_, area, ctx| {
-        rect(id!(key))
-            .fill(Color::TRANSPARENT)
-            .corner_rounding(area.height)
-            .build(ctx)
-    })
-    .knob(move |state, _, ctx| {
-        circle(id!(key))
-            .fill(btn_label_color(
-                ButtonState {
-                    depressed: state.dragging,
-                    hovered: state.hovered,
+    let mut current = vec![
+        rect(id!())
+            .fill(s.display_color())
+            .stroke(field_border, Stroke::new(1.))
+            .corner_rounding(8.)
+            .view()
+            .gesture(gesture::click(id!()).button(MouseButton::Left).run(
+                |state: &mut State, app, event| {
+                    if matches!(event.state, ClickPhase::Completed) {
+                        state.explorer_open = !state.explorer_open;
+                        app.redraw();
+                    }
                 },
-                knob_color,
             ))
-            .finish(ctx)
-    })
-    .on_change(move |state, app, val| {
-        let mut values = state.values;
-        values[i] = val;
-        state.set_values(values, app);
-    })
-    .build(app)
-    .height(26.)
-    .pad_y(2.)
+            .build(app),
+    ];
+    if !in_srgb_gamut(s.values) {
+        current.push(gamut_badge(s, field_bg, label_color, app));
+    }
+    current.push(pin_button(s, field_bg, label_color, app));
+    let Some((values, alpha)) = s.pinned else {
+        return stack(current);
+    };
+    let [r, g, b] = s.gamut_mapping.to_srgb(values);
+    column_spaced(
+        px(4.),
+        vec![
+            stack(current),
+            rect(id!())
+                .fill(Color::new([r, g, b, alpha]))
+                .stroke(field_border, Stroke::new(1.))
+                .corner_rounding(8.)
+                .build(app),
+        ],
+    )
+}
+
+fn pin_button<'a>(
+    s: &'a State,
+    field_bg: Color,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    let pinned = s.pinned.is_some();
+    button(id!(), binding!(s.pin_button))
+        .surface(move |btn, ctx| {
+            rect(id!())
+                .fill(btn_surface_color(btn, field_bg))
+                .corner_rounding(5.)
+                .build(ctx)
+        })
+        .label(move |btn, ctx| {
+            let c = if pinned {
+                label_color
+            } else {
+                btn_label_color(btn, label_color)
+            };
+            svg(id!(), PIN_ICON)
+                .fill(Brush::Solid(c))
+                .finish(ctx)
+                .pad(px(3.))
+        })
+        .on_click(|state, app| {
+            state.toggle_pin();
+            app.redraw();
+        })
+        .build(app)
+        .height(px(20.))
+        .width(px(20.))
+        .align(Align::TopTrailing)
+        .pad(px(5.))
+}
+
+fn gamut_badge<'a>(
+    s: &'a State,
+    field_bg: Color,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    button(id!(), binding!(s.gamut_button))
+        .surface(move |btn, ctx| {
+            rect(id!())
+                .fill(btn_surface_color(btn, field_bg))
+                .stroke(WARNING_COLOR, Stroke::new(1.))
+                .corner_rounding(5.)
+                .build(ctx)
+        })
+        .label(move |btn, ctx| {
+            text(id!(), format!("! {}", s.gamut_mapping.label()))
+                .font_size(font(11))
+                .font_weight(FontWeight::BOLD)
+                .fill(btn_label_color(btn, label_color))
+                .build(ctx)
+        })
+        .on_click(|state, app| {
+            state.gamut_mapping = state.gamut_mapping.toggled();
+            state.update_ui();
+            state.save_state(app);
+            app.redraw();
+        })
+        .build(app)
+        .height(px(20.))
+        .width(px(42.))
+        .align(Align::BottomTrailing)
+        .pad(px(5.))
+}
+
+fn named_color_button<'a>(
+    s: &'a State,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    let (name, distance) = named_colors::nearest(s.values);
+    let label_text = if s.named_color_button.hovered {
+        format!("copy \"most like: {name}\"")
+    } else {
+        format!("most like {name} · ΔE {distance:.1}")
+    };
+    let gray = s.theme(Theme::Gray70);
+    row(vec![
+        button(id!(), binding!(s.named_color_button))
+            .surface(move |_, _ctx| space().height(0.).width(0.))
+            .label(move |btn, ctx| {
+                let c = if btn.hovered { label_color } else { gray };
+                text(id!(), &label_text)
+                    .font_size(font(13))
+                    .fill(c)
+                    .build(ctx)
+            })
+            .on_click(move |state, app| {
+                state.copy_text(format!("most like: {name}"), app);
+            })
+            .build(app)
+            .height(px(20.)),
+        space().inert_y(),
+        material_button(s, label_color, app),
+    ])
+}
+
+fn material_button<'a>(s: &'a State, label_color: Color, app: &mut PaneState) -> View<'a, State> {
+    let (token, distance) = material::nearest(s.values);
+    let label_text = if s.material_button.hovered {
+        format!("copy {token}")
+    } else {
+        format!("md {token} · ΔE {distance:.1}")
+    };
+    let gray = s.theme(Theme::Gray70);
+    button(id!(), binding!(s.material_button))
+        .surface(move |_, _ctx| space().height(0.).width(0.))
+        .label(move |btn, ctx| {
+            let c = if btn.hovered { label_color } else { gray };
+            text(id!(), &label_text)
+                .font_size(font(13))
+                .fill(c)
+                .build(ctx)
+        })
+        .on_click(move |state, app| {
+            state.copy_text(token.to_string(), app);
+        })
+        .build(app)
+        .height(px(20.))
+}
+
+fn theme_pair_row<'a>(
+    s: &'a State,
+    field_border: Color,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    let (light, dark) = contrast::theme_pair(s.values);
+    let pair = [light, dark].map(|values| {
+        let [r, g, b] = s.gamut_mapping.to_srgb(values);
+        (values, [r, g, b])
+    });
+    let background = |key: u64, fill: Color, app: &mut PaneState| {
+        stack(vec![
+            rect(id!(key))
+                .fill(fill)
+                .stroke(field_border, Stroke::new(1.))
+                .corner_rounding(5.)
+                .build(app),
+            row_spaced(
+                px(4.),
+                pair.iter()
+                    .enumerate()
+                    .map(|(i, (_, [r, g, b]))| {
+                        rect(id!(key, i as u64))
+                            .fill(Color::new([*r, *g, *b, 1.0]))
+                            .corner_rounding(3.)
+                            .build(app)
+                            .width(px(26.))
+                            .height(px(14.))
+                    })
+                    .collect(),
+            )
+            .pad(px(5.)),
+        ])
+        .height(px(24.))
+    };
+    let gray = s.theme(Theme::Gray70);
+    let label_text = if s.theme_pair_button.hovered {
+        "copy pair"
+    } else {
+        "light / dark"
+    };
+    row_spaced(
+        px(8.),
+        vec![
+            background(0, Color::WHITE, app),
+            background(1, Color::BLACK, app),
+            button(id!(), binding!(s.theme_pair_button))
+                .surface(move |_, _ctx| space().height(0.).width(0.))
+                .label(move |btn, ctx| {
+                    let c = if btn.hovered { label_color } else { gray };
+                    text(id!(), label_text)
+                        .font_size(font(13))
+                        .fill(c)
+                        .build(ctx)
+                })
+                .on_click(move |state, app| {
+                    let [light, dark] =
+                        pair.map(|(values, rgb)| CopyFormat::Hex.format(rgb, values, 1.0));
+                    state.copy_text(format!("light: {light}\ndark: {dark}"), app);
+                })
+                .build(app)
+                .height(px(24.)),
+            space().inert_y(),
+        ],
+    )
+}
+
+fn zoom_button<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    let label_text = format!("{:.0}%", s.ui_scale * 100.);
+    button(id!(), binding!(s.zoom_button))
+        .surface(move |btn, ctx| {
+            rect(id!())
+                .fill(btn_surface_color(btn, field_bg))
+                .stroke(field_border, Stroke::new(1.))
+                .corner_rounding(7.)
+                .build(ctx)
+        })
+        .label(move |btn, ctx| {
+            text(id!(), &label_text)
+                .font_size(font(12))
+                .fill(btn_label_color(btn, label_color))
+                .build(ctx)
+        })
+        .on_click(|state, app| {
+            state.ui_scale = ui_scale::next(state.ui_scale);
+            ui_scale::set(state.ui_scale);
+            state.save_state(app);
+            app.redraw();
+        })
+        .build(app)
+        .height(px(30.))
+        .width(px(44.))
+}
+
+fn sensitivity_button<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    let label_text = format!("{}×", s.drag_sensitivity);
+    button(id!(), binding!(s.sensitivity_button))
+        .surface(move |btn, ctx| {
+            rect(id!())
+                .fill(btn_surface_color(btn, field_bg))
+                .stroke(field_border, Stroke::new(1.))
+                .corner_rounding(7.)
+                .build(ctx)
+        })
+        .label(move |btn, ctx| {
+            text(id!(), &label_text)
+                .font_size(font(12))
+                .fill(btn_label_color(btn, label_color))
+                .build(ctx)
+        })
+        .on_click(|state, app| {
+            state.drag_sensitivity = drag::next(state.drag_sensitivity);
+            state.save_state(app);
+        })
+        .build(app)
+        .height(px(30.))
+        .width(px(44.))
+}
+
+fn contrast_button<'a>(s: &'a State, label_color: Color, app: &mut PaneState) -> View<'a, State> {
+    let [r, g, b, _] = s.srgb().components.map(|c| c.clamp(0., 1.));
+    let color = [r, g, b];
+    let label_text = if s.contrast_button.hovered {
+        format!("switch to {}", s.contrast.toggled().label())
+    } else {
+        format!(
+            "{} {} on white · {} on black",
+            s.contrast.label(),
+            s.contrast.format(color, [1.; 3]),
+            s.contrast.format(color, [0.; 3]),
+        )
+    };
+    let gray = s.theme(Theme::Gray70);
+    button(id!(), binding!(s.contrast_button))
+        .surface(move |_, _ctx| space().height(0.).width(0.))
+        .label(move |btn, ctx| {
+            let c = if btn.hovered { label_color } else { gray };
+            text(id!(), &label_text)
+                .font_size(font(13))
+                .fill(c)
+                .build(ctx)
+        })
+        .on_click(|state, app| {
+            state.contrast = state.contrast.toggled();
+            state.save_state(app);
+            app.redraw();
+        })
+        .build(app)
+        .height(px(25.))
+}
+
+fn grid_button<'a>(s: &'a State, label_color: Color, app: &mut PaneState) -> View<'a, State> {
+    let gray = s.theme(Theme::Gray70);
+    button(id!(), binding!(s.grid_button))
+        .surface(move |_, _ctx| space().height(0.).width(0.))
+        .label(move |btn, ctx| {
+            let c = if btn.hovered { label_color } else { gray };
+            text(id!(), "grid").font_size(font(13)).fill(c).build(ctx)
+        })
+        .on_click(|state, app| {
+            state.contrast_grid_open = !state.contrast_grid_open;
+            app.redraw();
+        })
+        .build(app)
+        .height(px(20.))
+}
+
+fn popover_panel<'a>(
+    key: u64,
+    width: f32,
+    content: View<'a, State>,
+    close: fn(&mut State),
+    field_bg: Color,
+    field_border: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    let close_outside = gesture::click(id!(key))
+        .anywhere()
+        .button(MouseButton::Left)
+        .observe()
+        .run(move |state: &mut State, _app, event| {
+            if matches!(event.state, ClickPhase::Completed) {
+                close(state);
+            }
+        });
+
+    stack(vec![
+        stack(vec![
+            shadow(id!(key)).build(app).offset(0., 5.),
+            rect(id!(key))
+                .fill(field_bg)
+                .stroke(field_border, Stroke::new(1.))
+                .corner_rounding(6.)
+                .view()
+                .occlude(&close_outside)
+                .build(app),
+            content,
+        ])
+        .width(px(width))
+        .align(Align::TopLeading),
+    ])
+    .width(1.)
+    .height(1.)
+    .offset(px(20.), px(60.))
+    .align(Align::TopLeading)
+    .layer(10)
+}
+
+fn contrast_grid_layer<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    const GRID_WIDTH: f32 = 360.;
+    // Cells narrower than this only show the pairing, not the number.
+    const LABELED_CELL: f32 = 40.;
+    if !s.contrast_grid_open {
+        return empty();
+    }
+    let colors = s.contrast_grid_colors();
+    let gray = s.theme(Theme::Gray70);
+    let rgb_color = |[r, g, b]: [f32; 3]| Color::new([r, g, b, 1.0]);
+    let cell = ((px(GRID_WIDTH) - px(16.)) / (colors.len() + 1) as f32).min(px(48.));
+
+    let header = row_spaced(
+        px(10.),
+        vec![
+            text(id!(), "contrast grid")
+                .font_size(font(13))
+                .fill(label_color)
+                .build(app)
+                .height(px(20.)),
+            space().inert_y(),
+            button(id!(), binding!(s.grid_save_button))
+                .surface(move |_, _ctx| space().height(0.).width(0.))
+                .label(move |btn, ctx| {
+                    let c = if btn.hovered { label_color } else { gray };
+                    text(id!(), "save .png")
+                        .font_size(font(13))
+                        .fill(c)
+                        .build(ctx)
+                })
+                .on_click(|state, app| {
+                    let algorithm = state.contrast;
+                    let colors = state.contrast_grid_colors();
+                    state.save_in_background(
+                        async move {
+                            let bytes = contrast_grid::encode(&colors, algorithm)?;
+                            export::save_file(bytes, "contrast-grid.png", "PNG image", "png").await
+                        },
+                        app,
+                    );
+                })
+                .build(app)
+                .height(px(20.)),
+        ],
+    );
+
+    let grid = if colors.is_empty() {
+        text(id!(), "the palette is empty")
+            .font_size(font(12))
+            .fill(gray)
+            .build(app)
+            .height(px(20.))
+    } else {
+        let mut rows = vec![row_spaced(
+            px(1.),
+            std::iter::once(space().height(cell).width(cell))
+                .chain(colors.iter().enumerate().map(|(col, rgb)| {
+                    rect(id!(col as u64))
+                        .fill(rgb_color(*rgb))
+                        .corner_rounding(3.)
+                        .build(app)
+                        .height(cell)
+                        .width(cell)
+                }))
+                .collect(),
+        )];
+        rows.extend(colors.iter().enumerate().map(|(row, text_rgb)| {
+            let mut cells = vec![
+                rect(id!(row as u64))
+                    .fill(rgb_color(*text_rgb))
+                    .corner_rounding(3.)
+                    .build(app)
+                    .height(cell)
+                    .width(cell),
+            ];
+            cells.extend(colors.iter().enumerate().map(|(col, bg_rgb)| {
+                let key = (row * colors.len() + col) as u64;
+                let label = if cell >= px(LABELED_CELL) {
+                    s.contrast.format(*text_rgb, *bg_rgb)
+                } else {
+                    "•".to_string()
+                };
+                stack(vec![
+                    rect(id!(key)).fill(rgb_color(*bg_rgb)).build(app),
+                    text(id!(key), label)
+                        .font_size(font(10))
+                        .fill(rgb_color(*text_rgb))
+                        .build(app),
+                ])
+                .height(cell)
+                .width(cell)
+            }));
+            row_spaced(px(1.), cells)
+        }));
+        column_spaced(px(1.), rows)
+    };
+
+    popover_panel(
+        0,
+        GRID_WIDTH,
+        column_spaced(px(6.), vec![header, grid]).pad(px(8.)),
+        |state| state.contrast_grid_open = false,
+        field_bg,
+        field_border,
+        app,
+    )
+}
+
+fn figma_layer<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    highlight_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    const PANEL_WIDTH: f32 = 360.;
+    if !s.figma.open {
+        return empty();
+    }
+    let gray = s.theme(Theme::Gray70);
+    let input = move |key: u64,
+                      field: &'a TextState,
+                      binding: Binding<State, TextState>,
+                      app: &mut PaneState| {
+        text_field(id!(key), (field, binding))
+            .font_size(font(13))
+            .text_fill(label_color)
+            .cursor_fill(label_color)
+            .highlight_fill(highlight_color)
+            .singleline()
+            .enter_end_editing()
+            .esc_end_editing()
+            .background(move |_, _, ctx| {
+                rect(id!(key))
+                    .fill(s.theme(Theme::Gray0))
+                    .stroke(field_border, Stroke::new(1.))
+                    .corner_rounding(6.)
+                    .build(ctx)
+            })
+            .padding(px(5.))
+            .build(app)
+            .expand_x()
+            .height(px(28.))
+    };
+    let caption = move |key: u64, content: &'static str, app: &mut PaneState| {
+        text(id!(key), content)
+            .font_size(font(12))
+            .fill(gray)
+            .build(app)
+            .height(px(16.))
+    };
+    let status_text = match &s.figma.status {
+        FigmaStatus::Idle => String::new(),
+        FigmaStatus::Publishing => "publishing…".to_string(),
+        FigmaStatus::Published { count } => format!("published {count} colors"),
+        FigmaStatus::Error(msg) if msg.chars().count() > 40 => {
+            format!("{}...", msg.chars().take(37).collect::<String>())
+        }
+        FigmaStatus::Error(msg) => msg.clone(),
+    };
+    let status_color = if matches!(s.figma.status, FigmaStatus::Error(_)) {
+        WARNING_COLOR
+    } else {
+        gray
+    };
+
+    let contents = vec![
+        text(id!(), "publish palette to figma")
+            .font_size(font(13))
+            .fill(label_color)
+            .build(app)
+            .height(px(20.)),
+        caption(0, "personal access token", app),
+        input(
+            0,
+            &s.figma.token,
+            Binding::new(
+                |s: &State| &s.figma.token,
+                |s: &mut State| &mut s.figma.token,
+            ),
+            app,
+        ),
+        caption(1, "file link or key", app),
+        input(
+            1,
+            &s.figma.file,
+            Binding::new(|s: &State| &s.figma.file, |s: &mut State| &mut s.figma.file),
+            app,
+        ),
+        row_spaced(
+            px(10.),
+            vec![
+                text(id!(), status_text)
+                    .font_size(font(12))
+                    .fill(status_color)
+                    .build(app)
+                    .height(px(20.)),
+                space().inert_y(),
+                button(
+                    id!(),
+                    (
+                        &s.figma.publish_button,
+                        Binding::new(
+                            |s: &State| &s.figma.publish_button,
+                            |s: &mut State| &mut s.figma.publish_button,
+                        ),
+                    ),
+                )
+                .surface(move |btn, ctx| {
+                    rect(id!())
+                        .fill(btn_surface_color(btn, field_bg))
+                        .stroke(field_border, Stroke::new(1.))
+                        .corner_rounding(7.)
+                        .build(ctx)
+                })
+                .label(move |btn, ctx| {
+                    text(id!(), "publish")
+                        .font_size(font(13))
+                        .fill(btn_label_color(btn, label_color))
+                        .build(ctx)
+                })
+                .on_click(|state, app| state.publish_to_figma(app))
+                .build(app)
+                .height(px(26.))
+                .width(px(70.)),
+            ],
+        ),
+    ];
+
+    popover_panel(
+        1,
+        PANEL_WIDTH,
+        column_spaced(px(6.), contents).pad(px(10.)),
+        |state| state.figma.open = false,
+        field_bg,
+        field_border,
+        app,
+    )
+}
+
+fn formats_layer<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    const PANEL_WIDTH: f32 = 360.;
+    if !s.formats_open {
+        return empty();
+    }
+    let gray = s.theme(Theme::Gray70);
+    let [r, g, b, _] = s.srgb().components;
+
+    let mut contents = vec![
+        text(id!(), "all formats")
+            .font_size(font(13))
+            .fill(label_color)
+            .build(app)
+            .height(px(20.)),
+    ];
+    contents.extend(CopyFormat::CSS.into_iter().enumerate().map(|(i, format)| {
+        let key = i as u64;
+        let code = format.format([r, g, b], s.values, s.alpha);
+        button(
+            id!(key),
+            (
+                &s.formats_rows[i],
+                Binding::new(
+                    move |s: &State| &s.formats_rows[i],
+                    move |s: &mut State| &mut s.formats_rows[i],
+                ),
+            ),
+        )
+        .surface(move |btn, ctx| {
+            rect(id!(key))
+                .fill(btn_surface_color(btn, field_bg))
+                .corner_rounding(5.)
+                .build(ctx)
+        })
+        .label(move |btn, ctx| {
+            let c = btn_label_color(btn, label_color);
+            row_spaced(
+                px(8.),
+                vec![
+                    text(id!(key), format.label())
+                        .font_size(font(12))
+                        .fill(gray)
+                        .build(ctx)
+                        .width(px(72.)),
+                    text(id!(key), &code).font_size(font(13)).fill(c).build(ctx),
+                    space().inert_y(),
+                    svg(id!(key), COPY_ICON)
+                        .fill(Brush::Solid(if btn.hovered { c } else { gray }))
+                        .finish(ctx)
+                        .width(px(12.))
+                        .height(px(12.)),
+                ],
+            )
+            .pad_x(px(6.))
+        })
+        .on_click(move |state, app| {
+            let [r, g, b, _] = state.srgb().components;
+            let text = format.format([r, g, b], state.values, state.alpha);
+            state.copy_text(text, app);
+            state.commit_color(HistorySource::Copied);
+        })
+        .build(app)
+        .height(px(24.))
+    }));
+
+    popover_panel(
+        2,
+        PANEL_WIDTH,
+        column_spaced(px(4.), contents).pad(px(10.)),
+        |state| state.formats_open = false,
+        field_bg,
+        field_border,
+        app,
+    )
+}
+
+fn blend_preview_layer<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    highlight_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    const PANEL_WIDTH: f32 = 360.;
+    if !s.blend_preview.open {
+        return empty();
+    }
+    let gray = s.theme(Theme::Gray70);
+    let option = move |key: u64,
+                       label: String,
+                       selected: bool,
+                       state: (&'a ButtonState, Binding<State, ButtonState>),
+                       on_click: Box<dyn Fn(&mut State, &mut PaneState)>,
+                       app: &mut PaneState| {
+        button(id!(key), state)
+            .surface(move |_, _ctx| space().height(0.).width(0.))
+            .label(move |btn, ctx| {
+                let c = if btn.hovered || selected {
+                    label_color
+                } else {
+                    gray
+                };
+                text(id!(key), &label)
+                    .font_size(font(13))
+                    .fill(c)
+                    .build(ctx)
+            })
+            .on_click(move |state, app| {
+                on_click(state, app);
+                app.redraw();
+            })
+            .build(app)
+            .height(px(20.))
+    };
+
+    let modes: Vec<View<'a, State>> = BlendMode::ALL
+        .into_iter()
+        .enumerate()
+        .map(|(i, mode)| {
+            option(
+                i as u64,
+                mode.label().to_string(),
+                s.blend_preview.mode == mode,
+                (
+                    &s.blend_preview.mode_buttons[i],
+                    Binding::new(
+                        move |s: &State| &s.blend_preview.mode_buttons[i],
+                        move |s: &mut State| &mut s.blend_preview.mode_buttons[i],
+                    ),
+                ),
+                Box::new(move |state, _app| state.blend_preview.mode = mode),
+                app,
+            )
+        })
+        .collect();
+    let alpha_step = move |i: usize, label: &str, step: f32, app: &mut PaneState| {
+        option(
+            10 + i as u64,
+            label.to_string(),
+            false,
+            (
+                &s.blend_preview.alpha_buttons[i],
+                Binding::new(
+                    move |s: &State| &s.blend_preview.alpha_buttons[i],
+                    move |s: &mut State| &mut s.blend_preview.alpha_buttons[i],
+                ),
+            ),
+            Box::new(move |state, _app| {
+                let alpha = (state.blend_preview.alpha + step).clamp(0.0, 1.0);
+                state.blend_preview.alpha = (alpha * 10.0).round() / 10.0;
+            }),
+            app,
+        )
+    };
+
+    let background = text_field(
+        id!(),
+        (
+            &s.blend_preview.background,
+            Binding::new(
+                |s: &State| &s.blend_preview.background,
+                |s: &mut State| &mut s.blend_preview.background,
+            ),
+        ),
+    )
+    .font_size(font(13))
+    .text_fill(label_color)
+    .cursor_fill(label_color)
+    .highlight_fill(highlight_color)
+    .singleline()
+    .enter_end_editing()
+    .esc_end_editing()
+    .background(move |_, _, ctx| {
+        rect(id!())
+            .fill(s.theme(Theme::Gray0))
+            .stroke(field_border, Stroke::new(1.))
+            .corner_rounding(6.)
+            .build(ctx)
+    })
+    .padding(px(5.))
+    .build(app)
+    .expand_x()
+    .height(px(28.));
+
+    let result = match s.blend_preview_result() {
+        Some(rgb) => {
+            let backdrop = parse_color(s.blend_preview.background.text.trim())
+                .map(|c| c.to_alpha_color::<Srgb>())
+                .unwrap_or(Color::WHITE);
+            let hex = CopyFormat::Hex.format(rgb, s.values, 1.0);
+            row_spaced(
+                px(10.),
+                vec![
+                    stack(vec![
+                        rect(id!())
+                            .fill(backdrop.with_alpha(1.0))
+                            .stroke(field_border, Stroke::new(1.))
+                            .corner_rounding(6.)
+                            .build(app),
+                        rect(id!())
+                            .fill(Color::new([rgb[0], rgb[1], rgb[2], 1.0]))
+                            .corner_rounding(4.)
+                            .build(app)
+                            .pad(px(10.)),
+                    ])
+                    .width(px(96.))
+                    .height(px(48.)),
+                    text(id!(), hex.clone())
+                        .font_size(font(13))
+                        .fill(label_color)
+                        .build(app),
+                    space().inert_y(),
+                    button(
+                        id!(),
+                        (
+                            &s.blend_preview.copy_button,
+                            Binding::new(
+                                |s: &State| &s.blend_preview.copy_button,
+                                |s: &mut State| &mut s.blend_preview.copy_button,
+                            ),
+                        ),
+                    )
+                    .surface(move |btn, ctx| {
+                        rect(id!())
+                            .fill(btn_surface_color(btn, field_bg))
+                            .stroke(field_border, Stroke::new(1.))
+                            .corner_rounding(7.)
+                            .build(ctx)
+                    })
+                    .label(move |btn, ctx| {
+                        text(id!(), "copy")
+                            .font_size(font(13))
+                            .fill(btn_label_color(btn, label_color))
+                            .build(ctx)
+                    })
+                    .on_click(move |state, app| {
+                        state.copy_text(hex.clone(), app);
+                    })
+                    .build(app)
+                    .height(px(26.))
+                    .width(px(70.)),
+                ],
+            )
+            .height(px(48.))
+        }
+        None => text(id!(), "couldn't read the background color")
+            .font_size(font(12))
+            .fill(gray)
+            .build(app)
+            .height(px(20.)),
+    };
+
+    let contents = vec![
+        text(id!(), "blend preview")
+            .font_size(font(13))
+            .fill(label_color)
+            .build(app)
+            .height(px(20.)),
+        row_spaced(
+            px(10.),
+            vec![
+                text(id!(), "over")
+                    .font_size(font(13))
+                    .fill(gray)
+                    .build(app)
+                    .height(px(20.)),
+                background,
+            ],
+        ),
+        row_spaced(px(10.), modes),
+        row_spaced(
+            px(10.),
+            vec![
+                text(id!(), "opacity")
+                    .font_size(font(13))
+                    .fill(gray)
+                    .build(app)
+                    .height(px(20.)),
+                alpha_step(0, "−", -0.1, app),
+                text(id!(), format!("{:.0}%", s.blend_preview.alpha * 100.0))
+                    .font_size(font(13))
+                    .fill(label_color)
+                    .build(app)
+                    .height(px(20.)),
+                alpha_step(1, "+", 0.1, app),
+                space().inert_y(),
+            ],
+        ),
+        result,
+    ];
+
+    popover_panel(
+        3,
+        PANEL_WIDTH,
+        column_spaced(px(6.), contents).pad(px(10.)),
+        |state| state.blend_preview.open = false,
+        field_bg,
+        field_border,
+        app,
+    )
+}
+
+fn explorer_cell(row: usize, col: usize, hue: f32) -> [f32; 3] {
+    let lightness = 1.0 - (row as f32 + 0.5) / EXPLORER_ROWS as f32;
+    let chroma = (col as f32 + 0.5) / EXPLORER_COLUMNS as f32 * OKLCH_CHANNELS[1].max;
+    [lightness, chroma, hue]
+}
+
+fn explorer_position([lightness, chroma, _]: [f32; 3]) -> (usize, usize) {
+    let row = ((1.0 - lightness) * EXPLORER_ROWS as f32) as usize;
+    let col = (chroma / OKLCH_CHANNELS[1].max * EXPLORER_COLUMNS as f32) as usize;
+    (row.min(EXPLORER_ROWS - 1), col.min(EXPLORER_COLUMNS - 1))
+}
+
+fn explorer_layer<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    const PANEL_WIDTH: f32 = 360.;
+    const CELL_WIDTH: f32 = 13.;
+    const CELL_HEIGHT: f32 = 11.;
+    if !s.explorer_open {
+        return empty();
+    }
+    let gray = s.theme(Theme::Gray70);
+    let hue = s.values[2];
+    let current = explorer_position(s.values);
+
+    let rows = (0..EXPLORER_ROWS)
+        .map(|row_index| {
+            let cells = (0..EXPLORER_COLUMNS)
+                .map(|col| {
+                    let key = (row_index * EXPLORER_COLUMNS + col) as u64;
+                    let values = explorer_cell(row_index, col, hue);
+                    let cell = if !in_srgb_gamut(values) {
+                        rect(id!(key))
+                            .fill(Color::TRANSPARENT)
+                            .corner_rounding(0.)
+                            .build(app)
+                    } else {
+                        rect(id!(key))
+                            .fill(palette_color(values))
+                            .corner_rounding(0.)
+                            .view()
+                            .gesture(gesture::click(id!(key)).button(MouseButton::Left).run(
+                                move |state: &mut State, app, event| {
+                                    if matches!(event.state, ClickPhase::Completed) {
+                                        state.set_values(values, app);
+                                        app.redraw();
+                                    }
+                                },
+                            ))
+                            .build(app)
+                    };
+                    let cell = if (row_index, col) == current {
+                        stack(vec![
+                            cell,
+                            rect(id!(key))
+                                .fill(Color::TRANSPARENT)
+                                .stroke(label_color, Stroke::new(2.))
+                                .corner_rounding(2.)
+                                .build(app),
+                        ])
+                    } else {
+                        cell
+                    };
+                    cell.width(px(CELL_WIDTH)).height(px(CELL_HEIGHT))
+                })
+                .collect();
+            row(cells)
+        })
+        .collect();
+
+    let contents = vec![
+        text(id!(), format!("lightness × chroma at hue {hue:.0}°"))
+            .font_size(font(13))
+            .fill(label_color)
+            .build(app)
+            .height(px(20.)),
+        row_spaced(
+            px(6.),
+            vec![
+                column(vec![
+                    text(id!(), "L")
+                        .font_size(font(11))
+                        .fill(gray)
+                        .build(app)
+                        .height(px(14.)),
+                    space(),
+                ])
+                .width(px(12.)),
+                stack(vec![
+                    rect(id!())
+                        .fill(Color::TRANSPARENT)
+                        .stroke(field_border, Stroke::new(1.))
+                        .corner_rounding(0.)
+                        .build(app),
+                    column(rows),
+                ])
+                .width(px(CELL_WIDTH * EXPLORER_COLUMNS as f32))
+                .height(px(CELL_HEIGHT * EXPLORER_ROWS as f32)),
+                space().inert_y(),
+            ],
+        ),
+        row(vec![
+            space().width(px(18.)).inert_y(),
+            text(id!(), "C →")
+                .font_size(font(11))
+                .fill(gray)
+                .build(app)
+                .height(px(14.)),
+            space().inert_y(),
+            text(id!(), "blank cells are outside sRGB")
+                .font_size(font(11))
+                .fill(gray)
+                .build(app)
+                .height(px(14.)),
+        ]),
+    ];
+
+    popover_panel(
+        4,
+        PANEL_WIDTH,
+        column_spaced(px(6.), contents).pad(px(10.)),
+        |state| state.explorer_open = false,
+        field_bg,
+        field_border,
+        app,
+    )
+}
+
+fn gradient_thumbnail<'a>(
+    key: u64,
+    gradient: &GradientPreset,
+    field_border: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    let key = key * GRADIENT_THUMBNAIL_SAMPLES as u64;
+    let samples = gradient
+        .samples(GRADIENT_THUMBNAIL_SAMPLES)
+        .into_iter()
+        .enumerate()
+        .map(|(i, values)| {
+            rect(id!(key + i as u64))
+                .fill(palette_color(values))
+                .corner_rounding(0.)
+                .build(app)
+        })
+        .collect();
+    stack(vec![
+        row(samples),
+        rect(id!(key))
+            .fill(Color::TRANSPARENT)
+            .stroke(field_border, Stroke::new(1.))
+            .corner_rounding(3.)
+            .build(app),
+    ])
+    .height(px(18.))
+}
+
+fn gradient_layer<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    const PANEL_WIDTH: f32 = 360.;
+    if !s.gradient_panel.open {
+        return empty();
+    }
+    let gray = s.theme(Theme::Gray70);
+    let action = move |key: u64,
+                       label: &'static str,
+                       state: (&'a ButtonState, Binding<State, ButtonState>),
+                       on_click: Box<dyn Fn(&mut State, &mut PaneState)>,
+                       app: &mut PaneState| {
+        button(id!(key), state)
+            .surface(move |_, _ctx| space().height(0.).width(0.))
+            .label(move |btn, ctx| {
+                let c = if btn.hovered { label_color } else { gray };
+                text(id!(key), label).font_size(font(13)).fill(c).build(ctx)
+            })
+            .on_click(move |state, app| {
+                on_click(state, app);
+                app.redraw();
+            })
+            .build(app)
+            .height(px(20.))
+    };
+
+    let draft = &s.gradient_panel.draft;
+    let mut draft_row = vec![if draft.is_empty() {
+        text(id!(), "add the current color as each stop")
+            .font_size(font(12))
+            .fill(gray)
+            .build(app)
+            .height(px(18.))
+    } else {
+        gradient_thumbnail(
+            0,
+            &GradientPreset {
+                stops: draft.clone(),
+            },
+            field_border,
+            app,
+        )
+    }];
+    draft_row.push(space().inert_y());
+    if draft.len() < gradient::MAX_STOPS {
+        draft_row.push(action(
+            0,
+            "+ stop",
+            (
+                &s.gradient_panel.add_button,
+                Binding::new(
+                    |s: &State| &s.gradient_panel.add_button,
+                    |s: &mut State| &mut s.gradient_panel.add_button,
+                ),
+            ),
+            Box::new(|state, _app| state.add_gradient_stop()),
+            app,
+        ));
+    }
+    if !draft.is_empty() {
+        draft_row.push(action(
+            1,
+            "clear",
+            (
+                &s.gradient_panel.clear_button,
+                Binding::new(
+                    |s: &State| &s.gradient_panel.clear_button,
+                    |s: &mut State| &mut s.gradient_panel.clear_button,
+                ),
+            ),
+            Box::new(|state, _app| state.gradient_panel.draft.clear()),
+            app,
+        ));
+    }
+    if draft.len() >= 2 {
+        draft_row.push(action(
+            2,
+            "save",
+            (
+                &s.gradient_panel.save_button,
+                Binding::new(
+                    |s: &State| &s.gradient_panel.save_button,
+                    |s: &mut State| &mut s.gradient_panel.save_button,
+                ),
+            ),
+            Box::new(|state, app| state.save_gradient(app)),
+            app,
+        ));
+    }
+
+    let mut contents = vec![
+        text(id!(), "gradients")
+            .font_size(font(13))
+            .fill(label_color)
+            .build(app)
+            .height(px(20.)),
+        row_spaced(px(10.), draft_row),
+    ];
+    if s.gradients.is_empty() {
+        contents.push(
+            text(id!(), "saved gradients show up here")
+                .font_size(font(12))
+                .fill(gray)
+                .build(app)
+                .height(px(20.)),
+        );
+    }
+    contents.extend(s.gradients.iter().enumerate().map(|(i, preset)| {
+        let key = 10 + 2 * i as u64;
+        row_spaced(
+            px(10.),
+            vec![
+                gradient_thumbnail(1 + i as u64, preset, field_border, app).expand_x(),
+                action(
+                    key,
+                    "copy css",
+                    (
+                        &s.gradient_panel.copy_buttons[i],
+                        Binding::new(
+                            move |s: &State| &s.gradient_panel.copy_buttons[i],
+                            move |s: &mut State| &mut s.gradient_panel.copy_buttons[i],
+                        ),
+                    ),
+                    Box::new(move |state, app| {
+                        if let Some(css) = state.gradients.get(i).map(GradientPreset::css) {
+                            state.copy_text(css, app);
+                        }
+                    }),
+                    app,
+                ),
+                action(
+                    key + 1,
+                    "×",
+                    (
+                        &s.gradient_panel.delete_buttons[i],
+                        Binding::new(
+                            move |s: &State| &s.gradient_panel.delete_buttons[i],
+                            move |s: &mut State| &mut s.gradient_panel.delete_buttons[i],
+                        ),
+                    ),
+                    Box::new(move |state, app| {
+                        if i < state.gradients.len() {
+                            state.gradients.remove(i);
+                            state.save_state(app);
+                        }
+                    }),
+                    app,
+                ),
+            ],
+        )
+        .height(px(20.))
+    }));
+
+    popover_panel(
+        5,
+        PANEL_WIDTH,
+        column_spaced(px(6.), contents).pad(px(10.)),
+        |state| state.gradient_panel.open = false,
+        field_bg,
+        field_border,
+        app,
+    )
+}
+
+fn swatch_layer<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    const PANEL_WIDTH: f32 = 360.;
+    if !s.swatch.open {
+        return empty();
+    }
+    let gray = s.theme(Theme::Gray70);
+    let option = move |key: u64,
+                       label: String,
+                       selected: bool,
+                       state: (&'a ButtonState, Binding<State, ButtonState>),
+                       on_click: Box<dyn Fn(&mut State, &mut PaneState)>,
+                       app: &mut PaneState| {
+        button(id!(key), state)
+            .surface(move |_, _ctx| space().height(0.).width(0.))
+            .label(move |btn, ctx| {
+                let c = if btn.hovered || selected {
+                    label_color
+                } else {
+                    gray
+                };
+                text(id!(key), &label)
+                    .font_size(font(13))
+                    .fill(c)
+                    .build(ctx)
+            })
+            .on_click(move |state, app| {
+                on_click(state, app);
+                app.redraw();
+            })
+            .build(app)
+            .height(px(20.))
+    };
+
+    let mut sizes: Vec<View<'a, State>> = SWATCH_SIZES
+        .into_iter()
+        .enumerate()
+        .map(|(i, size)| {
+            option(
+                i as u64,
+                format!("{size}px"),
+                s.swatch_size == size,
+                (
+                    &s.swatch.size_buttons[i],
+                    Binding::new(
+                        move |s: &State| &s.swatch.size_buttons[i],
+                        move |s: &mut State| &mut s.swatch.size_buttons[i],
+                    ),
+                ),
+                Box::new(move |state, _app| state.swatch_size = size),
+                app,
+            )
+        })
+        .collect();
+    sizes.push(space().inert_y());
+    sizes.push(option(
+        10,
+        if s.swatch_label {
+            "hex label: on".to_string()
+        } else {
+            "hex label: off".to_string()
+        },
+        s.swatch_label,
+        (
+            &s.swatch.label_button,
+            Binding::new(
+                |s: &State| &s.swatch.label_button,
+                |s: &mut State| &mut s.swatch.label_button,
+            ),
+        ),
+        Box::new(|state, _app| state.swatch_label = !state.swatch_label),
+        app,
+    ));
+
+    let contents = vec![
+        text(id!(), "save swatch")
+            .font_size(font(13))
+            .fill(label_color)
+            .build(app)
+            .height(px(20.)),
+        row_spaced(px(10.), sizes),
+        row(vec![
+            space().inert_y(),
+            button(
+                id!(),
+                (
+                    &s.swatch.save_button,
+                    Binding::new(
+                        |s: &State| &s.swatch.save_button,
+                        |s: &mut State| &mut s.swatch.save_button,
+                    ),
+                ),
+            )
+            .surface(move |btn, ctx| {
+                rect(id!())
+                    .fill(btn_surface_color(btn, field_bg))
+                    .stroke(field_border, Stroke::new(1.))
+                    .corner_rounding(7.)
+                    .build(ctx)
+            })
+            .label(move |btn, ctx| {
+                text(id!(), "save…")
+                    .font_size(font(13))
+                    .fill(btn_label_color(btn, label_color))
+                    .build(ctx)
+            })
+            .on_click(|state, app| {
+                state.save_state(app);
+                state.save_swatch(app);
+            })
+            .build(app)
+            .height(px(26.))
+            .width(px(70.)),
+        ]),
+    ];
+
+    popover_panel(
+        6,
+        PANEL_WIDTH,
+        column_spaced(px(6.), contents).pad(px(10.)),
+        |state| state.swatch.open = false,
+        field_bg,
+        field_border,
+        app,
+    )
+}
+
+fn picker_settings_layer<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    highlight_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    const PANEL_WIDTH: f32 = 360.;
+    if !s.picker_settings.open {
+        return empty();
+    }
+    let gray = s.theme(Theme::Gray70);
+    let caption = move |key: u64, content: &'static str, app: &mut PaneState| {
+        text(id!(key), content)
+            .font_size(font(12))
+            .fill(gray)
+            .build(app)
+            .height(px(16.))
+    };
+    let option = move |key: u64,
+                       label: String,
+                       selected: bool,
+                       state: (&'a ButtonState, Binding<State, ButtonState>),
+                       on_click: Box<dyn Fn(&mut State, &mut PaneState)>,
+                       app: &mut PaneState| {
+        button(id!(key), state)
+            .surface(move |_, _ctx| space().height(0.).width(0.))
+            .label(move |btn, ctx| {
+                let c = if btn.hovered || selected {
+                    label_color
+                } else {
+                    gray
+                };
+                text(id!(key), &label)
+                    .font_size(font(13))
+                    .fill(c)
+                    .build(ctx)
+            })
+            .on_click(move |state, app| {
+                on_click(state, app);
+                state.save_state(app);
+                app.redraw();
+            })
+            .build(app)
+            .height(px(20.))
+    };
+
+    let contents = vec![
+        text(id!(), "picker settings")
+            .font_size(font(13))
+            .fill(label_color)
+            .build(app)
+            .height(px(20.)),
+        caption(0, "shortcut to bring idle-hue forward", app),
+        text_field(
+            id!(),
+            (
+                &s.picker_settings.hotkey,
+                Binding::new(
+                    |s: &State| &s.picker_settings.hotkey,
+                    |s: &mut State| &mut s.picker_settings.hotkey,
+                ),
+            ),
+        )
+        .font_size(font(13))
+        .text_fill(label_color)
+        .cursor_fill(label_color)
+        .highlight_fill(highlight_color)
+        .singleline()
+        .enter_end_editing()
+        .esc_end_editing()
+        .on_edit(|state, app, edit| {
+            if matches!(edit, EditInteraction::End) {
+                state.set_hotkey(app);
+            }
+        })
+        .background(move |_, _, ctx| {
+            rect(id!())
+                .fill(s.theme(Theme::Gray0))
+                .stroke(field_border, Stroke::new(1.))
+                .corner_rounding(6.)
+                .build(ctx)
+        })
+        .padding(px(5.))
+        .build(app)
+        .expand_x()
+        .height(px(28.)),
+        caption(1, "e.g. CmdOrCtrl+Shift+C, or empty for none", app),
+        option(
+            0,
+            if s.hotkey_dropper {
+                "shortcut opens the dropper: on".to_string()
+            } else {
+                "shortcut opens the dropper: off".to_string()
+            },
+            s.hotkey_dropper,
+            (
+                &s.picker_settings.hotkey_dropper_button,
+                Binding::new(
+                    |s: &State| &s.picker_settings.hotkey_dropper_button,
+                    |s: &mut State| &mut s.picker_settings.hotkey_dropper_button,
+                ),
+            ),
+            Box::new(|state, _app| state.hotkey_dropper = !state.hotkey_dropper),
+            app,
+        ),
+        caption(2, "capture picked colors in", app),
+        row_spaced(
+            px(10.),
+            CaptureSpace::ALL
+                .into_iter()
+                .enumerate()
+                .map(|(i, capture)| {
+                    option(
+                        1 + i as u64,
+                        capture.label().to_string(),
+                        s.capture_space == capture,
+                        (
+                            &s.picker_settings.capture_space_buttons[i],
+                            Binding::new(
+                                move |s: &State| &s.picker_settings.capture_space_buttons[i],
+                                move |s: &mut State| {
+                                    &mut s.picker_settings.capture_space_buttons[i]
+                                },
+                            ),
+                        ),
+                        Box::new(move |state, _app| state.capture_space = capture),
+                        app,
+                    )
+                })
+                .collect(),
+        ),
+        caption(3, "P3 falls back to sRGB on sRGB-only displays", app),
+    ];
+
+    popover_panel(
+        8,
+        PANEL_WIDTH,
+        column_spaced(px(6.), contents).pad(px(10.)),
+        |state| state.picker_settings.open = false,
+        field_bg,
+        field_border,
+        app,
+    )
+}
+
+fn toast_layer<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    if s.toasts.shown().is_empty() {
+        return empty();
+    }
+    let mut toasts = vec![space()];
+    toasts.extend(s.toasts.shown().iter().map(|toast| {
+        let id = toast.id;
+        let color = match toast.kind {
+            ToastKind::Info => label_color,
+            ToastKind::Error => WARNING_COLOR,
+        };
+        stack(vec![
+            shadow(id!(id)).build(app).offset(0., 3.),
+            rect(id!(id))
+                .fill(field_bg)
+                .stroke(field_border, Stroke::new(1.))
+                .corner_rounding(6.)
+                .view()
+                .gesture(gesture::click(id!(id)).button(MouseButton::Left).run(
+                    move |state: &mut State, app, event| {
+                        if matches!(event.state, ClickPhase::Completed) {
+                            state.toasts.dismiss(id);
+                            app.redraw();
+                        }
+                    },
+                ))
+                .build(app),
+            text(id!(id), toast.message.as_str())
+                .font_size(font(12))
+                .fill(color)
+                .build(app)
+                .pad_x(px(10.)),
+        ])
+        .height(px(28.))
+    }));
+    column_spaced(px(6.), toasts)
+        .pad_x(px(20.))
+        .pad_bottom(px(44.))
+        .layer(20)
+}
+
+fn onboarding_layer<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    const PANEL_WIDTH: f32 = 360.;
+    let Some(step) = s.onboarding_step else {
+        return empty();
+    };
+    let (title, lines, top) = ONBOARDING_STEPS[step];
+    let last = step + 1 == ONBOARDING_STEPS.len();
+    let gray = s.theme(Theme::Gray70);
+    let text_button = move |key: u64,
+                            label: &'static str,
+                            state: (&'a ButtonState, Binding<State, ButtonState>),
+                            on_click: fn(&mut State, &mut PaneState),
+                            app: &mut PaneState| {
+        button(id!(key), state)
+            .surface(move |_, _ctx| space().height(0.).width(0.))
+            .label(move |btn, ctx| {
+                let c = if btn.hovered { label_color } else { gray };
+                text(id!(key), label).font_size(font(13)).fill(c).build(ctx)
+            })
+            .on_click(on_click)
+            .build(app)
+            .height(px(20.))
+    };
+
+    let mut contents = vec![
+        text(id!(), title)
+            .font_size(font(13))
+            .fill(label_color)
+            .build(app)
+            .height(px(20.)),
+    ];
+    contents.extend(lines.iter().enumerate().map(|(i, line)| {
+        text(id!(i as u64), *line)
+            .font_size(font(12))
+            .fill(label_color)
+            .build(app)
+            .height(px(16.))
+    }));
+    contents.push(row_spaced(
+        px(10.),
+        vec![
+            text(id!(), format!("{} of {}", step + 1, ONBOARDING_STEPS.len()))
+                .font_size(font(12))
+                .fill(gray)
+                .build(app)
+                .height(px(20.)),
+            space().inert_y(),
+            if last {
+                empty()
+            } else {
+                text_button(
+                    0,
+                    "skip",
+                    binding!(s.onboarding_skip_button),
+                    |state, app| state.advance_onboarding(true, app),
+                    app,
+                )
+            },
+            text_button(
+                1,
+                if last { "done" } else { "next" },
+                binding!(s.onboarding_next_button),
+                |state, app| state.advance_onboarding(false, app),
+                app,
+            ),
+        ],
+    ));
+
+    stack(vec![
+        stack(vec![
+            shadow(id!()).build(app).offset(0., 5.),
+            rect(id!())
+                .fill(field_bg)
+                .stroke(field_border, Stroke::new(1.))
+                .corner_rounding(6.)
+                .build(app),
+            column_spaced(px(6.), contents).pad(px(10.)),
+        ])
+        .width(px(PANEL_WIDTH))
+        .align(Align::TopLeading),
+    ])
+    .width(1.)
+    .height(1.)
+    .offset(px(20.), px(top))
+    .align(Align::TopLeading)
+    .layer(10)
+}
+
+fn bulk_paste_layer<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    const PANEL_WIDTH: f32 = 360.;
+    const PREVIEW_SWATCHES: usize = 12;
+    let Some(colors) = s.bulk_paste.as_ref() else {
+        return empty();
+    };
+    let gray = s.theme(Theme::Gray70);
+    let text_button = move |key: u64,
+                            label: &'static str,
+                            state: (&'a ButtonState, Binding<State, ButtonState>),
+                            on_click: fn(&mut State, &mut PaneState),
+                            app: &mut PaneState| {
+        button(id!(key), state)
+            .surface(move |_, _ctx| space().height(0.).width(0.))
+            .label(move |btn, ctx| {
+                let c = if btn.hovered { label_color } else { gray };
+                text(id!(key), label).font_size(font(13)).fill(c).build(ctx)
+            })
+            .on_click(on_click)
+            .build(app)
+            .height(px(20.))
+    };
+
+    let mut preview: Vec<View<'a, State>> = colors
+        .iter()
+        .take(PREVIEW_SWATCHES)
+        .enumerate()
+        .map(|(i, values)| {
+            rect(id!(i as u64))
+                .fill(palette_color(*values))
+                .stroke(field_border, Stroke::new(1.))
+                .corner_rounding(3.)
+                .build(app)
+                .width(px(14.))
+                .height(px(14.))
+        })
+        .collect();
+    if colors.len() > PREVIEW_SWATCHES {
+        preview.push(
+            text(id!(), format!("+{}", colors.len() - PREVIEW_SWATCHES))
+                .font_size(font(12))
+                .fill(gray)
+                .build(app),
+        );
+    }
+    preview.push(space().inert_y());
+
+    let contents = vec![
+        row_spaced(px(4.), preview).height(px(16.)),
+        row_spaced(
+            px(10.),
+            vec![
+                text(id!(), format!("fill {} empty palette cells?", colors.len()))
+                    .font_size(font(13))
+                    .fill(label_color)
+                    .build(app)
+                    .height(px(20.)),
+                space().inert_y(),
+                text_button(
+                    0,
+                    "dismiss",
+                    binding!(s.bulk_dismiss_button),
+                    |state, _app| state.bulk_paste = None,
+                    app,
+                ),
+                text_button(
+                    1,
+                    "fill",
+                    binding!(s.bulk_fill_button),
+                    |state, app| state.fill_bulk_paste(app),
+                    app,
+                ),
+            ],
+        ),
+    ];
+
+    stack(vec![
+        stack(vec![
+            shadow(id!()).build(app).offset(0., 5.),
+            rect(id!())
+                .fill(field_bg)
+                .stroke(field_border, Stroke::new(1.))
+                .corner_rounding(6.)
+                .build(app),
+            column_spaced(px(6.), contents).pad(px(10.)),
+        ])
+        .width(px(PANEL_WIDTH))
+        .align(Align::TopLeading),
+    ])
+    .width(1.)
+    .height(1.)
+    .offset(px(20.), px(60.))
+    .align(Align::TopLeading)
+    .layer(10)
+}
+
+fn history_layer<'a>(
+    s: &'a State,
+    field_bg: Color,
+    field_border: Color,
+    label_color: Color,
+    highlight_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    const PANEL_WIDTH: f32 = 360.;
+    if !s.history_open {
+        return empty();
+    }
+    let gray = s.theme(Theme::Gray70);
+    let now = history::now();
+    let query = s.history_search.text.as_str();
+    let matches: Vec<&HistoryEntry> = s
+        .history
+        .iter()
+        .rev()
+        .filter(|entry| entry.matches(query))
+        .take(HISTORY_ROWS)
+        .collect();
+
+    let header = row_spaced(
+        px(10.),
+        vec![
+            text(id!(), "history")
+                .font_size(font(13))
+                .fill(label_color)
+                .build(app)
+                .height(px(20.)),
+            space().inert_y(),
+            button(id!(), binding!(s.history_export_button))
+                .surface(move |_, _ctx| space().height(0.).width(0.))
+                .label(move |btn, ctx| {
+                    let c = if btn.hovered { label_color } else { gray };
+                    text(id!(), "export .json")
+                        .font_size(font(13))
+                        .fill(c)
+                        .build(ctx)
+                })
+                .on_click(|state, app| {
+                    let Ok(json) = serde_json::to_string_pretty(&state.history) else {
+                        return;
+                    };
+                    state.save_in_background(
+                        export::save_file(
+                            json.into_bytes(),
+                            "idle-hue-history.json",
+                            "JSON",
+                            "json",
+                        ),
+                        app,
+                    );
+                })
+                .build(app)
+                .height(px(20.)),
+        ],
+    );
+    let search = text_field(
+        id!(),
+        (
+            &s.history_search,
+            Binding::new(
+                |s: &State| &s.history_search,
+                |s: &mut State| &mut s.history_search,
+            ),
+        ),
+    )
+    .font_size(font(13))
+    .text_fill(label_color)
+    .cursor_fill(label_color)
+    .highlight_fill(highlight_color)
+    .singleline()
+    .esc_end_editing()
+    .background(move |_, _, ctx| {
+        rect(id!())
+            .fill(s.theme(Theme::Gray0))
+            .stroke(field_border, Stroke::new(1.))
+            .corner_rounding(6.)
+            .build(ctx)
+    })
+    .padding(px(5.))
+    .build(app)
+    .expand_x()
+    .height(px(28.));
+
+    let mut contents = vec![header, search];
+    if matches.is_empty() {
+        contents.push(
+            text(
+                id!(),
+                if s.history.is_empty() {
+                    "colors you pick, paste, type or copy show up here"
+                } else {
+                    "no matches"
+                },
+            )
+            .font_size(font(12))
+            .fill(gray)
+            .build(app)
+            .height(px(20.)),
+        );
+    }
+    contents.extend(matches.into_iter().enumerate().map(|(row_index, entry)| {
+        let key = row_index as u64;
+        let swatch = palette_color(entry.oklch);
+        let hex = entry.hex.clone();
+        let details = match &entry.profile {
+            Some(profile) => format!(
+                "{} on {} · {}",
+                entry.source.label(),
+                profile.chars().take(18).collect::<String>(),
+                history::age(entry.time, now)
+            ),
+            None => format!(
+                "{} · {}",
+                entry.source.label(),
+                history::age(entry.time, now)
+            ),
+        };
+        let (values, alpha) = (entry.oklch, entry.alpha);
+        button(
+            id!(key),
+            (
+                &s.history_rows[row_index],
+                Binding::new(
+                    move |s: &State| &s.history_rows[row_index],
+                    move |s: &mut State| &mut s.history_rows[row_index],
+                ),
+            ),
+        )
+        .surface(move |btn, ctx| {
+            rect(id!(key))
+                .fill(btn_surface_color(btn, field_bg))
+                .corner_rounding(5.)
+                .build(ctx)
+        })
+        .label(move |btn, ctx| {
+            row_spaced(
+                px(8.),
+                vec![
+                    rect(id!(key))
+                        .fill(swatch)
+                        .stroke(field_border, Stroke::new(1.))
+                        .corner_rounding(3.)
+                        .build(ctx)
+                        .height(px(14.))
+                        .width(px(14.)),
+                    text(id!(key), &hex)
+                        .font_size(font(13))
+                        .fill(btn_label_color(btn, label_color))
+                        .build(ctx),
+                    space().inert_y(),
+                    text(id!(key), &details)
+                        .font_size(font(12))
+                        .fill(gray)
+                        .build(ctx),
+                ],
+            )
+            .pad_x(px(6.))
+        })
+        .on_click(move |state, app| {
+            state.alpha = alpha;
+            state.set_values(values, app);
+            app.redraw();
+        })
+        .build(app)
+        .height(px(24.))
+    }));
+
+    popover_panel(
+        7,
+        PANEL_WIDTH,
+        column_spaced(px(4.), contents).pad(px(10.)),
+        |state| state.history_open = false,
+        field_bg,
+        field_border,
+        app,
+    )
+}
+
+fn channel_slider<'a>(
+    key: u64,
+    i: usize,
+    slider_state: &'a SliderState,
+    mode: ColorMode,
+    components: Components,
+    knob_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    let ch = &mode.channels()[i];
+    let range = (ch.min, ch.max);
+    #[cfg(test)]
+    let slider_id = TEST_CHANNEL_SLIDER_IDS[i];
+    #[cfg(not(test))]
+    let slider_id = id!(key);
+    let stops: Vec<Color> = (0..=16)
+        .map(|step| {
+            let t = step as f32 / 16.0;
+            let val = ch.min + t * (ch.max - ch.min);
+            let mut c = components;
+            c[i] = val;
+            palette_color(mode.to_oklch(c))
+        })
+        .collect();
+    gradient_slider(
+        slider_id,
+        key,
+        (
+            slider_state,
+            Binding::new(
+                move |s: &State| &s.sliders[i],
+                move |s: &mut State| &mut s.sliders[i],
+            ),
+        ),
+        range,
+        stops,
+        knob_color,
+        app,
+        move |state, app, val| {
+            let mut components = state.slider_components();
+            components[i] = state.drag_value(i, val, range);
+            state.set_components(components, app);
+        },
+    )
+}
+
+fn alpha_slider<'a>(s: &'a State, app: &mut PaneState) -> View<'a, State> {
+    let opaque = palette_color(s.values);
+    let stops: Vec<Color> = (0..=4)
+        .map(|step| opaque.with_alpha(step as f32 / 4.0))
+        .collect();
+    gradient_slider(
+        id!(),
+        id!(),
+        binding!(s.alpha_slider),
+        (0.0, 1.0),
+        stops,
+        s.theme_inverted(Theme::Gray0),
+        app,
+        |state, app, val| {
+            let alpha = state.drag_value(MAX_CHANNELS, val, (0.0, 1.0));
+            state.set_alpha(alpha, app)
+        },
+    )
+}
+
+fn gradient_slider<'a>(
+    slider_id: u64,
+    key: u64,
+    state: (&'a SliderState, Binding<State, SliderState>),
+    (min, max): (f32, f32),
+    stops: Vec<Color>,
+    knob_color: Color,
+    app: &mut PaneState,
+    on_change: impl Fn(&mut State, &mut PaneState, f32) + 'static,
+) -> View<'a, State> {
+    slider(slider_id, state)
+        .range(min, max)
+        .background(move |_, area, ctx| {
+            rect(id!(key))
+                .fill(
+                    Gradient::new_linear(
+                        (area.x as f64, area.y as f64),
+                        (area.x as f64 + area.width as f64, area.y as f64),
+                    )
+                    .with_stops(stops.as_slice()),
+                )
+                .corner_rounding(area.height)
+                .build(ctx)
+        })
+        .track(move |_, area, ctx| {
+            rect(id!(key))
+                .fill(Color::TRANSPARENT)
+                .corner_rounding(area.height)
+                .build(ctx)
+        })
+        .traveled_track(move |_, area, ctx| {
+            rect(id!(key))
+                .fill(Color::TRANSPARENT)
+                .corner_rounding(area.height)
+                .build(ctx)
+        })
+        .knob(move |state, _, ctx| {
+            circle(id!(key))
+                .fill(btn_label_color(
+                    ButtonState {
+                        depressed: state.dragging,
+                        hovered: state.hovered,
+                    },
+                    knob_color,
+                ))
+                .finish(ctx)
+        })
+        .on_change(on_change)
+        .build(app)
+        .height(px(26.))
+        .pad_y(px(2.))
+}
+
+fn palette_color(values: [f32; 3]) -> Color {
+    AlphaColor::<Oklch>::new([values[0], values[1], values[2], 1.0]).convert::<Srgb>()
+}
+
+fn recent_strip<'a>(s: &'a State, field_border: Color, app: &mut PaneState) -> View<'a, State> {
+    row_spaced(
+        px(PALETTE_SWATCH_GAP),
+        (0..RECENT_SIZE)
+            .map(|index| {
+                let Some(values) = s.recent.get(index).copied() else {
+                    return rect(id!(index as u64))
+                        .fill(TRANSPARENT)
+                        .stroke(field_border, Stroke::new(1.))
+                        .corner_rounding(5.)
+                        .build(app)
+                        .height(px(PALETTE_SWATCH_SIZE))
+                        .width(px(PALETTE_SWATCH_SIZE));
+                };
+                rect(id!(index as u64))
+                    .fill(palette_color(values))
+                    .corner_rounding(5.)
+                    .view()
+                    .gesture(
+                        gesture::click(id!(index as u64))
+                            .button(MouseButton::Left)
+                            .run(move |state: &mut State, app, event| {
+                                if matches!(event.state, ClickPhase::Completed) {
+                                    state.set_values(values, app);
+                                }
+                            }),
+                    )
+                    .build(app)
+                    .height(px(PALETTE_SWATCH_SIZE))
+                    .width(px(PALETTE_SWATCH_SIZE))
+            })
+            .collect(),
+    )
+}
+
+fn palette_actions<'a>(s: &'a State, label_color: Color, app: &mut PaneState) -> View<'a, State> {
+    if s.palette.labeling {
+        return row_spaced(px(10.), labeling_actions(s, label_color, app));
+    }
+    let mut actions = blend_actions(s, label_color, app);
+    if !s.palette.blending {
+        actions.extend(page_actions(s, label_color, app));
+        actions.push(drop_mode_button(s, label_color, app));
+        actions.push(grid_button(s, label_color, app));
+    }
+    actions.push(space().inert_y());
+    if s.palette.blending {
+        return row_spaced(px(10.), actions);
+    }
+
+    if let Some(report) = &s.tidy_report {
+        actions.push(
+            text(id!(), report.as_str())
+                .font_size(font(13))
+                .fill(s.theme(Theme::Gray70))
+                .build(app)
+                .height(px(20.)),
+        );
+    }
+    if s.undo.can_undo() {
+        actions.push(undo_button(s, label_color, app));
+    }
+    actions.push(tidy_menu(s, label_color, app));
+    actions.push(export_menu(s, label_color, app));
+    row_spaced(px(10.), actions)
+}
+
+fn labeling_actions<'a>(
+    s: &'a State,
+    label_color: Color,
+    app: &mut PaneState,
+) -> Vec<View<'a, State>> {
+    let gray = s.theme(Theme::Gray70);
+    vec![
+        text(id!(), "name each row, like \"primary\" or \"neutrals\"")
+            .font_size(font(13))
+            .fill(gray)
+            .build(app)
+            .height(px(20.)),
+        space().inert_y(),
+        button(id!(), binding!(s.labels_done_button))
+            .surface(move |_, _ctx| space().height(0.).width(0.))
+            .label(move |btn, ctx| {
+                let c = if btn.hovered { label_color } else { gray };
+                text(id!(), "done").font_size(font(13)).fill(c).build(ctx)
+            })
+            .on_click(|state, app| state.finish_labeling(app))
+            .build(app)
+            .height(px(20.)),
+    ]
+}
+
+fn undo_button<'a>(s: &'a State, label_color: Color, app: &mut PaneState) -> View<'a, State> {
+    let gray = s.theme(Theme::Gray70);
+    button(id!(), binding!(s.undo_button))
+        .surface(move |_, _ctx| space().height(0.).width(0.))
+        .label(move |btn, ctx| {
+            let c = if btn.hovered { label_color } else { gray };
+            text(id!(), "undo").font_size(font(13)).fill(c).build(ctx)
+        })
+        .on_click(|state, app| state.undo(app))
+        .build(app)
+        .height(px(20.))
+}
+
+fn palette_menu_entry<'a>(
+    key: u64,
+    label: &'static str,
+    state: (&'a ButtonState, Binding<State, ButtonState>),
+    field_bg: Color,
+    label_color: Color,
+    app: &mut PaneState,
+    on_click: impl Fn(&mut State, &mut PaneState) + 'static,
+) -> View<'a, State> {
+    button(id!(key), state)
+        .surface(move |btn, ctx| {
+            rect(id!(key))
+                .fill(btn_surface_color(btn, field_bg))
+                .corner_rounding(5.)
+                .build(ctx)
+        })
+        .label(move |btn, ctx| {
+            row(vec![
+                text(id!(key), label)
+                    .font_size(font(13))
+                    .fill(btn_label_color(btn, label_color))
+                    .build(ctx),
+                space().inert_y(),
+            ])
+            .pad_x(px(8.))
+        })
+        .on_click(move |state, app| {
+            state.export_menu_open = false;
+            state.tidy_menu_open = false;
+            on_click(state, app);
+        })
+        .build(app)
+        .width(px(PALETTE_MENU_WIDTH - 6.))
+        .height(px(24.))
+}
+
+fn export_menu<'a>(s: &'a State, label_color: Color, app: &mut PaneState) -> View<'a, State> {
+    let gray = s.theme(Theme::Gray70);
+    let field_bg = s.theme(Theme::Gray30);
+    let field_border = s.theme(Theme::Gray50);
+    let export_button = button(id!(), binding!(s.export_menu_button))
+        .surface(move |_, _ctx| space().height(0.).width(0.))
+        .label(move |btn, ctx| {
+            let c = if btn.hovered || s.export_menu_open {
+                label_color
+            } else {
+                gray
+            };
+            text(id!(), "export").font_size(font(13)).fill(c).build(ctx)
+        })
+        .on_click(|state, app| {
+            state.export_menu_open = !state.export_menu_open;
+            state.tidy_menu_open = false;
+            app.redraw();
+        })
+        .build(app)
+        .height(px(20.));
+    if !s.export_menu_open {
+        return export_button;
+    }
+
+    let mut entries: Vec<View<'a, State>> = PaletteExport::ALL
+        .into_iter()
+        .enumerate()
+        .map(|(i, export)| {
+            palette_menu_entry(
+                i as u64,
+                export.label(),
+                (
+                    &s.export_buttons[i],
+                    Binding::new(
+                        move |s: &State| &s.export_buttons[i],
+                        move |s: &mut State| &mut s.export_buttons[i],
+                    ),
+                ),
+                field_bg,
+                label_color,
+                app,
+                move |state, app| {
+                    let cells = state.palette.colors.clone();
+                    if !export.is_snippet() {
+                        state.save_in_background(export.save(cells), app);
+                        return;
+                    }
+                    match export.encode(&cells) {
+                        Ok(bytes) => {
+                            state.copy_text(String::from_utf8_lossy(&bytes).into_owned(), app)
+                        }
+                        Err(e) => state.toast(ToastKind::Error, e, app),
+                    }
+                },
+            )
+        })
+        .collect();
+    entries.push(palette_menu_entry(
+        100,
+        "copy link",
+        binding!(s.link_button),
+        field_bg,
+        label_color,
+        app,
+        |state, app| {
+            let link = palette_link::encode(&state.palette.colors);
+            state.copy_text(link, app);
+        },
+    ));
+    entries.push(palette_menu_entry(
+        104,
+        "design tokens",
+        binding!(s.tokens_button),
+        field_bg,
+        label_color,
+        app,
+        |state, app| {
+            let json = tokens::encode(&state.palette.colors, &state.palette.labels, state.mode);
+            state.copy_text(json, app);
+        },
+    ));
+    entries.push(palette_menu_entry(
+        101,
+        "figma…",
+        binding!(s.figma_button),
+        field_bg,
+        label_color,
+        app,
+        |state, app| {
+            state.figma.open = true;
+            state.figma.status = FigmaStatus::Idle;
+            app.redraw();
+        },
+    ));
+    entries.push(palette_menu_entry(
+        103,
+        "gradients…",
+        binding!(s.gradients_button),
+        field_bg,
+        label_color,
+        app,
+        |state, app| {
+            state.gradient_panel.open = true;
+            app.redraw();
+        },
+    ));
+    stack(vec![
+        export_button,
+        palette_menu(0, 40., entries, field_bg, field_border, app),
+    ])
+}
+
+fn tidy_menu<'a>(s: &'a State, label_color: Color, app: &mut PaneState) -> View<'a, State> {
+    let gray = s.theme(Theme::Gray70);
+    let field_bg = s.theme(Theme::Gray30);
+    let field_border = s.theme(Theme::Gray50);
+    let tidy_button = button(id!(), binding!(s.tidy_menu_button))
+        .surface(move |_, _ctx| space().height(0.).width(0.))
+        .label(move |btn, ctx| {
+            let c = if btn.hovered || s.tidy_menu_open {
+                label_color
+            } else {
+                gray
+            };
+            text(id!(), "tidy").font_size(font(13)).fill(c).build(ctx)
+        })
+        .on_click(|state, app| {
+            state.tidy_menu_open = !state.tidy_menu_open;
+            state.export_menu_open = false;
+            state.tidy_report = None;
+            app.redraw();
+        })
+        .build(app)
+        .height(px(20.));
+    if !s.tidy_menu_open {
+        return tidy_button;
+    }
+
+    let mut entries: Vec<View<'a, State>> = PaletteSort::ALL
+        .into_iter()
+        .enumerate()
+        .map(|(i, sort)| {
+            palette_menu_entry(
+                200 + i as u64,
+                sort.label(),
+                (
+                    &s.sort_buttons[i],
+                    Binding::new(
+                        move |s: &State| &s.sort_buttons[i],
+                        move |s: &mut State| &mut s.sort_buttons[i],
+                    ),
+                ),
+                field_bg,
+                label_color,
+                app,
+                move |state, app| state.sort_palette(sort, app),
+            )
+        })
+        .collect();
+    entries.push(palette_menu_entry(
+        210,
+        "merge duplicates",
+        binding!(s.merge_button),
+        field_bg,
+        label_color,
+        app,
+        |state, app| state.merge_duplicates(app),
+    ));
+    entries.push(palette_menu_entry(
+        220,
+        "label rows",
+        binding!(s.label_rows_button),
+        field_bg,
+        label_color,
+        app,
+        |state, app| {
+            state.start_labeling();
+            app.redraw();
+        },
+    ));
+
+    stack(vec![
+        tidy_button,
+        palette_menu(1, 25., entries, field_bg, field_border, app),
+    ])
+}
+
+fn palette_menu<'a>(
+    key: u64,
+    anchor_width: f32,
+    entries: Vec<View<'a, State>>,
+    field_bg: Color,
+    field_border: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    let menu_height = px(entries.len() as f32 * 26. + 4.);
+
+    let close_outside = gesture::click(id!(key))
+        .anywhere()
+        .button(MouseButton::Left)
+        .observe()
+        .run(|state: &mut State, _app, event| {
+            if matches!(event.state, ClickPhase::Completed) {
+                state.export_menu_open = false;
+                state.tidy_menu_open = false;
+            }
+        });
+
+    stack(vec![
+        stack(vec![
+            shadow(id!(key)).build(app).offset(0., 5.),
+            rect(id!(key))
+                .fill(field_bg)
+                .stroke(field_border, Stroke::new(1.))
+                .corner_rounding(6.)
+                .view()
+                .occlude(&close_outside)
+                .build(app),
+            column_spaced(px(2.), entries).pad(px(3.)),
+        ])
+        .width(px(PALETTE_MENU_WIDTH))
+        .align(Align::TopLeading),
+    ])
+    .width(1.)
+    .height(1.)
+    .offset(px(anchor_width - PALETTE_MENU_WIDTH), -menu_height - px(6.))
+    .align(Align::TopLeading)
+    .layer(10)
+}
+
+fn drop_mode_button<'a>(s: &'a State, label_color: Color, app: &mut PaneState) -> View<'a, State> {
+    let gray = s.theme(Theme::Gray70);
+    let label_text = if s.palette.insert_on_drop {
+        "insert"
+    } else {
+        "swap"
+    };
+    button(id!(), binding!(s.drop_mode_button))
+        .surface(move |_, _ctx| space().height(0.).width(0.))
+        .label(move |btn, ctx| {
+            let c = if btn.hovered { label_color } else { gray };
+            text(id!(), label_text)
+                .font_size(font(13))
+                .fill(c)
+                .build(ctx)
+        })
+        .on_click(|state, app| {
+            state.palette.insert_on_drop = !state.palette.insert_on_drop;
+            state.save_state(app);
+        })
+        .build(app)
+        .height(px(20.))
 }
 
-fn palette_color(values: [f32; 3]) -> Color {
-    AlphaColor::<Oklch>::new([values[0], values[1], values[2], 1.0]).convert::<Srgb>()
+fn page_actions<'a>(s: &'a State, label_color: Color, app: &mut PaneState) -> Vec<View<'a, State>> {
+    let gray = s.theme(Theme::Gray70);
+    let page_button = move |i: usize, label: &'static str, app: &mut PaneState| {
+        button(
+            id!(i as u64),
+            (
+                &s.page_buttons[i],
+                Binding::new(
+                    move |s: &State| &s.page_buttons[i],
+                    move |s: &mut State| &mut s.page_buttons[i],
+                ),
+            ),
+        )
+        .surface(move |_, _ctx| space().height(0.).width(0.))
+        .label(move |btn, ctx| {
+            let c = if btn.hovered { label_color } else { gray };
+            text(id!(i as u64), label)
+                .font_size(font(13))
+                .fill(c)
+                .build(ctx)
+        })
+        .on_click(move |state, _app| state.palette.turn_page(i == 1))
+        .build(app)
+        .height(px(20.))
+    };
+    vec![
+        page_button(0, "‹", app),
+        text(
+            id!(),
+            format!("{}/{}", s.palette.page + 1, s.palette.page_count()),
+        )
+        .font_size(font(13))
+        .fill(gray)
+        .build(app)
+        .height(px(20.)),
+        page_button(1, "›", app),
+    ]
+}
+
+fn blend_actions<'a>(
+    s: &'a State,
+    label_color: Color,
+    app: &mut PaneState,
+) -> Vec<View<'a, State>> {
+    let gray = s.theme(Theme::Gray70);
+    let has_ends = s.palette.blend_ends.len() == 2;
+    let mut actions = Vec::new();
+    for (i, action) in BlendAction::ALL.into_iter().enumerate() {
+        if action != BlendAction::Toggle && !has_ends {
+            continue;
+        }
+        let label = match action {
+            BlendAction::Toggle if s.palette.blending => "done",
+            BlendAction::Toggle => "blend",
+            BlendAction::Fewer => "−",
+            BlendAction::More => "+",
+            BlendAction::Fill => "fill",
+            BlendAction::Copy => "copy",
+        };
+        actions.push(
+            button(
+                id!(i as u64),
+                (
+                    &s.blend_buttons[i],
+                    Binding::new(
+                        move |s: &State| &s.blend_buttons[i],
+                        move |s: &mut State| &mut s.blend_buttons[i],
+                    ),
+                ),
+            )
+            .surface(move |_, _ctx| space().height(0.).width(0.))
+            .label(move |btn, ctx| {
+                let c = if btn.hovered { label_color } else { gray };
+                text(id!(i as u64), label)
+                    .font_size(font(13))
+                    .fill(c)
+                    .build(ctx)
+            })
+            .on_click(move |state, app| {
+                let palette = &mut state.palette;
+                match action {
+                    BlendAction::Toggle => {
+                        palette.blending = !palette.blending;
+                        palette.blend_ends.clear();
+                    }
+                    BlendAction::Fewer => {
+                        palette.blend_steps = palette.blend_steps.saturating_sub(1).max(1);
+                    }
+                    BlendAction::More => {
+                        palette.blend_steps = (palette.blend_steps + 1).min(PALETTE_SIZE - 2);
+                    }
+                    BlendAction::Fill => {
+                        if palette.blend_scale().is_some() {
+                            state.record_undo();
+                            state.palette.fill_blend();
+                            state.save_state(app);
+                        }
+                    }
+                    BlendAction::Copy => {
+                        if let Some(scale) = palette.blend_scale() {
+                            let list = scale
+                                .into_iter()
+                                .map(|values| {
+                                    let c = palette_color(values).to_rgba8();
+                                    format!("#{:02X}{:02X}{:02X}", c.r, c.g, c.b)
+                                })
+                                .collect::<Vec<_>>()
+                                .join("\n");
+                            state.copy_text(list, app);
+                        }
+                    }
+                }
+            })
+            .build(app)
+            .height(px(20.)),
+        );
+        if action == BlendAction::Fewer {
+            actions.push(
+                text(id!(), format!("{} steps", s.palette.blend_steps))
+                    .font_size(font(13))
+                    .fill(label_color)
+                    .build(app)
+                    .height(px(20.)),
+            );
+        }
+    }
+    if s.palette.blending && !has_ends {
+        actions.push(
+            text(id!(), "pick two swatches")
+                .font_size(font(13))
+                .fill(gray)
+                .build(app)
+                .height(px(20.)),
+        );
+    }
+    actions
 }
 
-fn palette_grid<'a>(s: &'a State, app: &mut PaneState) -> View<'a, State> {
+fn palette_grid<'a>(
+    s: &'a State,
+    label_color: Color,
+    highlight_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
     let rows = (0..PALETTE_HEIGHT)
         .map(|row| {
             let cols = (0..PALETTE_WIDTH)
                 .map(|col| {
-                    let index = row * PALETTE_WIDTH + col;
+                    let slot = row * PALETTE_WIDTH + col;
+                    let index = s.palette.page * PALETTE_SIZE + slot;
                     let swatch_color = s.palette.colors[index].map(palette_color);
                     let is_dragging_this = s.palette.dragging == Some(index);
                     let is_dragging = s.palette.dragging.is_some();
@@ -1210,23 +5799,52 @@ fn palette_grid<'a>(s: &'a State, app: &mut PaneState) -> View<'a, State> {
                         palette_sensor(index, app),
                         svg(id!(index as u64), PLUS_ICON)
                             .fill(
-                                if swatch_color.is_none() && s.palette.hover[index] && !is_dragging
-                                {
+                                if swatch_color.is_none() && s.palette.hover[slot] && !is_dragging {
                                     s.theme_inverted(Theme::Gray0)
                                 } else {
                                     TRANSPARENT
                                 },
                             )
                             .finish(app)
-                            .height(15.)
-                            .width(15.),
+                            .height(px(15.))
+                            .width(px(15.)),
+                        match s.palette.colors[index] {
+                            Some(values) if s.palette.hover[slot] && !is_dragging => {
+                                palette_tooltip(s, index, values, col, app)
+                            }
+                            _ => empty(),
+                        },
                     ])
-                    .height(PALETTE_SWATCH_SIZE)
-                    .width(PALETTE_SWATCH_SIZE)
+                    .height(px(PALETTE_SWATCH_SIZE))
+                    .width(px(PALETTE_SWATCH_SIZE))
                 })
                 .collect::<Vec<_>>();
 
-            row_spaced(PALETTE_SWATCH_GAP, cols)
+            let swatches = row_spaced(px(PALETTE_SWATCH_GAP), cols);
+            let label = s.palette.row_label(row);
+            if s.palette.labeling {
+                column_spaced(
+                    px(2.),
+                    vec![
+                        row_label_field(s, row, label_color, highlight_color, app),
+                        swatches,
+                    ],
+                )
+            } else if !label.is_empty() {
+                column_spaced(
+                    px(2.),
+                    vec![
+                        text(id!(row as u64), label)
+                            .font_size(font(10))
+                            .fill(s.theme(Theme::Gray70))
+                            .build(app)
+                            .height(px(12.)),
+                        swatches,
+                    ],
+                )
+            } else {
+                swatches
+            }
         })
         .collect::<Vec<_>>();
 
@@ -1247,8 +5865,110 @@ fn palette_grid<'a>(s: &'a State, app: &mut PaneState) -> View<'a, State> {
             ))
             .build(app)
             .inert(),
-        column_spaced(PALETTE_SWATCH_GAP, rows),
+        column_spaced(px(PALETTE_SWATCH_GAP), rows),
+    ])
+}
+
+fn row_label_field<'a>(
+    s: &'a State,
+    row: usize,
+    label_color: Color,
+    highlight_color: Color,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    text_field(
+        id!(row as u64),
+        (
+            &s.label_fields[row],
+            Binding::new(
+                move |s: &State| &s.label_fields[row],
+                move |s: &mut State| &mut s.label_fields[row],
+            ),
+        ),
+    )
+    .font_size(font(11))
+    .text_fill(label_color)
+    .cursor_fill(label_color)
+    .highlight_fill(highlight_color)
+    .singleline()
+    .enter_end_editing()
+    .esc_end_editing()
+    .on_edit(move |state, app, edit| {
+        if let EditInteraction::End = edit {
+            let label = state.label_fields[row].text.clone();
+            state.palette.set_row_label(row, &label);
+            state.save_state(app);
+        }
+    })
+    .background(move |_, _, ctx| {
+        rect(id!(row as u64))
+            .fill(s.theme(Theme::Gray0))
+            .stroke(s.theme(Theme::Gray50), Stroke::new(1.))
+            .corner_rounding(4.)
+            .build(ctx)
+    })
+    .padding(px(2.))
+    .build(app)
+    .expand_x()
+    .height(px(18.))
+}
+
+fn palette_tooltip<'a>(
+    s: &'a State,
+    index: usize,
+    values: [f32; 3],
+    col: usize,
+    app: &mut PaneState,
+) -> View<'a, State> {
+    const TOOLTIP_WIDTH: f32 = 170.;
+    let rgb = s.gamut_mapping.to_srgb(values);
+    let [bg_r, bg_g, bg_b, _] = s.theme(Theme::Gray0).components;
+    let lines = [
+        ColorMode::Oklch.format(values, 1.0),
+        CopyFormat::Hex.format(rgb, values, 1.0),
+        format!(
+            "{} {} on background",
+            s.contrast.label(),
+            s.contrast.format(rgb, [bg_r, bg_g, bg_b])
+        ),
+    ];
+    let label_color = s.theme_inverted(Theme::Gray0);
+    let texts = lines
+        .into_iter()
+        .enumerate()
+        .map(|(line, content)| {
+            text(id!(index as u64 * 3 + line as u64), content)
+                .font_size(font(12))
+                .fill(label_color)
+                .build(app)
+        })
+        .collect();
+    // Cells on the right half open leftward so the tooltip stays inside the window.
+    let x = if col < PALETTE_WIDTH / 2 {
+        0.
+    } else {
+        px(PALETTE_SWATCH_SIZE - TOOLTIP_WIDTH)
+    };
+
+    stack(vec![
+        stack(vec![
+            shadow(id!(index as u64)).build(app).offset(0., 5.),
+            rect(id!(index as u64))
+                .fill(s.theme(Theme::Gray30))
+                .stroke(s.theme(Theme::Gray50), Stroke::new(1.))
+                .corner_rounding(6.)
+                .build(app),
+            column_spaced(px(2.), texts).pad(px(6.)),
+        ])
+        .width(px(TOOLTIP_WIDTH))
+        .align(Align::TopLeading),
     ])
+    .width(1.)
+    .height(1.)
+    .offset(x, px(PALETTE_SWATCH_SIZE + 4.))
+    .align(Align::TopLeading)
+    .inert()
+    .layer(10)
 }
 
 fn palette_swatch<'a>(
@@ -1260,18 +5980,19 @@ fn palette_swatch<'a>(
     s: &'a State,
     app: &mut PaneState,
 ) -> View<'a, State> {
+    let is_blend_end = s.palette.blending && s.palette.blend_ends.contains(&index);
     let swatch = stack(vec![
         rect(id!(index as u64))
             .fill(swatch_color.unwrap_or(s.theme(Theme::Gray30)))
             .stroke(
-                if is_drag_target {
+                if is_drag_target || is_blend_end {
                     s.theme_inverted(Theme::Gray0)
                 } else {
                     s.theme(Theme::Gray50)
                 },
                 Stroke::new(if is_dragging {
                     3.
-                } else if is_drag_target {
+                } else if is_drag_target || is_blend_end {
                     2.
                 } else {
                     1.
@@ -1297,8 +6018,8 @@ fn palette_swatch<'a>(
                 })
                 .finish(app),
         ])
-        .height(15.)
-        .width(15.)
+        .height(px(15.))
+        .width(px(15.))
         .inert(),
     ])
     .offset(
@@ -1323,7 +6044,7 @@ fn palette_sensor(index: usize, app: &mut PaneState) -> View<'static, State> {
         .view()
         .gesture(gesture::hover(id!(id, 0_u64)).observe().run(
             move |state: &mut State, _app, hovered| {
-                state.palette.hover[index] = hovered;
+                state.palette.hover[index % PALETTE_SIZE] = hovered;
                 if state.palette.dragging.is_some() {
                     if hovered {
                         state.palette.drag_target = PaletteDragTarget::Swatch(index);
@@ -1338,9 +6059,19 @@ fn palette_sensor(index: usize, app: &mut PaneState) -> View<'static, State> {
                 .button(MouseButton::Left)
                 .run(move |state: &mut State, app, event| {
                     if matches!(event.state, ClickPhase::Completed) {
+                        if state.palette.blending {
+                            state.palette.toggle_blend_end(index);
+                            return;
+                        }
                         if let Some(palette_values) = state.palette.colors[index] {
+                            if palette_values != state.values {
+                                state.record_undo();
+                            }
+                            state.remember_recent();
                             state.set_values(palette_values, app);
+                            state.undo_color = (state.values, state.alpha);
                         } else {
+                            state.record_undo();
                             state.palette.colors[index] = Some(state.values);
                         }
                         state.save_state(app);
@@ -1364,21 +6095,22 @@ fn palette_sensor(index: usize, app: &mut PaneState) -> View<'static, State> {
                 }
                 DragPhase::Completed { .. } => {
                     if let Some(dragging_index) = state.palette.dragging {
+                        let before = state.undo_snapshot();
                         let changed = match state.palette.drag_target {
-                            PaletteDragTarget::Swatch(target_index) => {
-                                let dragging_color = state.palette.colors[dragging_index];
-                                let target_color = state.palette.colors[target_index];
-                                state.palette.colors[dragging_index] = target_color;
-                                state.palette.colors[target_index] = dragging_color;
+                            PaletteDragTarget::Swatch(target_index)
+                                if target_index != dragging_index =>
+                            {
+                                state.palette.move_swatch(dragging_index, target_index);
                                 true
                             }
                             PaletteDragTarget::Delete => {
                                 state.palette.colors[dragging_index] = None;
                                 true
                             }
-                            PaletteDragTarget::None => false,
+                            PaletteDragTarget::Swatch(_) | PaletteDragTarget::None => false,
                         };
                         if changed {
+                            state.record_undo_snapshot(before);
                             state.save_state(app);
                         }
                         state.palette.dragging = None;
@@ -1468,6 +6200,469 @@ mod tests {
         assert!(state.format_fields[1].editing);
     }
 
+    #[test]
+    fn recent_colors_are_distinct_and_capped() {
+        let mut state = State::default();
+
+        for hue in 0..RECENT_SIZE + 2 {
+            state.values = [0.7, 0.1, hue as f32 * 20.0];
+            state.remember_recent();
+        }
+        assert_eq!(state.recent.len(), RECENT_SIZE);
+
+        let oldest_kept = state.recent[RECENT_SIZE - 1];
+        state.values = oldest_kept;
+        state.remember_recent();
+        assert_eq!(state.recent.len(), RECENT_SIZE);
+        assert_eq!(state.recent[0], oldest_kept);
+    }
+
+    #[test]
+    fn blend_fills_empty_cells_after_the_first_end() {
+        let mut palette = PaletteState::default();
+        palette.colors[0] = Some([0.2, 0.1, 0.0]);
+        palette.colors[1] = Some([0.9, 0.0, 0.0]);
+        palette.colors[4] = Some([0.8, 0.1, 0.0]);
+        palette.blending = true;
+        palette.blend_steps = 3;
+
+        palette.toggle_blend_end(2);
+        assert!(palette.blend_ends.is_empty());
+        palette.toggle_blend_end(0);
+        palette.toggle_blend_end(4);
+        palette.fill_blend();
+
+        assert_eq!(palette.colors[1], Some([0.9, 0.0, 0.0]));
+        let filled = [2, 3, 5].map(|i| palette.colors[i].unwrap()[0]);
+        for (l, want) in filled.iter().zip([0.35, 0.5, 0.65]) {
+            assert!((l - want).abs() < 1e-4);
+        }
+        assert_eq!(palette.colors[6], None);
+    }
+
+    #[test]
+    fn palette_drop_swaps_or_inserts() {
+        let mut palette = PaletteState::default();
+        for i in 0..4 {
+            palette.colors[i] = Some([i as f32 / 10.0, 0.0, 0.0]);
+        }
+        let lightness = |p: &PaletteState| {
+            p.colors[..4]
+                .iter()
+                .map(|c| c.unwrap()[0])
+                .collect::<Vec<_>>()
+        };
+
+        palette.move_swatch(0, 2);
+        assert_eq!(lightness(&palette), [0.2, 0.1, 0.0, 0.3]);
+
+        palette.insert_on_drop = true;
+        palette.move_swatch(2, 0);
+        assert_eq!(lightness(&palette), [0.0, 0.2, 0.1, 0.3]);
+        palette.move_swatch(0, 3);
+        assert_eq!(lightness(&palette), [0.2, 0.1, 0.3, 0.0]);
+        assert_eq!(palette.colors.len(), PALETTE_SIZE);
+    }
+
+    #[test]
+    fn palette_pages_grow_and_trim() {
+        let mut palette = PaletteState::default();
+
+        palette.turn_page(true);
+        assert_eq!((palette.page, palette.page_count()), (0, 1));
+
+        palette.colors[0] = Some([0.5, 0.1, 0.0]);
+        palette.turn_page(true);
+        assert_eq!((palette.page, palette.page_count()), (1, 2));
+
+        palette.turn_page(false);
+        assert_eq!((palette.page, palette.page_count()), (0, 1));
+
+        palette.set_colors(vec![Some([0.5, 0.1, 0.0]); PALETTE_SIZE + 1]);
+        assert_eq!(palette.colors.len(), PALETTE_SIZE * 2);
+        assert_eq!(palette.colors[PALETTE_SIZE + 1], None);
+    }
+
+    #[test]
+    fn imported_palettes_start_a_new_page() {
+        let mut palette = PaletteState::default();
+        palette.import(&[Some([0.5, 0.1, 0.0]); 2]);
+        assert_eq!((palette.page, palette.page_count()), (0, 1));
+
+        palette.import(&[Some([0.7, 0.1, 90.0]); PALETTE_SIZE + 1]);
+        assert_eq!((palette.page, palette.page_count()), (1, 3));
+        assert_eq!(palette.colors[PALETTE_SIZE], Some([0.7, 0.1, 90.0]));
+        assert_eq!(palette.colors[2 * PALETTE_SIZE + 1], None);
+    }
+
+    #[test]
+    fn contrast_grid_covers_every_page() {
+        let mut state = State::default();
+        state.palette.import(&[Some([0.5, 0.1, 0.0]); 2]);
+        state
+            .palette
+            .import(&[Some([0.7, 0.1, 90.0]); PALETTE_SIZE + 1]);
+        assert_eq!(state.palette.page, 1);
+        assert_eq!(state.contrast_grid_colors().len(), PALETTE_SIZE + 3);
+    }
+
+    #[test]
+    fn pasted_color_lists_fill_empty_cells() {
+        let colors = parse_color_list("#ff0000, rgb(0, 255, 0); blue").unwrap();
+        assert_eq!(colors.len(), 3);
+        assert_eq!(
+            parse_color_list(":root {\n  --a: #fff;\n  --b: oklch(0.5 0.1 200);\n}")
+                .map(|colors| colors.len()),
+            Some(2)
+        );
+        assert_eq!(parse_color_list("rgb(0, 255, 0)"), None);
+        assert_eq!(parse_color_list("red green nope"), None);
+
+        let mut palette = PaletteState::default();
+        palette.colors[0] = Some([0.5, 0.1, 0.0]);
+        palette.colors[PALETTE_SIZE - 1] = Some([0.5, 0.1, 0.0]);
+        palette.fill_empty(&vec![[0.7, 0.1, 90.0]; PALETTE_SIZE - 1]);
+        assert_eq!(palette.page_count(), 2);
+        assert_eq!(palette.colors[1], Some([0.7, 0.1, 90.0]));
+        assert_eq!(palette.colors[PALETTE_SIZE - 1], Some([0.5, 0.1, 0.0]));
+        assert_eq!(palette.colors[PALETTE_SIZE], Some([0.7, 0.1, 90.0]));
+        assert_eq!(palette.colors[PALETTE_SIZE + 1], None);
+    }
+
+    #[test]
+    fn sorting_packs_the_palette() {
+        let gray = [0.9, 0.0, 0.0];
+        let red = [0.6, 0.2, 30.0];
+        let blue = [0.4, 0.1, 260.0];
+        let dark_gray = [0.2, 0.01, 120.0];
+        let mut palette = PaletteState::default();
+        palette.colors[3] = Some(blue);
+        palette.colors[5] = Some(gray);
+        palette.colors[9] = Some(dark_gray);
+        palette.colors.extend([None; PALETTE_SIZE]);
+        palette.colors[PALETTE_SIZE + 2] = Some(red);
+        palette.page = 1;
+
+        palette.sort(PaletteSort::Hue);
+        assert_eq!(palette.page_count(), 1);
+        assert_eq!(palette.page, 0);
+        assert_eq!(
+            palette.colors[..5],
+            [Some(red), Some(blue), Some(gray), Some(dark_gray), None]
+        );
+
+        palette.sort(PaletteSort::Lightness);
+        assert_eq!(
+            palette.colors[..4],
+            [Some(gray), Some(red), Some(blue), Some(dark_gray)]
+        );
+
+        palette.sort(PaletteSort::Chroma);
+        assert_eq!(palette.colors[..2], [Some(red), Some(blue)]);
+    }
+
+    #[test]
+    fn near_duplicates_merge_into_the_first() {
+        let mut palette = PaletteState::default();
+        palette.colors[0] = Some([0.6, 0.1, 200.0]);
+        palette.colors[1] = Some([0.601, 0.1, 200.5]);
+        palette.colors[2] = Some([0.65, 0.1, 200.0]);
+        palette.colors.extend([None; PALETTE_SIZE]);
+        palette.colors[PALETTE_SIZE] = Some([0.6, 0.1, 200.0]);
+
+        assert_eq!(palette.merge_duplicates(), 2);
+        assert_eq!(palette.colors, {
+            let mut colors = vec![None; PALETTE_SIZE];
+            colors[0] = Some([0.6, 0.1, 200.0]);
+            colors[2] = Some([0.65, 0.1, 200.0]);
+            colors
+        });
+        assert_eq!(palette.merge_duplicates(), 0);
+    }
+
+    #[test]
+    fn color_commits_and_palette_changes_share_the_undo_stack() {
+        let mut state = State::default();
+        let start = state.undo_snapshot();
+
+        state.values = [0.5, 0.1, 30.0];
+        state.commit_color(HistorySource::Random);
+        state.commit_color(HistorySource::Copied);
+        state.record_undo();
+        state.palette.colors[0] = Some(state.values);
+        let before_delete = state.undo_snapshot();
+        state.record_undo();
+        state.palette.colors[0] = None;
+
+        assert_eq!(state.undo.undo(), Some(before_delete));
+        assert_eq!(
+            state.undo.undo().map(|snapshot| snapshot.values),
+            Some([0.5, 0.1, 30.0])
+        );
+        assert_eq!(state.undo.undo(), Some(start));
+        assert!(!state.undo.can_undo());
+    }
+
+    #[test]
+    fn blend_preview_flattens_onto_the_background() {
+        let mut state = State {
+            values: [0.6, 0.15, 30.0],
+            ..Default::default()
+        };
+        let rgb = state
+            .gamut_mapping
+            .to_srgb(state.values)
+            .map(|c| c.clamp(0.0, 1.0));
+
+        state.blend_preview.mode = BlendMode::Multiply;
+        assert_eq!(state.blend_preview_result(), Some(rgb));
+
+        state.blend_preview.alpha = 0.0;
+        state.blend_preview.background = TextState::new("black");
+        assert_eq!(state.blend_preview_result(), Some([0.0; 3]));
+
+        state.blend_preview.background = TextState::new("not a color");
+        assert_eq!(state.blend_preview_result(), None);
+    }
+
+    #[test]
+    fn row_labels_follow_the_page_and_drop_trailing_blanks() {
+        let mut palette = PaletteState::default();
+        palette.set_colors(vec![None; PALETTE_SIZE * 2]);
+        palette.page = 1;
+        palette.set_row_label(0, "  Neutrals ");
+        assert_eq!(palette.labels.len(), PALETTE_HEIGHT + 1);
+        assert_eq!(palette.row_label(0), "Neutrals");
+
+        palette.page = 0;
+        palette.set_row_label(2, "Primary");
+        assert_eq!(palette.row_label(2), "Primary");
+        assert_eq!(palette.row_label(0), "");
+
+        palette.page = 1;
+        palette.set_row_label(0, "");
+        assert_eq!(palette.labels.len(), PALETTE_HEIGHT);
+    }
+
+    #[test]
+    fn explorer_cells_cover_the_plane_and_find_the_current_color() {
+        let top_left = explorer_cell(0, 0, 120.0);
+        assert!(top_left[0] > 0.95 && top_left[1] < 0.01 && top_left[2] == 120.0);
+        let bottom_right = explorer_cell(EXPLORER_ROWS - 1, EXPLORER_COLUMNS - 1, 120.0);
+        assert!(bottom_right[0] < 0.05 && bottom_right[1] > 0.39);
+        assert!(!in_srgb_gamut(bottom_right));
+
+        for (row, col) in [(0, 0), (5, 7), (EXPLORER_ROWS - 1, EXPLORER_COLUMNS - 1)] {
+            assert_eq!(explorer_position(explorer_cell(row, col, 30.0)), (row, col));
+        }
+        assert_eq!(
+            explorer_position([0.0, 1.0, 0.0]),
+            (EXPLORER_ROWS - 1, EXPLORER_COLUMNS - 1)
+        );
+    }
+
+    #[test]
+    fn pinned_reference_stays_put_while_the_color_changes() {
+        let mut state = State {
+            values: [0.6, 0.1, 200.0],
+            alpha: 0.8,
+            ..Default::default()
+        };
+        state.toggle_pin();
+        state.values = [0.4, 0.2, 20.0];
+        assert_eq!(state.pinned, Some(([0.6, 0.1, 200.0], 0.8)));
+
+        state.toggle_pin();
+        assert_eq!(state.pinned, None);
+    }
+
+    #[tokio::test]
+    async fn update_restarts_hand_over_the_new_version() {
+        let path =
+            std::env::temp_dir().join(format!("idle-hue-restart-{}.json", std::process::id()));
+        let state = State {
+            values: [0.7, 0.12, 40.0],
+            ..Default::default()
+        };
+        let version = Version::new(1, 0, 0);
+        let read = || -> SavedState {
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap()
+        };
+
+        let restart = async { Ok::<(), String>(()) };
+        restart_into_update(&path, state.saved_state(), version.clone(), restart)
+            .await
+            .unwrap();
+        assert_eq!(read().updated_to, Some(version.clone()));
+        assert_eq!(read().values, [0.7, 0.12, 40.0]);
+
+        let restart = async { Err::<(), _>("no restart".to_string()) };
+        assert!(
+            restart_into_update(&path, state.saved_state(), version, restart)
+                .await
+                .is_err()
+        );
+        assert_eq!(read().updated_to, None);
+        assert_eq!(read().values, [0.7, 0.12, 40.0]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn skipped_and_deferred_updates_stay_quiet() {
+        let version = Version::new(2, 0, 0);
+        let mut state = State {
+            update_status: UpdateStatus::Available {
+                version: version.clone(),
+                elevate: false,
+            },
+            ..Default::default()
+        };
+        state.skip_update();
+        assert_eq!(state.skipped_update, Some(version));
+        assert_eq!(state.update_status, UpdateStatus::Idle);
+
+        let now = history::now();
+        assert!(state.background_update_check_due(now));
+        state.update_status = UpdateStatus::Available {
+            version: Version::new(2, 1, 0),
+            elevate: true,
+        };
+        state.remind_later();
+        assert!(!state.background_update_check_due(now));
+        let deferral = UPDATE_REMIND_DAYS * 24 * 60 * 60;
+        assert!(state.background_update_check_due(now + deferral + 60));
+    }
+
+    #[test]
+    fn slider_drags_follow_the_chosen_sensitivity() {
+        let mut state = State {
+            drag_sensitivity: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(state.drag_value(1, 0.2, (0.0, 0.4)), 0.2);
+        assert!((state.drag_value(1, 0.4, (0.0, 0.4)) - 0.3).abs() < 1e-6);
+
+        state.slider_drag = None;
+        assert_eq!(state.drag_value(MAX_CHANNELS, 0.9, (0.0, 1.0)), 0.9);
+        assert_eq!(
+            state.slider_drag.map(|drag| drag.slider),
+            Some(MAX_CHANNELS)
+        );
+    }
+
+    #[test]
+    fn gradient_drafts_cap_their_stops_and_presets_persist() {
+        let mut state = State::default();
+        for i in 0..gradient::MAX_STOPS + 2 {
+            state.values = [0.6, 0.1, i as f32 * 30.0];
+            state.add_gradient_stop();
+        }
+        assert_eq!(state.gradient_panel.draft.len(), gradient::MAX_STOPS);
+
+        state.gradients.push(GradientPreset {
+            stops: state.gradient_panel.draft.clone(),
+        });
+        let json = serde_json::to_string(&state.saved_state()).unwrap();
+        let saved: SavedState = serde_json::from_str(&json).unwrap();
+        assert_eq!(saved.gradients, state.gradients);
+
+        let old: SavedState =
+            serde_json::from_str(r#"{"values":[0.5,0.1,30.0],"dark_mode":true,"palette":[]}"#)
+                .unwrap();
+        assert!(old.gradients.is_empty());
+    }
+
+    #[test]
+    fn figma_tokens_stay_out_of_saved_state() {
+        let mut state = State::default();
+        state.figma.token = TextState::new("figd_secret");
+        let json = serde_json::to_string(&state.saved_state()).unwrap();
+        assert!(!json.contains("figd_secret"));
+        assert!(!json.contains("figma_token"));
+
+        let old: SavedState = serde_json::from_str(
+            r#"{"values":[0.5,0.1,30.0],"dark_mode":true,"palette":[],"figma_token":"figd_old"}"#,
+        )
+        .unwrap();
+        assert_eq!(old.figma_token, "figd_old");
+    }
+
+    #[test]
+    fn onboarding_only_shows_on_a_first_launch() {
+        let old: SavedState =
+            serde_json::from_str(r#"{"values":[0.5,0.1,30.0],"dark_mode":true,"palette":[]}"#)
+                .unwrap();
+        assert!(old.onboarding_seen);
+        assert!(!State::default().saved_state().onboarding_seen);
+    }
+
+    #[test]
+    fn randomize_keeps_locked_channels() {
+        let mut state = State::default();
+        let before = state.slider_components();
+
+        state.locks[0] = true;
+        state.locks[1] = true;
+        for _ in 0..10 {
+            let after = state.random_components();
+            assert_eq!(after[..2], before[..2]);
+            assert!(in_srgb_gamut(state.mode.to_oklch(after)));
+        }
+    }
+
+    #[test]
+    fn system_theme_follows_detected_appearance() {
+        let mut state = State {
+            theme: ThemePreference::System,
+            system_dark: Some(false),
+            ..Default::default()
+        };
+        state.apply_theme();
+        assert!(!state.dark_mode);
+
+        state.system_dark = Some(true);
+        state.apply_theme();
+        assert!(state.dark_mode);
+
+        state.theme = state.theme.next();
+        state.apply_theme();
+        assert_eq!(state.theme, ThemePreference::Dark);
+        assert!(state.dark_mode);
+    }
+
+    #[test]
+    fn translucent_colors_round_trip_through_eight_digit_hex() {
+        let mut state = State::default();
+
+        assert!(state.parse_format("#ff000080"));
+        assert!((state.alpha - 128.0 / 255.0).abs() < 1e-3);
+        assert!(state.format_hex().ends_with("80"));
+        assert!(state.format_rgb().starts_with("rgba("));
+        assert!(state.format_rgb().ends_with(", 0.50)"));
+
+        assert!(state.parse_format("#00ff00"));
+        assert_eq!(state.alpha, 1.0);
+    }
+
+    #[test]
+    fn pasted_css_declarations_and_shorthands_parse() {
+        let mut state = State::default();
+
+        assert!(state.parse_format("  color: rebeccapurple;"));
+        let declared = state.values;
+        assert!(state.parse_format("rebeccapurple"));
+        assert_eq!(state.values, declared);
+        assert!(state.parse_format("--accent: #abc !important;"));
+        assert!(state.parse_format("abcd"));
+        assert!(state.alpha < 1.0);
+
+        state.mode = ColorMode::Oklch;
+        assert!(state.parse_format("hwb(120 10% 20%)"));
+        assert_eq!(state.mode, ColorMode::Hsv);
+        assert!(state.parse_format("rgb(10% 20% 30%)"));
+        assert!(!state.parse_format("not a color"));
+    }
+
     #[test]
     fn parsed_oklch_values_stay_inside_slider_ranges() {
         let parsed = parse_color("oklch(0.7 4.2 900)").unwrap();
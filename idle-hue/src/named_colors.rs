@@ -0,0 +1,198 @@
+use color::{AlphaColor, Oklab, Oklch, Srgb, parse_color};
+use std::sync::LazyLock;
+
+/// CSS named colors, excluding `transparent` and the `grey` spellings.
+const NAMES: [&str; 141] = [
+    "aliceblue",
+    "antiquewhite",
+    "aqua",
+    "aquamarine",
+    "azure",
+    "beige",
+    "bisque",
+    "black",
+    "blanchedalmond",
+    "blue",
+    "blueviolet",
+    "brown",
+    "burlywood",
+    "cadetblue",
+    "chartreuse",
+    "chocolate",
+    "coral",
+    "cornflowerblue",
+    "cornsilk",
+    "crimson",
+    "cyan",
+    "darkblue",
+    "darkcyan",
+    "darkgoldenrod",
+    "darkgray",
+    "darkgreen",
+    "darkkhaki",
+    "darkmagenta",
+    "darkolivegreen",
+    "darkorange",
+    "darkorchid",
+    "darkred",
+    "darksalmon",
+    "darkseagreen",
+    "darkslateblue",
+    "darkslategray",
+    "darkturquoise",
+    "darkviolet",
+    "deeppink",
+    "deepskyblue",
+    "dimgray",
+    "dodgerblue",
+    "firebrick",
+    "floralwhite",
+    "forestgreen",
+    "fuchsia",
+    "gainsboro",
+    "ghostwhite",
+    "gold",
+    "goldenrod",
+    "gray",
+    "green",
+    "greenyellow",
+    "honeydew",
+    "hotpink",
+    "indianred",
+    "indigo",
+    "ivory",
+    "khaki",
+    "lavender",
+    "lavenderblush",
+    "lawngreen",
+    "lemonchiffon",
+    "lightblue",
+    "lightcoral",
+    "lightcyan",
+    "lightgoldenrodyellow",
+    "lightgray",
+    "lightgreen",
+    "lightpink",
+    "lightsalmon",
+    "lightseagreen",
+    "lightskyblue",
+    "lightslategray",
+    "lightsteelblue",
+    "lightyellow",
+    "lime",
+    "limegreen",
+    "linen",
+    "magenta",
+    "maroon",
+    "mediumaquamarine",
+    "mediumblue",
+    "mediumorchid",
+    "mediumpurple",
+    "mediumseagreen",
+    "mediumslateblue",
+    "mediumspringgreen",
+    "mediumturquoise",
+    "mediumvioletred",
+    "midnightblue",
+    "mintcream",
+    "mistyrose",
+    "moccasin",
+    "navajowhite",
+    "navy",
+    "oldlace",
+    "olive",
+    "olivedrab",
+    "orange",
+    "orangered",
+    "orchid",
+    "palegoldenrod",
+    "palegreen",
+    "paleturquoise",
+    "palevioletred",
+    "papayawhip",
+    "peachpuff",
+    "peru",
+    "pink",
+    "plum",
+    "powderblue",
+    "purple",
+    "rebeccapurple",
+    "red",
+    "rosybrown",
+    "royalblue",
+    "saddlebrown",
+    "salmon",
+    "sandybrown",
+    "seagreen",
+    "seashell",
+    "sienna",
+    "silver",
+    "skyblue",
+    "slateblue",
+    "slategray",
+    "snow",
+    "springgreen",
+    "steelblue",
+    "tan",
+    "teal",
+    "thistle",
+    "tomato",
+    "turquoise",
+    "violet",
+    "wheat",
+    "white",
+    "whitesmoke",
+    "yellow",
+    "yellowgreen",
+];
+
+static NAMED_OKLAB: LazyLock<Vec<(&'static str, [f32; 3])>> = LazyLock::new(|| {
+    NAMES
+        .iter()
+        .filter_map(|name| {
+            let [l, a, b, _] = parse_color(name)
+                .ok()?
+                .to_alpha_color::<Srgb>()
+                .convert::<Oklab>()
+                .components;
+            Some((*name, [l, a, b]))
+        })
+        .collect()
+});
+
+/// Distance is ΔEOK scaled by 100, so a just-noticeable difference is about 2.
+pub(crate) fn nearest([l, c, h]: [f32; 3]) -> (&'static str, f32) {
+    let [l, a, b, _] = AlphaColor::<Oklch>::new([l, c, h, 1.0])
+        .convert::<Oklab>()
+        .components;
+    NAMED_OKLAB
+        .iter()
+        .map(|(name, [nl, na, nb])| {
+            let distance = ((l - nl).powi(2) + (a - na).powi(2) + (b - nb).powi(2)).sqrt();
+            (*name, distance * 100.0)
+        })
+        .min_by(|x, y| x.1.total_cmp(&y.1))
+        .unwrap_or(("black", f32::INFINITY))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_exact_and_near_matches() {
+        assert_eq!(NAMED_OKLAB.len(), NAMES.len());
+
+        let steelblue = parse_color("steelblue")
+            .unwrap()
+            .to_alpha_color::<Oklch>()
+            .components;
+        let (name, distance) = nearest([steelblue[0], steelblue[1], steelblue[2]]);
+        assert_eq!(name, "steelblue");
+        assert!(distance < 0.01);
+
+        let (name, distance) = nearest([0.63, 0.25, 29.0]);
+        assert_eq!(name, "red");
+        assert!(distance > 0.01);
+    }
+}
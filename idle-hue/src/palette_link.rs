@@ -0,0 +1,144 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use color::{Oklch, parse_color};
+
+const LINK_PREFIX: &str = "idle-hue://palette/";
+const LINK_VERSION: u8 = 1;
+/// Chroma stored in a link is quantized over `0.0..=LINK_MAX_CHROMA`, the slider's range.
+const LINK_MAX_CHROMA: f32 = 0.4;
+
+/// Empty cells are kept so swatches land where they were; trailing ones are dropped.
+pub(crate) fn encode(cells: &[Option<[f32; 3]>]) -> String {
+    let used = cells
+        .iter()
+        .rposition(Option::is_some)
+        .map_or(0, |last| last + 1);
+    let mut bytes = vec![LINK_VERSION];
+    for cell in &cells[..used] {
+        match cell {
+            None => bytes.push(0),
+            Some([l, c, h]) => {
+                bytes.push(1);
+                let quantize = |value: f32, max: f32| {
+                    ((value / max).clamp(0.0, 1.0) * u16::MAX as f32).round() as u16
+                };
+                for value in [
+                    quantize(*l, 1.0),
+                    quantize(*c, LINK_MAX_CHROMA),
+                    quantize(h.rem_euclid(360.0), 360.0),
+                ] {
+                    bytes.extend(value.to_be_bytes());
+                }
+            }
+        }
+    }
+    format!("{LINK_PREFIX}{}", URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// Also reads coolors.co links (`https://coolors.co/264653-2a9d8f-e9c46a`, `/palette/...`
+/// or a bare dash-separated hex list). A single hex code is not a palette.
+pub(crate) fn decode(text: &str) -> Option<Vec<Option<[f32; 3]>>> {
+    let text = text.trim();
+    if let Some(data) = text.strip_prefix(LINK_PREFIX) {
+        return decode_link(data);
+    }
+    let path = text.split(['?', '#']).next()?.trim_end_matches('/');
+    let list = path.rsplit('/').next()?;
+    let segments: Vec<&str> = list.split('-').collect();
+    if segments.len() < 2 {
+        return None;
+    }
+    segments
+        .into_iter()
+        .map(|hex| {
+            if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+            let [l, c, h, _] = parse_color(&format!("#{hex}"))
+                .ok()?
+                .to_alpha_color::<Oklch>()
+                .components;
+            Some(Some([l, c, if h.is_finite() { h } else { 0.0 }]))
+        })
+        .collect()
+}
+
+fn decode_link(data: &str) -> Option<Vec<Option<[f32; 3]>>> {
+    let bytes = URL_SAFE_NO_PAD.decode(data.trim_end_matches('/')).ok()?;
+    let (&version, mut rest) = bytes.split_first()?;
+    if version != LINK_VERSION {
+        return None;
+    }
+    let mut cells = Vec::new();
+    while let Some((&flag, tail)) = rest.split_first() {
+        match flag {
+            0 => {
+                cells.push(None);
+                rest = tail;
+            }
+            1 if tail.len() >= 6 => {
+                let value =
+                    |i: usize| u16::from_be_bytes([tail[i], tail[i + 1]]) as f32 / u16::MAX as f32;
+                cells.push(Some([
+                    value(0),
+                    value(2) * LINK_MAX_CHROMA,
+                    value(4) * 360.0,
+                ]));
+                rest = &tail[6..];
+            }
+            _ => return None,
+        }
+    }
+    cells.iter().any(Option::is_some).then_some(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_coolors_links_and_hex_lists() {
+        let colors =
+            decode("https://coolors.co/palette/264653-2a9d8f-e9c46a-f4a261-e76f51").unwrap();
+        assert_eq!(colors.len(), 5);
+        assert_eq!(
+            decode("https://coolors.co/264653-2a9d8f?ref=x")
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!((decode("ffffff-000000").unwrap()[0].unwrap()[0] - 1.0).abs() < 1e-3);
+
+        assert_eq!(decode("#264653"), None);
+        assert_eq!(decode("264653"), None);
+        assert_eq!(decode("oklch(0.5 0.1 200)"), None);
+        assert_eq!(decode("https://coolors.co/264653-nothex"), None);
+    }
+
+    #[test]
+    fn links_round_trip_positions_and_values() {
+        let cells = [
+            None,
+            Some([0.62, 0.21, 259.0]),
+            None,
+            Some([0.3, 0.05, 12.5]),
+            None,
+        ];
+        let link = encode(&cells);
+        assert!(link.starts_with(LINK_PREFIX));
+
+        let decoded = decode(&link).unwrap();
+        assert_eq!(decoded.len(), 4);
+        for (a, b) in decoded.iter().zip(&cells) {
+            match (a, b) {
+                (Some(a), Some(b)) => {
+                    for (x, y) in a.iter().zip(b) {
+                        assert!((x - y).abs() < 0.01, "{a:?} != {b:?}");
+                    }
+                }
+                (a, b) => assert_eq!(a.is_some(), b.is_some()),
+            }
+        }
+        assert_eq!(decode(&format!("{LINK_PREFIX}AQ")), None);
+    }
+}
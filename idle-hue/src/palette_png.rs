@@ -0,0 +1,203 @@
+use image::{ImageFormat, Rgba, RgbaImage};
+use std::io::Cursor;
+
+const CELL: u32 = 100;
+const GAP: u32 = 8;
+const PADDING: u32 = 12;
+const GLYPH_SCALE: u32 = 2;
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+const BACKGROUND: Rgba<u8> = Rgba([0xff, 0xff, 0xff, 0xff]);
+const EMPTY_CELL: Rgba<u8> = Rgba([0xee, 0xee, 0xee, 0xff]);
+
+/// Trailing empty rows are left out.
+pub(crate) fn encode(cells: &[Option<[f32; 3]>], columns: usize) -> Result<Vec<u8>, String> {
+    let columns = columns.max(1);
+    let rows = cells
+        .iter()
+        .rposition(Option::is_some)
+        .map_or(1, |last| last / columns + 1);
+    let span = |count: u32| PADDING * 2 + count * CELL + count.saturating_sub(1) * GAP;
+    let mut image = RgbaImage::from_pixel(span(columns as u32), span(rows as u32), BACKGROUND);
+
+    for (index, cell) in cells.iter().enumerate().take(rows * columns) {
+        let x = PADDING + (index % columns) as u32 * (CELL + GAP);
+        let y = PADDING + (index / columns) as u32 * (CELL + GAP);
+        let Some(rgb) = cell else {
+            fill(&mut image, x, y, CELL, CELL, EMPTY_CELL);
+            continue;
+        };
+        let [r, g, b] = rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        fill(&mut image, x, y, CELL, CELL, Rgba([r, g, b, 0xff]));
+
+        let label = format!("#{r:02X}{g:02X}{b:02X}");
+        let pen_x = x + (CELL - text_width(&label)) / 2;
+        let pen_y = y + CELL - TEXT_HEIGHT - 8;
+        draw_text(&mut image, pen_x, pen_y, &label, ink(*rgb));
+    }
+
+    png(&image)
+}
+
+pub(crate) fn encode_swatch(
+    rgb: [f32; 3],
+    alpha: f32,
+    size: u32,
+    label: Option<&str>,
+) -> Result<Vec<u8>, String> {
+    let size = size.max(1);
+    let [r, g, b, a] =
+        [rgb[0], rgb[1], rgb[2], alpha].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    let mut image = RgbaImage::from_pixel(size, size, Rgba([r, g, b, a]));
+    if let Some(label) = label {
+        let scale = (size / 80).max(1);
+        let width = scaled_text_width(label, scale);
+        let height = GLYPH_HEIGHT * scale;
+        let margin = height;
+        if width < size && height + margin < size {
+            let pen_x = (size - width) / 2;
+            let pen_y = size - height - margin;
+            draw_scaled_text(&mut image, pen_x, pen_y, label, ink(rgb), scale);
+        }
+    }
+    png(&image)
+}
+
+fn ink(rgb: [f32; 3]) -> Rgba<u8> {
+    let luminance = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+    if luminance > 0.5 {
+        Rgba([0x00, 0x00, 0x00, 0xff])
+    } else {
+        Rgba([0xff, 0xff, 0xff, 0xff])
+    }
+}
+
+fn png(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode PNG: {e}"))?;
+    Ok(bytes)
+}
+
+pub(crate) const TEXT_HEIGHT: u32 = GLYPH_HEIGHT * GLYPH_SCALE;
+
+pub(crate) fn text_width(text: &str) -> u32 {
+    scaled_text_width(text, GLYPH_SCALE)
+}
+
+pub(crate) fn draw_text(image: &mut RgbaImage, x: u32, y: u32, text: &str, ink: Rgba<u8>) {
+    draw_scaled_text(image, x, y, text, ink, GLYPH_SCALE);
+}
+
+fn scaled_text_width(text: &str, scale: u32) -> u32 {
+    let advance = (GLYPH_WIDTH + 1) * scale;
+    (text.chars().count() as u32 * advance).saturating_sub(scale)
+}
+
+fn draw_scaled_text(image: &mut RgbaImage, x: u32, y: u32, text: &str, ink: Rgba<u8>, scale: u32) {
+    let advance = (GLYPH_WIDTH + 1) * scale;
+    for (i, ch) in text.chars().enumerate() {
+        draw_glyph(image, x + i as u32 * advance, y, ch, ink, scale);
+    }
+}
+
+pub(crate) fn fill(
+    image: &mut RgbaImage,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    color: Rgba<u8>,
+) {
+    for py in y..y + height {
+        for px in x..x + width {
+            image.put_pixel(px, py, color);
+        }
+    }
+}
+
+fn draw_glyph(image: &mut RgbaImage, x: u32, y: u32, ch: char, ink: Rgba<u8>, scale: u32) {
+    for (row, bits) in glyph(ch).iter().enumerate() {
+        for col in 0..GLYPH_WIDTH {
+            if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                fill(
+                    image,
+                    x + col * scale,
+                    y + row as u32 * scale,
+                    scale,
+                    scale,
+                    ink,
+                );
+            }
+        }
+    }
+}
+
+/// 5×7 bitmaps, one byte per row.
+fn glyph(ch: char) -> [u8; 7] {
+    match ch {
+        '0' => [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e],
+        '1' => [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+        '2' => [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+        '3' => [0x1e, 0x01, 0x01, 0x0e, 0x01, 0x01, 0x1e],
+        '4' => [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+        '5' => [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e],
+        '6' => [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e],
+        '7' => [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e],
+        '9' => [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c],
+        'A' => [0x0e, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11],
+        'B' => [0x1e, 0x11, 0x11, 0x1e, 0x11, 0x11, 0x1e],
+        'C' => [0x0e, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0e],
+        'D' => [0x1c, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1c],
+        'E' => [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x1f],
+        'F' => [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x10],
+        '#' => [0x0a, 0x0a, 0x1f, 0x0a, 0x1f, 0x0a, 0x0a],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1f],
+        'c' => [0x00, 0x00, 0x0e, 0x10, 0x10, 0x11, 0x0e],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c],
+        ':' => [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x0c, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00],
+        _ => [0; 7],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_filled_rows_only() {
+        let mut cells = vec![None; 6];
+        cells[1] = Some([1.0, 0.0, 0.0]);
+
+        let bytes = encode(&cells, 3).unwrap();
+        let image = image::load_from_memory(&bytes).unwrap().to_rgba8();
+
+        assert_eq!(image.width(), PADDING * 2 + 3 * CELL + 2 * GAP);
+        assert_eq!(image.height(), PADDING * 2 + CELL);
+        let swatch_corner = image.get_pixel(PADDING + CELL + GAP + 1, PADDING + 1);
+        assert_eq!(*swatch_corner, Rgba([0xff, 0x00, 0x00, 0xff]));
+        assert_eq!(*image.get_pixel(PADDING + 1, PADDING + 1), EMPTY_CELL);
+    }
+
+    #[test]
+    fn renders_a_labeled_swatch() {
+        let bytes = encode_swatch([0.0, 0.0, 1.0], 0.5, 160, Some("#0000FF")).unwrap();
+        let image = image::load_from_memory(&bytes).unwrap().to_rgba8();
+
+        assert_eq!(image.dimensions(), (160, 160));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([0x00, 0x00, 0xff, 0x80]));
+        let ink = Rgba([0xff, 0xff, 0xff, 0xff]);
+        assert!(image.pixels().any(|pixel| *pixel == ink));
+
+        let bytes = encode_swatch([0.0, 0.0, 1.0], 1.0, 16, Some("#0000FF")).unwrap();
+        let image = image::load_from_memory(&bytes).unwrap().to_rgba8();
+        assert!(
+            image
+                .pixels()
+                .all(|pixel| *pixel == Rgba([0x00, 0x00, 0xff, 0xff]))
+        );
+    }
+}
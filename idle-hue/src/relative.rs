@@ -0,0 +1,101 @@
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct RelativeBase {
+    pub(crate) var: String,
+    pub(crate) oklch: [f32; 3],
+}
+
+impl RelativeBase {
+    fn reference(&self) -> String {
+        let name = self.var.trim();
+        if name.starts_with("--") {
+            format!("var({name})")
+        } else {
+            format!("var(--{name})")
+        }
+    }
+
+    /// Channels that didn't change are left as bare keywords.
+    pub(crate) fn relative(&self, color: [f32; 3], alpha: f32) -> String {
+        let [l0, c0, h0] = self.oklch;
+        let [l, c, h] = color;
+        // Grays have no meaningful hue; use the base's so the hue term drops out.
+        let h = if c < 1e-4 { h0 } else { h };
+        let dh = (h - h0 + 180.0).rem_euclid(360.0) - 180.0;
+        let channel = |name: &str, delta: f32, precision: usize, threshold: f32| {
+            if delta.abs() < threshold {
+                name.to_string()
+            } else {
+                let sign = if delta < 0.0 { '-' } else { '+' };
+                format!("calc({name} {sign} {:.precision$})", delta.abs())
+            }
+        };
+        let alpha = if alpha < 1.0 {
+            format!(" / {alpha:.2}")
+        } else {
+            String::new()
+        };
+        format!(
+            "oklch(from {} {} {} {}{alpha})",
+            self.reference(),
+            channel("l", l - l0, 2, 0.005),
+            channel("c", c - c0, 3, 0.0005),
+            channel("h", dh, 1, 0.05),
+        )
+    }
+
+    /// Tints and shades of the base read better as `color-mix()` than as relative colors.
+    pub(crate) fn mix(&self, color: [f32; 3]) -> Option<String> {
+        const TOLERANCE: f32 = 0.01;
+        let [l0, c0, h0] = self.oklch;
+        let [l, c, h] = color;
+        let hue_matches = c < TOLERANCE
+            || c0 < TOLERANCE
+            || ((h - h0 + 180.0).rem_euclid(360.0) - 180.0).abs() < 2.0;
+        if !hue_matches {
+            return None;
+        }
+        // Mixing in OKLCH with white or black moves lightness toward the end point and
+        // scales chroma down by the same amount; white's and black's hues are powerless.
+        let (target, amount) = if l > l0 {
+            ("white", (l - l0) / (1.0 - l0))
+        } else {
+            ("black", (l0 - l) / l0)
+        };
+        if !(0.005..0.995).contains(&amount) || (c0 * (1.0 - amount) - c).abs() > TOLERANCE {
+            return None;
+        }
+        let base = ((1.0 - amount) * 100.0).round();
+        Some(format!(
+            "color-mix(in oklch, {} {base:.0}%, {target})",
+            self.reference()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_relative_and_mixed_colors() {
+        let base = RelativeBase {
+            var: "--brand".to_string(),
+            oklch: [0.6, 0.15, 250.0],
+        };
+
+        assert_eq!(
+            base.relative([0.7, 0.15, 10.0], 1.0),
+            "oklch(from var(--brand) calc(l + 0.10) c calc(h + 120.0))"
+        );
+        assert_eq!(
+            base.relative([0.6, 0.1, 250.0], 0.5),
+            "oklch(from var(--brand) l calc(c - 0.050) h / 0.50)"
+        );
+        assert_eq!(
+            base.mix([0.8, 0.075, 250.0]).as_deref(),
+            Some("color-mix(in oklch, var(--brand) 50%, white)")
+        );
+        assert_eq!(base.mix([0.8, 0.15, 250.0]), None);
+        assert_eq!(base.mix([0.3, 0.075, 100.0]), None);
+    }
+}
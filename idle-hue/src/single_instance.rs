@@ -0,0 +1,272 @@
+use crate::url_scheme;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// What the running instance answers with, so a launch can tell it apart from a stale
+/// endpoint nobody is serving.
+const ACK: &[u8] = b"idle-hue\n";
+
+static FOCUS_REQUESTED: AtomicBool = AtomicBool::new(false);
+static NOTIFY: Mutex<Option<Box<dyn Fn() + Send>>> = Mutex::new(None);
+
+#[cfg(unix)]
+pub(crate) use unix::Listener;
+#[cfg(windows)]
+pub(crate) use windows::Listener;
+
+pub(crate) enum Launch {
+    /// Holds no listener when the endpoint couldn't be claimed.
+    First(Option<Listener>),
+    HandedOff,
+}
+
+pub(crate) fn start(args: &[String]) -> Launch {
+    #[cfg(unix)]
+    let launch = unix::start(args);
+    #[cfg(windows)]
+    let launch = windows::start(args);
+    launch
+}
+
+fn exchange(mut stream: impl Read + Write, args: &[String]) -> std::io::Result<bool> {
+    for arg in args.iter().filter(|arg| !arg.is_empty()) {
+        writeln!(stream, "{}", arg.replace('\n', " "))?;
+    }
+    writeln!(stream)?;
+    stream.flush()?;
+    let mut reply = Vec::new();
+    stream.take(ACK.len() as u64).read_to_end(&mut reply)?;
+    Ok(reply == ACK)
+}
+
+fn answer(stream: impl Read + Write) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 && !line.trim_end().is_empty() {
+        url_scheme::receive(line.trim_end());
+        line.clear();
+    }
+    // Set before the reply, so the request is there once the other launch sees it.
+    FOCUS_REQUESTED.store(true, Ordering::SeqCst);
+    let stream = reader.get_mut();
+    stream.write_all(ACK)?;
+    stream.flush()?;
+    if let Some(notify) = &*NOTIFY.lock().unwrap_or_else(|e| e.into_inner()) {
+        notify();
+    }
+    Ok(())
+}
+
+pub(crate) fn serve(listener: Listener) {
+    std::thread::spawn(move || listener.run());
+}
+
+pub(crate) fn take_focus_request() -> bool {
+    FOCUS_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+pub(crate) fn on_handoff(notify: impl Fn() + Send + 'static) {
+    *NOTIFY.lock().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(notify));
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::{Launch, answer, exchange};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_millis(500);
+
+    pub(crate) struct Listener(UnixListener);
+
+    impl Listener {
+        pub(super) fn run(self) {
+            for stream in self.0.incoming().flatten() {
+                if let Err(e) = stream
+                    .set_read_timeout(Some(TIMEOUT))
+                    .and_then(|()| answer(stream))
+                {
+                    log::error!("Failed to read a handoff from another launch: {e}");
+                }
+            }
+        }
+    }
+
+    /// The socket lives in a directory only this user can reach, so other users'
+    /// launches and processes can't talk to this instance.
+    fn socket_path() -> Option<PathBuf> {
+        let dir = dirs::runtime_dir().or_else(|| {
+            directories::ProjectDirs::from("com", "cyy", "idle-hue")
+                .map(|p| p.config_dir().to_path_buf())
+        })?;
+        std::fs::create_dir_all(&dir).ok()?;
+        Some(dir.join("idle-hue.sock"))
+    }
+
+    pub(super) fn start(args: &[String]) -> Launch {
+        match socket_path() {
+            Some(path) => start_at(&path, args),
+            None => {
+                log::error!("No directory for the single-instance socket");
+                Launch::First(None)
+            }
+        }
+    }
+
+    pub(super) fn start_at(path: &Path, args: &[String]) -> Launch {
+        if let Ok(listener) = bind(path) {
+            return Launch::First(Some(listener));
+        }
+        if hand_off(path, args) {
+            return Launch::HandedOff;
+        }
+        // Left behind by an instance that didn't exit cleanly.
+        let _ = std::fs::remove_file(path);
+        match bind(path) {
+            Ok(listener) => Launch::First(Some(listener)),
+            Err(e) => {
+                log::error!("Failed to claim the single-instance socket: {e}");
+                Launch::First(None)
+            }
+        }
+    }
+
+    fn bind(path: &Path) -> std::io::Result<Listener> {
+        let listener = UnixListener::bind(path)?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        Ok(Listener(listener))
+    }
+
+    pub(super) fn hand_off(path: &Path, args: &[String]) -> bool {
+        let send = || -> std::io::Result<bool> {
+            let stream = UnixStream::connect(path)?;
+            stream.set_read_timeout(Some(TIMEOUT))?;
+            stream.set_write_timeout(Some(TIMEOUT))?;
+            exchange(stream, args)
+        };
+        send().unwrap_or(false)
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::{Launch, answer, exchange};
+    use std::fs::{File, OpenOptions};
+    use std::os::windows::io::{AsRawHandle, FromRawHandle};
+    use windows_sys::Win32::Foundation::{
+        ERROR_PIPE_CONNECTED, GetLastError, INVALID_HANDLE_VALUE,
+    };
+    use windows_sys::Win32::Storage::FileSystem::{
+        FILE_FLAG_FIRST_PIPE_INSTANCE, FlushFileBuffers, PIPE_ACCESS_DUPLEX,
+    };
+    use windows_sys::Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS,
+        PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+
+    pub(crate) struct Listener(File);
+
+    impl Listener {
+        pub(super) fn run(self) {
+            let mut pipe = self.0;
+            loop {
+                let handle = pipe.as_raw_handle();
+                let connected = unsafe { ConnectNamedPipe(handle, std::ptr::null_mut()) } != 0
+                    || unsafe { GetLastError() } == ERROR_PIPE_CONNECTED;
+                if connected {
+                    if let Err(e) = answer(&pipe) {
+                        log::error!("Failed to read a handoff from another launch: {e}");
+                    }
+                    unsafe { FlushFileBuffers(handle) };
+                }
+                match create(false) {
+                    Some(next) => pipe = next,
+                    None => {
+                        log::error!("Failed to open the single-instance pipe again");
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Named per user, and the default pipe security only lets its creator write to it.
+    fn pipe_name() -> String {
+        let user = std::env::var("USERNAME").unwrap_or_default();
+        format!(r"\\.\pipe\idle-hue-{user}")
+    }
+
+    fn create(first: bool) -> Option<File> {
+        let name: Vec<u16> = pipe_name().encode_utf16().chain([0]).collect();
+        let mut open_mode = PIPE_ACCESS_DUPLEX;
+        if first {
+            open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+        }
+        let handle = unsafe {
+            CreateNamedPipeW(
+                name.as_ptr(),
+                open_mode,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                PIPE_UNLIMITED_INSTANCES,
+                512,
+                512,
+                0,
+                std::ptr::null(),
+            )
+        };
+        (handle != INVALID_HANDLE_VALUE).then(|| unsafe { File::from_raw_handle(handle) })
+    }
+
+    pub(super) fn start(args: &[String]) -> Launch {
+        if let Some(pipe) = create(true) {
+            return Launch::First(Some(Listener(pipe)));
+        }
+        let send = || -> std::io::Result<bool> {
+            let pipe = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(pipe_name())?;
+            exchange(&pipe, args)
+        };
+        if send().unwrap_or(false) {
+            Launch::HandedOff
+        } else {
+            log::error!("Failed to claim the single-instance pipe");
+            Launch::First(None)
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_launches_hand_their_links_to_the_first() {
+        let path = std::env::temp_dir().join(format!("idle-hue-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let Launch::First(Some(listener)) = unix::start_at(&path, &[]) else {
+            panic!("the first launch should claim the socket");
+        };
+        serve(listener);
+
+        let args = ["idle-hue://color/%23ff8800".to_string()];
+        assert!(matches!(unix::start_at(&path, &args), Launch::HandedOff));
+        assert!(take_focus_request());
+        assert_eq!(url_scheme::take_pending(), ["#ff8800"]);
+
+        let stale =
+            std::env::temp_dir().join(format!("idle-hue-stale-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&stale);
+        drop(std::os::unix::net::UnixListener::bind(&stale).unwrap());
+        assert!(matches!(
+            unix::start_at(&stale, &args),
+            Launch::First(Some(_))
+        ));
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&stale);
+    }
+}
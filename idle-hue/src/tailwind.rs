@@ -0,0 +1,128 @@
+const SHADES: [(u16, f32); 11] = [
+    (50, 0.97),
+    (100, 0.94),
+    (200, 0.89),
+    (300, 0.82),
+    (400, 0.72),
+    (500, 0.64),
+    (600, 0.56),
+    (700, 0.49),
+    (800, 0.42),
+    (900, 0.37),
+    (950, 0.27),
+];
+
+const FAMILIES: [(&str, f32); 17] = [
+    ("rose", 16.0),
+    ("red", 25.0),
+    ("orange", 47.0),
+    ("amber", 70.0),
+    ("yellow", 86.0),
+    ("lime", 131.0),
+    ("green", 150.0),
+    ("emerald", 163.0),
+    ("teal", 183.0),
+    ("cyan", 215.0),
+    ("sky", 237.0),
+    ("blue", 260.0),
+    ("indigo", 277.0),
+    ("violet", 293.0),
+    ("purple", 303.0),
+    ("fuchsia", 322.0),
+    ("pink", 354.0),
+];
+
+/// Below this chroma a color is filed under `gray` regardless of hue.
+const GRAY_CHROMA: f32 = 0.03;
+
+struct Family {
+    name: String,
+    shades: Vec<(u16, String)>,
+}
+
+/// A color whose shade is already taken starts a numbered family (`blue2`).
+pub(crate) fn encode(colors: &[([f32; 3], String)]) -> String {
+    let mut families: Vec<Family> = Vec::new();
+    for (oklch, hex) in colors {
+        let base = family_name(*oklch);
+        let shade = shade_key(oklch[0]);
+        let family = (1..)
+            .map(|n| {
+                if n == 1 {
+                    base.to_string()
+                } else {
+                    format!("{base}{n}")
+                }
+            })
+            .find(|name| {
+                families
+                    .iter()
+                    .find(|f| &f.name == name)
+                    .is_none_or(|f| f.shades.iter().all(|(key, _)| *key != shade))
+            })
+            .expect("family numbering is unbounded");
+        match families.iter_mut().find(|f| f.name == family) {
+            Some(f) => f.shades.push((shade, hex.to_lowercase())),
+            None => families.push(Family {
+                name: family,
+                shades: vec![(shade, hex.to_lowercase())],
+            }),
+        }
+    }
+
+    let mut out = String::from("colors: {\n");
+    for family in &mut families {
+        family.shades.sort_by_key(|(key, _)| *key);
+        out.push_str(&format!("  {}: {{\n", family.name));
+        for (key, hex) in &family.shades {
+            out.push_str(&format!("    {key}: '{hex}',\n"));
+        }
+        out.push_str("  },\n");
+    }
+    out.push_str("},\n");
+    out
+}
+
+fn family_name([_, c, h]: [f32; 3]) -> &'static str {
+    if c < GRAY_CHROMA {
+        return "gray";
+    }
+    let hue_distance = |a: f32| {
+        let d = (a - h).rem_euclid(360.0);
+        d.min(360.0 - d)
+    };
+    FAMILIES
+        .iter()
+        .min_by(|a, b| hue_distance(a.1).total_cmp(&hue_distance(b.1)))
+        .map(|(name, _)| *name)
+        .unwrap_or("gray")
+}
+
+fn shade_key(lightness: f32) -> u16 {
+    SHADES
+        .iter()
+        .min_by(|a, b| (a.1 - lightness).abs().total_cmp(&(b.1 - lightness).abs()))
+        .map(|(key, _)| *key)
+        .unwrap_or(500)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_colors_by_hue_and_lightness() {
+        let snippet = encode(&[
+            ([0.62, 0.21, 259.0], "#3B82F6".to_string()),
+            ([0.93, 0.03, 255.0], "#DBEAFE".to_string()),
+            ([0.63, 0.20, 262.0], "#3A80F0".to_string()),
+            ([0.5, 0.0, 0.0], "#666666".to_string()),
+        ]);
+
+        assert_eq!(
+            snippet,
+            "colors: {\n  blue: {\n    100: '#dbeafe',\n    500: '#3b82f6',\n  },\n  \
+             blue2: {\n    500: '#3a80f0',\n  },\n  gray: {\n    700: '#666666',\n  },\n},\n"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+/// Toasts shown at once; another pushes the oldest out.
+const MAX_TOASTS: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ToastKind {
+    Info,
+    Error,
+}
+
+impl ToastKind {
+    /// Errors get long enough to read.
+    pub(crate) fn duration(self) -> Duration {
+        match self {
+            ToastKind::Info => Duration::from_secs(2),
+            ToastKind::Error => Duration::from_secs(6),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Toast {
+    pub(crate) id: u64,
+    pub(crate) kind: ToastKind,
+    pub(crate) message: String,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Toasts {
+    next_id: u64,
+    shown: Vec<Toast>,
+}
+
+impl Toasts {
+    /// Repeating the newest message replaces it, so its timer starts over.
+    pub(crate) fn push(&mut self, kind: ToastKind, message: impl Into<String>) -> u64 {
+        let message = message.into();
+        if self
+            .shown
+            .last()
+            .is_some_and(|last| last.kind == kind && last.message == message)
+        {
+            self.shown.pop();
+        }
+        if self.shown.len() == MAX_TOASTS {
+            self.shown.remove(0);
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.shown.push(Toast { id, kind, message });
+        id
+    }
+
+    pub(crate) fn dismiss(&mut self, id: u64) {
+        self.shown.retain(|toast| toast.id != id);
+    }
+
+    pub(crate) fn shown(&self) -> &[Toast] {
+        &self.shown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stacks_a_few_and_collapses_repeats() {
+        let mut toasts = Toasts::default();
+        let copied = toasts.push(ToastKind::Info, "copied");
+        let again = toasts.push(ToastKind::Info, "copied");
+        assert_eq!(toasts.shown().len(), 1);
+        toasts.dismiss(copied);
+        assert_eq!(toasts.shown().len(), 1);
+
+        for i in 0..MAX_TOASTS {
+            toasts.push(ToastKind::Error, format!("error {i}"));
+        }
+        assert_eq!(toasts.shown().len(), MAX_TOASTS);
+        assert_eq!(toasts.shown()[0].message, "error 0");
+        toasts.dismiss(again);
+        assert_eq!(toasts.shown().len(), MAX_TOASTS);
+    }
+}
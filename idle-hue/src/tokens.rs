@@ -0,0 +1,101 @@
+use crate::PALETTE_WIDTH;
+use crate::color_mode::ColorMode;
+use color::{AlphaColor, Oklch, Srgb};
+use serde_json::{Map, Value, json};
+
+const UNNAMED_GROUP: &str = "palette";
+
+pub(crate) fn encode(cells: &[Option<[f32; 3]>], labels: &[String], mode: ColorMode) -> String {
+    let mut groups = Map::new();
+    for (index, cell) in cells.iter().enumerate() {
+        let Some(oklch) = *cell else {
+            continue;
+        };
+        let label = labels
+            .get(index / PALETTE_WIDTH)
+            .map(|label| group_name(label))
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| UNNAMED_GROUP.to_string());
+        let group = groups
+            .entry(label)
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .expect("groups are objects");
+        let name = (group.len() + 1).to_string();
+        group.insert(
+            name,
+            json!({
+                "$type": "color",
+                "$value": value(oklch, mode),
+            }),
+        );
+    }
+    serde_json::to_string_pretty(&Value::Object(groups)).unwrap_or_default()
+}
+
+/// HSV and CMYK have no DTCG space, so they're written as sRGB.
+fn value(oklch: [f32; 3], mode: ColorMode) -> Value {
+    let [r, g, b, _] = AlphaColor::<Oklch>::new([oklch[0], oklch[1], oklch[2], 1.0])
+        .convert::<Srgb>()
+        .components;
+    let rgb = [r, g, b].map(|c| c.clamp(0.0, 1.0));
+    let (space, components) = match mode {
+        ColorMode::Oklch => ("oklch", mode.components(oklch)),
+        ColorMode::Oklab => ("oklab", mode.components(oklch)),
+        ColorMode::Lab => ("lab", mode.components(oklch)),
+        ColorMode::Lch => ("lch", mode.components(oklch)),
+        ColorMode::Linear => ("srgb-linear", mode.components(oklch)),
+        ColorMode::Hsv | ColorMode::Cmyk => ("srgb", [rgb[0], rgb[1], rgb[2], 0.0]),
+    };
+    let [r, g, b] = rgb.map(|c| (c * 255.0).round() as u8);
+    json!({
+        "colorSpace": space,
+        "components": components[..3].iter().map(|&c| round(c)).collect::<Vec<_>>(),
+        "hex": format!("#{r:02x}{g:02x}{b:02x}"),
+    })
+}
+
+fn round(component: f32) -> f64 {
+    (component as f64 * 10_000.0).round() / 10_000.0
+}
+
+fn group_name(label: &str) -> String {
+    label
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_swatches_by_row_name() {
+        let mut cells = vec![None; PALETTE_WIDTH * 2];
+        cells[0] = Some([0.6, 0.1, 250.0]);
+        cells[1] = Some([0.5, 0.0, 0.0]);
+        cells[PALETTE_WIDTH] = Some([1.0, 0.0, 0.0]);
+        let labels = vec!["Brand Blues".to_string()];
+
+        let tokens = encode(&cells, &labels, ColorMode::Oklch);
+        let json: Value = serde_json::from_str(&tokens).unwrap();
+        assert_eq!(json["brand-blues"]["1"]["$type"], "color");
+        assert_eq!(
+            json["brand-blues"]["1"]["$value"]["components"],
+            json!([0.6, 0.1, 250.0])
+        );
+        assert_eq!(json["brand-blues"]["2"]["$value"]["colorSpace"], "oklch");
+        assert_eq!(json["palette"]["1"]["$value"]["hex"], "#ffffff");
+
+        let tokens = encode(&cells, &labels, ColorMode::Cmyk);
+        let json: Value = serde_json::from_str(&tokens).unwrap();
+        assert_eq!(json["palette"]["1"]["$value"]["colorSpace"], "srgb");
+        assert_eq!(
+            json["palette"]["1"]["$value"]["components"],
+            json!([1.0, 1.0, 1.0])
+        );
+    }
+}
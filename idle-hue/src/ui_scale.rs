@@ -0,0 +1,53 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Applied on top of the OS scale factor.
+pub(crate) const UI_SCALES: [f32; 6] = [0.8, 0.9, 1.0, 1.1, 1.25, 1.5];
+
+/// The current zoom as `f32` bits. View code reads it from everywhere, including helpers
+/// that never see `State`, so it lives here instead of being threaded through.
+static UI_SCALE: AtomicU32 = AtomicU32::new(1.0f32.to_bits());
+
+pub(crate) fn set(scale: f32) {
+    UI_SCALE.store(clamp(scale).to_bits(), Ordering::Relaxed);
+}
+
+pub(crate) fn get() -> f32 {
+    f32::from_bits(UI_SCALE.load(Ordering::Relaxed))
+}
+
+pub(crate) fn px(length: f32) -> f32 {
+    length * get()
+}
+
+pub(crate) fn font(size: u32) -> u32 {
+    (size as f32 * get()).round() as u32
+}
+
+pub(crate) fn next(scale: f32) -> f32 {
+    UI_SCALES
+        .into_iter()
+        .find(|step| *step > scale + 1e-3)
+        .unwrap_or(UI_SCALES[0])
+}
+
+pub(crate) fn clamp(scale: f32) -> f32 {
+    if scale.is_finite() {
+        scale.clamp(UI_SCALES[0], UI_SCALES[UI_SCALES.len() - 1])
+    } else {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_cycle_and_bad_values_are_clamped() {
+        assert_eq!(next(1.0), 1.1);
+        assert_eq!(next(1.5), 0.8);
+        assert_eq!(next(1.05), 1.1);
+        assert_eq!(clamp(4.0), 1.5);
+        assert_eq!(clamp(f32::NAN), 1.0);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::gamut::GamutMapping;
+use color::{AlphaColor, Oklch, Srgb};
+
+/// Largest chroma given to a tinted UI gray, reached at mid lightness.
+const TINT_CHROMA: f32 = 0.03;
+
+/// `Color` keeps the hue of whichever color was being edited when it was chosen.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum UiTint {
+    #[default]
+    Neutral,
+    Slate,
+    Sand,
+    Moss,
+    Rose,
+    Color {
+        hue: f32,
+    },
+}
+
+impl UiTint {
+    /// The last step picks up `current_hue`.
+    pub(crate) fn next(self, current_hue: f32) -> Self {
+        match self {
+            UiTint::Neutral => UiTint::Slate,
+            UiTint::Slate => UiTint::Sand,
+            UiTint::Sand => UiTint::Moss,
+            UiTint::Moss => UiTint::Rose,
+            UiTint::Rose => UiTint::Color { hue: current_hue },
+            UiTint::Color { .. } => UiTint::Neutral,
+        }
+    }
+
+    fn hue(self) -> Option<f32> {
+        match self {
+            UiTint::Neutral => None,
+            UiTint::Slate => Some(255.0),
+            UiTint::Sand => Some(75.0),
+            UiTint::Moss => Some(135.0),
+            UiTint::Rose => Some(10.0),
+            UiTint::Color { hue } => Some(hue),
+        }
+    }
+
+    pub(crate) fn preview(self) -> AlphaColor<Srgb> {
+        let oklch = match self.hue() {
+            Some(hue) => [0.65, 0.12, hue],
+            None => [0.65, 0.0, 0.0],
+        };
+        let [r, g, b] = GamutMapping::Perceptual.to_srgb(oklch);
+        AlphaColor::new([r, g, b, 1.0])
+    }
+
+    /// Black and white stay (nearly) neutral so text keeps its contrast.
+    pub(crate) fn apply(self, gray: AlphaColor<Srgb>) -> AlphaColor<Srgb> {
+        let Some(hue) = self.hue() else {
+            return gray;
+        };
+        let [l, _, _, alpha] = gray.convert::<Oklch>().components;
+        let chroma = TINT_CHROMA * 4.0 * l * (1.0 - l);
+        let [r, g, b] = GamutMapping::Perceptual.to_srgb([l, chroma, hue]);
+        AlphaColor::new([r, g, b, alpha])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tints_keep_lightness_and_spare_black_and_white() {
+        let gray = AlphaColor::<Srgb>::new([0.3, 0.3, 0.3, 1.0]);
+        let tinted = UiTint::Sand.apply(gray).convert::<Oklch>().components;
+        let original = gray.convert::<Oklch>().components;
+
+        assert!((tinted[0] - original[0]).abs() < 1e-3);
+        assert!(tinted[1] > 0.02);
+        assert!((tinted[2] - 75.0).abs() < 1.0);
+
+        let white = AlphaColor::<Srgb>::new([1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(
+            UiTint::Rose.apply(white).to_rgba8(),
+            white.to_rgba8(),
+            "white should stay white"
+        );
+        assert_eq!(UiTint::Neutral.apply(gray).to_rgba8(), gray.to_rgba8());
+    }
+}
@@ -0,0 +1,53 @@
+const LIMIT: usize = 50;
+
+#[derive(Debug)]
+pub(crate) struct UndoStack<T> {
+    snapshots: Vec<T>,
+}
+
+impl<T> Default for UndoStack<T> {
+    fn default() -> Self {
+        Self {
+            snapshots: Vec::new(),
+        }
+    }
+}
+
+impl<T> UndoStack<T> {
+    pub(crate) fn record(&mut self, snapshot: T) {
+        if self.snapshots.len() == LIMIT {
+            self.snapshots.remove(0);
+        }
+        self.snapshots.push(snapshot);
+    }
+
+    pub(crate) fn undo(&mut self) -> Option<T> {
+        self.snapshots.pop()
+    }
+
+    pub(crate) fn can_undo(&self) -> bool {
+        !self.snapshots.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undoes_newest_first_and_forgets_the_oldest() {
+        let mut stack = UndoStack::default();
+        for i in 0..LIMIT + 2 {
+            stack.record(i);
+        }
+        assert_eq!(stack.undo(), Some(LIMIT + 1));
+        assert_eq!(stack.undo(), Some(LIMIT));
+
+        let mut rest = Vec::new();
+        while let Some(i) = stack.undo() {
+            rest.push(i);
+        }
+        assert_eq!(rest.last(), Some(&2));
+        assert!(!stack.can_undo());
+    }
+}
@@ -0,0 +1,167 @@
+use std::sync::Mutex;
+
+pub(crate) const SCHEME: &str = "idle-hue";
+
+static PENDING: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static NOTIFY: Mutex<Option<Box<dyn Fn() + Send>>> = Mutex::new(None);
+
+pub(crate) fn color_code(url: &str) -> Option<String> {
+    let rest = url.trim().strip_prefix(SCHEME)?.strip_prefix("://")?;
+    let code = rest.strip_prefix("color/")?.trim_end_matches('/');
+    let code = percent_decode(code)?;
+    (!code.trim().is_empty()).then_some(code)
+}
+
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+pub(crate) fn receive(url: &str) -> bool {
+    let Some(code) = color_code(url) else {
+        return false;
+    };
+    PENDING.lock().unwrap_or_else(|e| e.into_inner()).push(code);
+    if let Some(notify) = &*NOTIFY.lock().unwrap_or_else(|e| e.into_inner()) {
+        notify();
+    }
+    true
+}
+
+pub(crate) fn take_pending() -> Vec<String> {
+    std::mem::take(&mut *PENDING.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+pub(crate) fn on_receive(notify: impl Fn() + Send + 'static) {
+    *NOTIFY.lock().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(notify));
+}
+
+/// Windows launches it with the link as an argument.
+#[cfg(target_os = "windows")]
+pub(crate) fn register() -> Result<(), String> {
+    use windows_sys::Win32::System::Registry::{
+        HKEY, HKEY_CURRENT_USER, KEY_WRITE, REG_OPTION_NON_VOLATILE, REG_SZ, RegCloseKey,
+        RegCreateKeyExW, RegSetValueExW,
+    };
+
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to find idle-hue: {e}"))?;
+    let command = format!("\"{}\" \"%1\"", exe.display());
+    let wide = |text: &str| text.encode_utf16().chain([0]).collect::<Vec<u16>>();
+    let set = |key: &str, name: Option<&str>, value: &str| -> Result<(), String> {
+        let key_name = wide(&format!("Software\\Classes\\{key}"));
+        let name = name.map(wide);
+        let value = wide(value);
+        unsafe {
+            let mut hkey: HKEY = std::ptr::null_mut();
+            let status = RegCreateKeyExW(
+                HKEY_CURRENT_USER,
+                key_name.as_ptr(),
+                0,
+                std::ptr::null(),
+                REG_OPTION_NON_VOLATILE,
+                KEY_WRITE,
+                std::ptr::null(),
+                &mut hkey,
+                std::ptr::null_mut(),
+            );
+            if status != 0 {
+                return Err(format!("Failed to create registry key {key}: {status}"));
+            }
+            let status = RegSetValueExW(
+                hkey,
+                name.as_ref().map_or(std::ptr::null(), |name| name.as_ptr()),
+                0,
+                REG_SZ,
+                value.as_ptr().cast(),
+                (value.len() * 2) as u32,
+            );
+            RegCloseKey(hkey);
+            if status != 0 {
+                return Err(format!("Failed to write registry key {key}: {status}"));
+            }
+        }
+        Ok(())
+    };
+
+    set(SCHEME, None, "URL:idle-hue")?;
+    set(SCHEME, Some("URL Protocol"), "")?;
+    set(&format!("{SCHEME}\\shell\\open\\command"), None, &command)
+}
+
+/// macOS delivers links to the running app as Apple events rather than arguments; this
+/// installs the handler that receives them. Must run on the main thread, before the app
+/// finishes launching, so the link that launched it isn't missed.
+#[cfg(target_os = "macos")]
+pub(crate) fn listen() {
+    use objc2::rc::Retained;
+    use objc2::runtime::NSObject;
+    use objc2::{AllocAnyThread, define_class, msg_send, sel};
+    use objc2_foundation::{NSAppleEventDescriptor, NSAppleEventManager};
+
+    /// `'GURL'`, both the event class and id of "open this URL".
+    const GET_URL: u32 = u32::from_be_bytes(*b"GURL");
+    /// `'----'`, the parameter holding the URL.
+    const DIRECT_OBJECT: u32 = u32::from_be_bytes(*b"----");
+
+    define_class!(
+        #[unsafe(super(NSObject))]
+        #[name = "IdleHueUrlHandler"]
+        struct UrlHandler;
+
+        impl UrlHandler {
+            #[unsafe(method(handleGetURLEvent:withReplyEvent:))]
+            fn handle_get_url(&self, event: &NSAppleEventDescriptor, _reply: &NSAppleEventDescriptor) {
+                let url = unsafe { event.paramDescriptorForKeyword(DIRECT_OBJECT) }
+                    .and_then(|descriptor| unsafe { descriptor.stringValue() });
+                if let Some(url) = url {
+                    receive(&url.to_string());
+                }
+            }
+        }
+    );
+
+    let handler: Retained<UrlHandler> = unsafe { msg_send![UrlHandler::alloc(), init] };
+    unsafe {
+        NSAppleEventManager::sharedAppleEventManager()
+            .setEventHandler_andSelector_forEventClass_andEventID(
+                &handler,
+                sel!(handleGetURLEvent:withReplyEvent:),
+                GET_URL,
+                GET_URL,
+            );
+    }
+    // The event manager doesn't retain its handlers; this one lives as long as the app.
+    std::mem::forget(handler);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_color_links() {
+        assert_eq!(
+            color_code("idle-hue://color/%23ff8800").as_deref(),
+            Some("#ff8800")
+        );
+        assert_eq!(
+            color_code("idle-hue://color/oklch(0.7%200.1%20200)/").as_deref(),
+            Some("oklch(0.7 0.1 200)")
+        );
+        assert_eq!(color_code("idle-hue://color/"), None);
+        assert_eq!(color_code("idle-hue://palette/abc"), None);
+        assert_eq!(color_code("https://color/red"), None);
+        assert_eq!(color_code("idle-hue://color/%2"), None);
+    }
+}
@@ -0,0 +1,177 @@
+/// The window's frame in the platform's own screen coordinates: points from the bottom
+/// left on macOS, pixels from the top left on Windows. Only ever restored on the
+/// platform that saved it.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+pub(crate) struct WindowGeometry {
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    pub(crate) width: f64,
+    pub(crate) height: f64,
+}
+
+/// Must be called on the main thread.
+pub(crate) fn current() -> Option<WindowGeometry> {
+    #[cfg(target_os = "macos")]
+    {
+        let window = macos::window()?;
+        if window.isMiniaturized() {
+            return None;
+        }
+        let frame = window.frame();
+        Some(WindowGeometry {
+            x: frame.origin.x,
+            y: frame.origin.y,
+            width: frame.size.width,
+            height: frame.size.height,
+        })
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows_sys::Win32::Foundation::RECT;
+        use windows_sys::Win32::UI::WindowsAndMessaging::{GetWindowRect, IsIconic};
+
+        let hwnd = windows::window()?;
+        let mut rect = RECT {
+            left: 0,
+            top: 0,
+            right: 0,
+            bottom: 0,
+        };
+        unsafe {
+            if IsIconic(hwnd) != 0 || GetWindowRect(hwnd, &mut rect) == 0 {
+                return None;
+            }
+        }
+        Some(WindowGeometry {
+            x: rect.left as f64,
+            y: rect.top as f64,
+            width: (rect.right - rect.left) as f64,
+            height: (rect.bottom - rect.top) as f64,
+        })
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    None
+}
+
+/// Skipped when the window would end up off every connected display.
+/// Must be called on the main thread.
+pub(crate) fn restore(geometry: WindowGeometry) {
+    #[cfg(target_os = "macos")]
+    {
+        use objc2::MainThreadMarker;
+        use objc2_app_kit::NSScreen;
+        use objc2_foundation::{NSPoint, NSRect, NSSize};
+
+        let (Some(mtm), Some(window)) = (MainThreadMarker::new(), macos::window()) else {
+            return;
+        };
+        if geometry.width < 1.0 || geometry.height < 1.0 {
+            return;
+        }
+        let frame = NSRect::new(
+            NSPoint::new(geometry.x, geometry.y),
+            NSSize::new(geometry.width, geometry.height),
+        );
+        let on_screen = NSScreen::screens(mtm).iter().any(|screen| {
+            let screen = screen.frame();
+            frame.origin.x < screen.origin.x + screen.size.width
+                && screen.origin.x < frame.origin.x + frame.size.width
+                && frame.origin.y < screen.origin.y + screen.size.height
+                && screen.origin.y < frame.origin.y + frame.size.height
+        });
+        if on_screen {
+            window.setFrame_display(frame, true);
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows_sys::Win32::Foundation::RECT;
+        use windows_sys::Win32::Graphics::Gdi::{MONITOR_DEFAULTTONULL, MonitorFromRect};
+        use windows_sys::Win32::UI::WindowsAndMessaging::{
+            SWP_NOACTIVATE, SWP_NOZORDER, SetWindowPos,
+        };
+
+        let Some(hwnd) = windows::window() else {
+            return;
+        };
+        if geometry.width < 1.0 || geometry.height < 1.0 {
+            return;
+        }
+        let rect = RECT {
+            left: geometry.x as i32,
+            top: geometry.y as i32,
+            right: (geometry.x + geometry.width) as i32,
+            bottom: (geometry.y + geometry.height) as i32,
+        };
+        unsafe {
+            if MonitorFromRect(&rect, MONITOR_DEFAULTTONULL).is_null() {
+                return;
+            }
+            SetWindowPos(
+                hwnd,
+                std::ptr::null_mut(),
+                rect.left,
+                rect.top,
+                rect.right - rect.left,
+                rect.bottom - rect.top,
+                SWP_NOZORDER | SWP_NOACTIVATE,
+            );
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let _ = geometry;
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use objc2::MainThreadMarker;
+    use objc2::rc::Retained;
+    use objc2_app_kit::{NSApplication, NSWindow};
+
+    pub(super) fn window() -> Option<Retained<NSWindow>> {
+        let app = NSApplication::sharedApplication(MainThreadMarker::new()?);
+        app.mainWindow().or_else(|| app.windows().firstObject())
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) mod windows {
+    use windows_sys::Win32::Foundation::{HWND, LPARAM};
+    use windows_sys::Win32::System::Threading::GetCurrentThreadId;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        EnumThreadWindows, GW_OWNER, GWL_EXSTYLE, GetWindow, GetWindowLongW, IsWindowVisible,
+        WS_EX_TOOLWINDOW,
+    };
+
+    /// The idle-hue window. winit creates it on the main thread, so it's the one visible,
+    /// unowned top-level window there; the hidden windows winit and the IME keep on the
+    /// same thread are skipped. Must be called on the main thread.
+    pub(crate) fn window() -> Option<HWND> {
+        unsafe extern "system" fn find(hwnd: HWND, found: LPARAM) -> i32 {
+            let ours = unsafe {
+                IsWindowVisible(hwnd) != 0
+                    && GetWindow(hwnd, GW_OWNER).is_null()
+                    && GetWindowLongW(hwnd, GWL_EXSTYLE) as u32 & WS_EX_TOOLWINDOW == 0
+            };
+            if !ours {
+                return 1;
+            }
+            unsafe { *(found as *mut HWND) = hwnd };
+            0
+        }
+
+        let mut found: HWND = std::ptr::null_mut();
+        unsafe {
+            EnumThreadWindows(
+                GetCurrentThreadId(),
+                Some(find),
+                &mut found as *mut HWND as LPARAM,
+            );
+        }
+        (!found.is_null()).then_some(found)
+    }
+}